@@ -0,0 +1,229 @@
+use crate::{
+    events::FactoryEvents,
+    storage::{self, VaultInfo, VaultList},
+};
+
+use soroban_sdk::{contract, contractimpl, xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+/// The maximum number of vaults returned by a single registry query
+const MAX_VAULTS_PER_PAGE: u32 = 50;
+
+#[contract]
+pub struct FeeVaultFactory;
+
+#[contractimpl]
+impl FeeVaultFactory {
+    /// Initialize the factory
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin address, which can update the fee vault WASM
+    /// * `wasm_hash` - The hash of the uploaded fee vault WASM to deploy
+    pub fn __constructor(e: Env, admin: Address, wasm_hash: BytesN<32>) {
+        admin.require_auth();
+
+        storage::set_admin(&e, &admin);
+        storage::set_wasm_hash(&e, &wasm_hash);
+    }
+
+    //********** Read-Only ***********//
+
+    /// Get the factory's admin
+    ///
+    /// ### Returns
+    /// * `Address` - The admin address for the factory
+    pub fn get_admin(e: Env) -> Address {
+        storage::get_admin(&e)
+    }
+
+    /// Get the hash of the fee vault WASM deployed by the factory
+    ///
+    /// ### Returns
+    /// * `BytesN<32>` - The WASM hash
+    pub fn get_wasm_hash(e: Env) -> BytesN<32> {
+        storage::get_wasm_hash(&e)
+    }
+
+    /// Fetch the address a vault will be deployed to
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin of the vault
+    /// * `salt` - The salt used to deploy the vault
+    ///
+    /// ### Returns
+    /// * `Address` - The address of the vault
+    pub fn get_vault_address(e: Env, admin: Address, salt: BytesN<32>) -> Address {
+        e.deployer()
+            .with_current_contract(vault_salt(&e, &admin, &salt))
+            .deployed_address()
+    }
+
+    /// Fetch the registry entry for a vault
+    ///
+    /// ### Arguments
+    /// * `vault` - The address of the vault
+    ///
+    /// ### Returns
+    /// * `Option<VaultInfo>` - The pool, asset and admin the vault was deployed with, or None if
+    ///   the vault was not deployed by the factory
+    pub fn get_vault(e: Env, vault: Address) -> Option<VaultInfo> {
+        storage::get_vault_info(&e, &vault)
+    }
+
+    /// Fetch a page of the vaults deployed for a pool
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    /// * `start` - The index of the first vault to return
+    /// * `limit` - The maximum number of vaults to return, capped at 50
+    ///
+    /// ### Returns
+    /// * `Vec<Address>` - The vaults deployed for the pool, in deployment order. Contains fewer than
+    ///   `limit` vaults once the end of the list is reached.
+    pub fn get_vaults_by_pool(e: Env, pool: Address, start: u32, limit: u32) -> Vec<Address> {
+        load_vault_page(&e, &VaultList::Pool(pool), start, limit)
+    }
+
+    /// Fetch a page of the vaults deployed for an asset
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the asset
+    /// * `start` - The index of the first vault to return
+    /// * `limit` - The maximum number of vaults to return, capped at 50
+    ///
+    /// ### Returns
+    /// * `Vec<Address>` - The vaults deployed for the asset, in deployment order. Contains fewer than
+    ///   `limit` vaults once the end of the list is reached.
+    pub fn get_vaults_by_asset(e: Env, asset: Address, start: u32, limit: u32) -> Vec<Address> {
+        load_vault_page(&e, &VaultList::Asset(asset), start, limit)
+    }
+
+    /// Fetch a page of the vaults deployed with an admin. The registry records the admin a vault
+    /// was deployed with, and is not updated if the vault's admin changes afterwards, so vaults
+    /// that have since transferred their admin are still listed under the admin they were
+    /// deployed with. Use the vault's `get_admin` for its current admin.
+    ///
+    /// ### Arguments
+    /// * `admin` - The address of the admin
+    /// * `start` - The index of the first vault to return
+    /// * `limit` - The maximum number of vaults to return, capped at 50
+    ///
+    /// ### Returns
+    /// * `Vec<Address>` - The vaults deployed with the admin, in deployment order. Contains fewer
+    ///   than `limit` vaults once the end of the list is reached.
+    pub fn get_vaults_by_admin(e: Env, admin: Address, start: u32, limit: u32) -> Vec<Address> {
+        load_vault_page(&e, &VaultList::Admin(admin), start, limit)
+    }
+
+    //********** Read-Write Admin Only ***********//
+
+    /// ADMIN ONLY
+    /// Sets the admin address for the factory. Requires a signature from both the current admin
+    /// and the new admin address.
+    ///
+    /// ### Arguments
+    /// * `admin` - The new admin address to set
+    pub fn set_admin(e: Env, admin: Address) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+        admin.require_auth();
+        storage::set_admin(&e, &admin);
+    }
+
+    /// ADMIN ONLY
+    /// Sets the fee vault WASM deployed by the factory. Vaults that are already deployed are
+    /// not affected.
+    ///
+    /// ### Arguments
+    /// * `wasm_hash` - The hash of the uploaded fee vault WASM to deploy
+    pub fn set_wasm_hash(e: Env, wasm_hash: BytesN<32>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+        storage::set_wasm_hash(&e, &wasm_hash);
+    }
+
+    //********** Read-Write ***********//
+
+    /// Deploys a new fee vault and records it in the registry. The vault's address is derived from
+    /// the admin and the salt, so it can be computed ahead of time with `get_vault_address`.
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin address of the vault
+    /// * `pool` - The blend pool address the vault will deposit into
+    /// * `asset` - The asset address of the reserve the vault will support
+    /// * `rate_type` - The rate type the vault will use
+    /// * `rate` - The rate value, with 7 decimals (e.g. 1000000 for 10%)
+    /// * `signer` - The signer address for the vault, or None if no signer is used
+    /// * `salt` - The salt used to deploy the vault
+    ///
+    /// ### Returns
+    /// * `Address` - The address of the deployed vault
+    ///
+    /// ### Panics
+    /// * If a vault was already deployed with the same admin and salt
+    /// * If the vault's constructor panics
+    pub fn deploy(
+        e: Env,
+        admin: Address,
+        pool: Address,
+        asset: Address,
+        rate_type: u32,
+        rate: u32,
+        signer: Option<Address>,
+        salt: BytesN<32>,
+    ) -> Address {
+        storage::extend_instance(&e);
+        admin.require_auth();
+
+        let vault = e
+            .deployer()
+            .with_current_contract(vault_salt(&e, &admin, &salt))
+            .deploy_v2(
+                storage::get_wasm_hash(&e),
+                (
+                    admin.clone(),
+                    pool.clone(),
+                    asset.clone(),
+                    rate_type,
+                    rate,
+                    signer,
+                ),
+            );
+
+        storage::set_vault_info(
+            &e,
+            &vault,
+            &VaultInfo {
+                pool: pool.clone(),
+                asset: asset.clone(),
+                admin: admin.clone(),
+            },
+        );
+        storage::push_vault_list(&e, &VaultList::Pool(pool.clone()), &vault);
+        storage::push_vault_list(&e, &VaultList::Asset(asset.clone()), &vault);
+        storage::push_vault_list(&e, &VaultList::Admin(admin.clone()), &vault);
+
+        FactoryEvents::vault_deployed(&e, &pool, &asset, &admin, &vault, rate_type, rate);
+        vault
+    }
+}
+
+/// Derive the deployment salt for a vault from its admin and the provided salt, so vault
+/// addresses can't be claimed by other deployers
+fn vault_salt(e: &Env, admin: &Address, salt: &BytesN<32>) -> BytesN<32> {
+    let mut salt_bytes: Bytes = admin.clone().to_xdr(e);
+    salt_bytes.append(&Bytes::from(salt.clone()));
+    e.crypto().keccak256(&salt_bytes).to_bytes()
+}
+
+/// Load up to `limit` vaults from a list in the registry, starting at index `start`
+fn load_vault_page(e: &Env, list: &VaultList, start: u32, limit: u32) -> Vec<Address> {
+    let end =
+        storage::get_vault_count(e, list).min(start.saturating_add(limit.min(MAX_VAULTS_PER_PAGE)));
+    let mut vaults = Vec::new(e);
+    for index in start..end {
+        if let Some(vault) = storage::get_vault_at(e, list, index) {
+            vaults.push_back(vault);
+        }
+    }
+    vaults
+}
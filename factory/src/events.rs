@@ -0,0 +1,27 @@
+use soroban_sdk::{Address, Env, Symbol};
+
+pub struct FactoryEvents {}
+
+impl FactoryEvents {
+    /// Emitted when a fee vault is deployed by the factory
+    ///
+    /// - topics - `["vault_deployed", pool: Address, asset: Address, admin: Address]`
+    /// - data - `[vault: Address, rate_type: u32, rate: u32]`
+    pub fn vault_deployed(
+        e: &Env,
+        pool: &Address,
+        asset: &Address,
+        admin: &Address,
+        vault: &Address,
+        rate_type: u32,
+        rate: u32,
+    ) {
+        let topics = (
+            Symbol::new(&e, "vault_deployed"),
+            pool.clone(),
+            asset.clone(),
+            admin.clone(),
+        );
+        e.events().publish(topics, (vault.clone(), rate_type, rate));
+    }
+}
@@ -0,0 +1,15 @@
+#![no_std]
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+
+pub mod contract;
+pub mod events;
+pub mod storage;
+
+pub use contract::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,187 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, BytesN, Env, Symbol};
+
+//********** Storage Keys **********//
+
+const ADMIN_KEY: &str = "Admin";
+const WASM_HASH_KEY: &str = "WasmHash";
+
+#[derive(Clone)]
+#[contracttype]
+pub enum FactoryDataKey {
+    Vault(Address),
+    VaultCount(VaultList),
+    VaultAt(VaultListIndex),
+}
+
+/// A list of vaults in the registry
+#[derive(Clone)]
+#[contracttype]
+pub enum VaultList {
+    // The vaults deployed for a pool
+    Pool(Address),
+    // The vaults deployed for an asset
+    Asset(Address),
+    // The vaults deployed with an admin
+    Admin(Address),
+}
+
+/// The key for a vault at an index of a list of vaults in the registry
+#[derive(Clone)]
+#[contracttype]
+pub struct VaultListIndex {
+    pub list: VaultList,
+    pub index: u32,
+}
+
+/// The registry entry for a deployed vault
+#[derive(Clone)]
+#[contracttype]
+pub struct VaultInfo {
+    // The pool the vault supplies to
+    pub pool: Address,
+    // The asset the vault holds
+    pub asset: Address,
+    // The admin the vault was deployed with
+    pub admin: Address,
+}
+
+//********** Storage Utils **********//
+
+pub const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5 seconds per ledger on average
+
+const LEDGER_BUMP_SHARED: u32 = 31 * ONE_DAY_LEDGERS;
+const LEDGER_THRESHOLD_SHARED: u32 = LEDGER_BUMP_SHARED - ONE_DAY_LEDGERS;
+
+const LEDGER_BUMP_REGISTRY: u32 = 120 * ONE_DAY_LEDGERS;
+const LEDGER_THRESHOLD_REGISTRY: u32 = LEDGER_BUMP_REGISTRY - 20 * ONE_DAY_LEDGERS;
+
+/// Bump the instance lifetime by the defined amount
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/********** Instance **********/
+
+/// Get the admin address
+pub fn get_admin(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, ADMIN_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the admin address
+pub fn set_admin(e: &Env, admin: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, ADMIN_KEY), admin);
+}
+
+/// Get the hash of the fee vault WASM deployed by the factory
+pub fn get_wasm_hash(e: &Env) -> BytesN<32> {
+    e.storage()
+        .instance()
+        .get::<Symbol, BytesN<32>>(&Symbol::new(e, WASM_HASH_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the hash of the fee vault WASM deployed by the factory
+pub fn set_wasm_hash(e: &Env, wasm_hash: &BytesN<32>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, BytesN<32>>(&Symbol::new(e, WASM_HASH_KEY), wasm_hash);
+}
+
+/********** Persistent **********/
+
+/// Get the registry entry for a vault. Returns None if the vault was not deployed by the factory.
+pub fn get_vault_info(e: &Env, vault: &Address) -> Option<VaultInfo> {
+    let key = FactoryDataKey::Vault(vault.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FactoryDataKey, VaultInfo>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_REGISTRY, LEDGER_BUMP_REGISTRY);
+    }
+    result
+}
+
+/// Set the registry entry for a vault
+pub fn set_vault_info(e: &Env, vault: &Address, info: &VaultInfo) {
+    let key = FactoryDataKey::Vault(vault.clone());
+    e.storage()
+        .persistent()
+        .set::<FactoryDataKey, VaultInfo>(&key, info);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_REGISTRY, LEDGER_BUMP_REGISTRY);
+}
+
+/// Get the number of vaults in a list in the registry. Returns 0 if no vaults are registered
+/// in `list`.
+pub fn get_vault_count(e: &Env, list: &VaultList) -> u32 {
+    let key = FactoryDataKey::VaultCount(list.clone());
+    let result = e.storage().persistent().get::<FactoryDataKey, u32>(&key);
+    match result {
+        Some(count) => {
+            e.storage().persistent().extend_ttl(
+                &key,
+                LEDGER_THRESHOLD_REGISTRY,
+                LEDGER_BUMP_REGISTRY,
+            );
+            count
+        }
+        None => 0,
+    }
+}
+
+/// Get the vault at an index of a list in the registry. Returns None if the index is out of bounds.
+pub fn get_vault_at(e: &Env, list: &VaultList, index: u32) -> Option<Address> {
+    let key = FactoryDataKey::VaultAt(VaultListIndex {
+        list: list.clone(),
+        index,
+    });
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FactoryDataKey, Address>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_REGISTRY, LEDGER_BUMP_REGISTRY);
+    }
+    result
+}
+
+/// Add a vault to the end of a list in the registry. Each vault is stored under its own key, so
+/// the cost of adding a vault does not grow with the size of the list.
+pub fn push_vault_list(e: &Env, list: &VaultList, vault: &Address) {
+    let index = get_vault_count(e, list);
+    let vault_key = FactoryDataKey::VaultAt(VaultListIndex {
+        list: list.clone(),
+        index,
+    });
+    e.storage()
+        .persistent()
+        .set::<FactoryDataKey, Address>(&vault_key, vault);
+    e.storage().persistent().extend_ttl(
+        &vault_key,
+        LEDGER_THRESHOLD_REGISTRY,
+        LEDGER_BUMP_REGISTRY,
+    );
+
+    let count_key = FactoryDataKey::VaultCount(list.clone());
+    e.storage()
+        .persistent()
+        .set::<FactoryDataKey, u32>(&count_key, &(index + 1));
+    e.storage().persistent().extend_ttl(
+        &count_key,
+        LEDGER_THRESHOLD_REGISTRY,
+        LEDGER_BUMP_REGISTRY,
+    );
+}
@@ -0,0 +1,223 @@
+#![cfg(test)]
+
+use crate::testutils::register_factory;
+use crate::FeeVaultFactoryClient;
+use blend_contract_sdk::testutils::BlendFixture;
+use fee_vault_v2::testutils::{create_blend_pool, EnvTestUtils};
+use fee_vault_v2::FeeVaultClient;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{
+    Address as _, AuthorizedFunction, AuthorizedInvocation, BytesN as _, Events,
+};
+use soroban_sdk::{vec, Address, BytesN, Env, IntoVal, Symbol};
+
+#[test]
+fn test_deploy_vaults() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let radagast = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+
+    let factory = register_factory(&e, &bombadil);
+    let factory_client = FeeVaultFactoryClient::new(&e, &factory);
+    assert_eq!(factory_client.get_admin(), bombadil);
+
+    /*
+     * Deploy a vault
+     */
+    let salt = BytesN::<32>::random(&e);
+    let expected_address = factory_client.get_vault_address(&gandalf, &salt);
+    let usdc_vault = factory_client.deploy(&gandalf, &pool, &usdc, &0, &100_0000, &None, &salt);
+    assert_eq!(usdc_vault, expected_address);
+    assert_eq!(e.auths()[0].0, gandalf);
+    assert_eq!(
+        e.auths()[0].1.function,
+        AuthorizedFunction::Contract((
+            factory.clone(),
+            Symbol::new(&e, "deploy"),
+            vec![
+                &e,
+                gandalf.into_val(&e),
+                pool.into_val(&e),
+                usdc.into_val(&e),
+                0_u32.into_val(&e),
+                100_0000_u32.into_val(&e),
+                Option::<Address>::None.into_val(&e),
+                salt.into_val(&e),
+            ]
+        ))
+    );
+
+    // -> the vault_deployed event is emitted by the factory
+    let factory_events: std::vec::Vec<_> = e
+        .events()
+        .all()
+        .iter()
+        .filter(|event| event.0 == factory)
+        .collect();
+    assert_eq!(factory_events.len(), 1);
+    assert_eq!(
+        factory_events[0].1,
+        (
+            Symbol::new(&e, "vault_deployed"),
+            pool.clone(),
+            usdc.clone(),
+            gandalf.clone()
+        )
+            .into_val(&e)
+    );
+    let event_data: (Address, u32, u32) = factory_events[0].2.into_val(&e);
+    assert_eq!(event_data, (usdc_vault.clone(), 0, 100_0000));
+
+    // -> the vault is configured and functional
+    let vault_client = FeeVaultClient::new(&e, &usdc_vault);
+    assert_eq!(vault_client.get_admin(), gandalf);
+    assert_eq!(vault_client.get_config(), (pool.clone(), usdc.clone()));
+    assert_eq!(vault_client.get_fee().take_rate, Some(100_0000));
+    usdc_client.mint(&frodo, &100_0000000);
+    let shares = vault_client.deposit(&frodo, &100_0000000);
+    assert!(shares > 0);
+
+    /*
+     * Deploy more vaults
+     */
+    let xlm_vault = factory_client.deploy(
+        &gandalf,
+        &pool,
+        &xlm,
+        &1,
+        &500_0000,
+        &None,
+        &BytesN::<32>::random(&e),
+    );
+    // -> the same salt can be used by a different admin
+    let radagast_vault = factory_client.deploy(
+        &radagast,
+        &pool,
+        &usdc,
+        &0,
+        &200_0000,
+        &Some(frodo.clone()),
+        &salt,
+    );
+    assert_ne!(radagast_vault, usdc_vault);
+
+    /*
+     * Query the registry
+     */
+    assert_eq!(
+        factory_client.get_vaults_by_pool(&pool, &0, &10),
+        vec![
+            &e,
+            usdc_vault.clone(),
+            xlm_vault.clone(),
+            radagast_vault.clone()
+        ]
+    );
+    assert_eq!(
+        factory_client.get_vaults_by_asset(&usdc, &0, &10),
+        vec![&e, usdc_vault.clone(), radagast_vault.clone()]
+    );
+    assert_eq!(
+        factory_client.get_vaults_by_asset(&xlm, &0, &10),
+        vec![&e, xlm_vault.clone()]
+    );
+    assert_eq!(
+        factory_client.get_vaults_by_admin(&gandalf, &0, &10),
+        vec![&e, usdc_vault.clone(), xlm_vault.clone()]
+    );
+    assert_eq!(
+        factory_client.get_vaults_by_admin(&radagast, &0, &10),
+        vec![&e, radagast_vault.clone()]
+    );
+    assert_eq!(factory_client.get_vaults_by_admin(&frodo, &0, &10).len(), 0);
+
+    // -> the registry can be paged through
+    assert_eq!(
+        factory_client.get_vaults_by_pool(&pool, &0, &2),
+        vec![&e, usdc_vault.clone(), xlm_vault.clone()]
+    );
+    assert_eq!(
+        factory_client.get_vaults_by_pool(&pool, &2, &2),
+        vec![&e, radagast_vault.clone()]
+    );
+    assert_eq!(factory_client.get_vaults_by_pool(&pool, &3, &2).len(), 0);
+    assert_eq!(factory_client.get_vaults_by_pool(&pool, &1, &0).len(), 0);
+
+    // -> the registry records the admin a vault was deployed with
+    let vault_client = FeeVaultClient::new(&e, &xlm_vault);
+    vault_client.propose_admin(&radagast);
+    vault_client.accept_admin();
+    assert_eq!(vault_client.get_admin(), radagast);
+    assert_eq!(
+        factory_client.get_vaults_by_admin(&gandalf, &0, &10),
+        vec![&e, usdc_vault.clone(), xlm_vault.clone()]
+    );
+    assert_eq!(factory_client.get_vault(&xlm_vault).unwrap().admin, gandalf);
+
+    let info = factory_client.get_vault(&xlm_vault).unwrap();
+    assert_eq!(info.pool, pool);
+    assert_eq!(info.asset, xlm);
+    assert_eq!(info.admin, gandalf);
+    assert!(factory_client.get_vault(&frodo).is_none());
+
+    // -> deploying twice with the same admin and salt fails
+    let result = factory_client.try_deploy(&gandalf, &pool, &usdc, &0, &100_0000, &None, &salt);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_set_wasm_hash() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+
+    let factory = register_factory(&e, &bombadil);
+    let factory_client = FeeVaultFactoryClient::new(&e, &factory);
+
+    let new_wasm_hash = BytesN::<32>::random(&e);
+    factory_client.set_wasm_hash(&new_wasm_hash);
+    assert_eq!(
+        e.auths()[0],
+        (
+            bombadil.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    factory.clone(),
+                    Symbol::new(&e, "set_wasm_hash"),
+                    vec![&e, new_wasm_hash.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(factory_client.get_wasm_hash(), new_wasm_hash);
+
+    factory_client.set_admin(&gandalf);
+    assert_eq!(e.auths().len(), 2);
+    assert_eq!(factory_client.get_admin(), gandalf);
+}
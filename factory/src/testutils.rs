@@ -0,0 +1,17 @@
+use crate::FeeVaultFactory;
+use soroban_sdk::{Address, Env};
+
+/// The fee vault WASM deployed by the factory. Run `make build` before testing so the WASM matches
+/// the current source.
+pub const FEE_VAULT_WASM: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../target/wasm32v1-none/release/fee_vault_v2.wasm"
+));
+
+/// Register a factory that deploys the fee vault WASM
+///
+/// Returns the factory address
+pub fn register_factory(e: &Env, admin: &Address) -> Address {
+    let wasm_hash = e.deployer().upload_contract_wasm(FEE_VAULT_WASM);
+    e.register(FeeVaultFactory {}, (admin.clone(), wasm_hash))
+}
@@ -2,5 +2,21 @@
 pub const SCALAR_7: i128 = 1_0000000;
 /// 1 with 12 decimal places
 pub const SCALAR_12: i128 = 1_000_000_000_000;
+/// 1 in basis points
+pub const SCALAR_BPS: i128 = 10_000;
+// the maximum entry or exit fee, in basis points (10%)
+pub const MAX_TRANSACTION_FEE: u32 = 1_000;
 // seconds per year
 pub const SECONDS_PER_YEAR: i128 = 31536000;
+// the maximum number of reward tokens a vault can distribute
+pub const MAX_REWARD_TOKENS: u32 = 5;
+// the maximum number of fee recipients a vault can split fees between
+pub const MAX_FEE_RECIPIENTS: u32 = 10;
+// the maximum number of take rate tiers a fee policy can have
+pub const MAX_TAKE_RATE_TIERS: u32 = 10;
+// the maximum number of take rate bands a fee policy can have
+pub const MAX_TAKE_RATE_BANDS: u32 = 10;
+// the number of seconds a proposed admin has to accept the role (7 days)
+pub const PENDING_ADMIN_EXPIRATION: u64 = 7 * 24 * 60 * 60;
+// the storage layout version used by the contract code
+pub const STORAGE_VERSION: u32 = 1;
@@ -1,15 +1,27 @@
 use crate::{
+    constants::{PENDING_ADMIN_EXPIRATION, STORAGE_VERSION},
     errors::FeeVaultError,
     events::FeeVaultEvents,
-    pool,
+    migration, pool,
     rewards::{self, load_updated_reward_data},
-    storage::{self, RewardData, UserRewards},
-    summary::VaultSummary,
-    validator::{require_positive, require_valid_fee},
+    storage::{
+        self, FeeRecipient, PauseState, PendingAdmin, PendingFee, PendingFeeDelay, RewardData,
+        Role, UserRewards,
+    },
+    summary::{UserSummary, VaultSummary},
+    token,
+    validator::{
+        require_admin_or_guardian, require_before_deadline, require_claims_allowed,
+        require_deposits_allowed, require_nonnegative, require_positive, require_valid_fee,
+        require_valid_fee_recipients, require_withdrawals_allowed,
+    },
     vault::{self, VaultData},
 };
 
-use soroban_sdk::{contract, contractimpl, Address, Env, Vec};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token::TokenClient, token::TokenInterface, Address,
+    BytesN, Env, MuxedAddress, String, Vec,
+};
 
 #[contract]
 pub struct FeeVault;
@@ -26,12 +38,15 @@ impl FeeVault {
     ///     * 0 = take rate (admin earns a percentage of the vault's earnings)
     ///     * 1 = capped rate (vault earns at most the APR cap, with any additional returns going to the admin)
     ///     * 2 = fixed rate (vault always earns the fixed rate, with the admin either supplementing or earning the difference)
+    ///     * 3 = management fee (admin earns an annualized percentage of the vault's deposits, regardless of the interest earned)
+    ///     * 4 = performance fee (admin earns a percentage of the vault's earnings above the high-water mark share price)
+    ///     * 5 = floor rate (vault earns at least the floor rate, supplemented by the admin, and keeps any additional returns)
     /// * `rate` - The rate value, with 7 decimals (e.g. 1000000 for 10%)
     /// * `signer`- The signer address if the vault is permissioned, None otherwise
     ///
     /// ### Panics
     /// * `InvalidFeeRate` - If the value is not within 0 and 1_000_0000
-    /// * `InvalidFeeRateType` - If the rate type is not 0, 1, or 2
+    /// * `InvalidFeeRateType` - If the rate type is not 0, 1, 2, 3, 4, or 5
     pub fn __constructor(
         e: Env,
         admin: Address,
@@ -47,12 +62,13 @@ impl FeeVault {
         storage::set_pool(&e, pool.clone());
         storage::set_asset(&e, asset.clone());
 
-        let fee = storage::Fee { rate_type, rate };
+        let fee = storage::Fee::from_rate_type(&e, rate_type, rate);
         require_valid_fee(&e, &fee);
         storage::set_fee(&e, fee);
         if let Some(signer) = signer {
             storage::set_signer(&e, signer);
         }
+        storage::set_storage_version(&e, STORAGE_VERSION);
         storage::set_vault_data(
             &e,
             &VaultData {
@@ -61,6 +77,10 @@ impl FeeVault {
                 total_shares: 0,
                 total_b_tokens: 0,
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             },
         );
     }
@@ -117,10 +137,125 @@ impl FeeVault {
         }
     }
 
+    /// Preview the number of shares minted for depositing `amount` tokens at the current block, net
+    /// of the entry fee
+    ///
+    /// ### Arguments
+    /// * `amount` - The amount of tokens to deposit
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares that would be minted
+    pub fn preview_deposit(e: Env, amount: i128) -> i128 {
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        let vault = vault::get_vault_updated(&e, &pool, &asset);
+        let fee = storage::get_fee(&e);
+        vault.preview_deposit(fee.entry_fee, amount).1
+    }
+
+    /// Preview the number of tokens required to mint `shares` at the current block, including the
+    /// entry fee
+    ///
+    /// ### Arguments
+    /// * `shares` - The number of shares to mint
+    ///
+    /// ### Returns
+    /// * `i128` - The number of tokens that would be deposited
+    pub fn preview_mint(e: Env, shares: i128) -> i128 {
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        let vault = vault::get_vault_updated(&e, &pool, &asset);
+        let fee = storage::get_fee(&e);
+        vault.preview_mint(fee.entry_fee, shares).0
+    }
+
+    /// Preview the number of shares burnt for withdrawing `amount` tokens at the current block,
+    /// including the exit fee
+    ///
+    /// ### Arguments
+    /// * `amount` - The amount of tokens to withdraw
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares that would be burnt
+    pub fn preview_withdraw(e: Env, amount: i128) -> i128 {
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        let vault = vault::get_vault_updated(&e, &pool, &asset);
+        let fee = storage::get_fee(&e);
+        vault.preview_withdraw(fee.exit_fee, amount).1
+    }
+
+    /// Preview the number of tokens withdrawn for redeeming `shares` at the current block, net of
+    /// the exit fee
+    ///
+    /// ### Arguments
+    /// * `shares` - The number of shares to redeem
+    ///
+    /// ### Returns
+    /// * `i128` - The number of tokens that would be withdrawn
+    pub fn preview_redeem(e: Env, shares: i128) -> i128 {
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        let vault = vault::get_vault_updated(&e, &pool, &asset);
+        let fee = storage::get_fee(&e);
+        vault.preview_redeem(fee.exit_fee, shares).0
+    }
+
+    /// Fetch the maximum amount of tokens a user can deposit, based on the reserve's supply cap
+    /// and the vault's deposit caps
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    ///
+    /// ### Returns
+    /// * `i128` - The maximum amount of tokens that can be deposited, or 0 if the reserve is disabled,
+    ///   deposits are paused, or a deposit cap has been reached
+    pub fn max_deposit(e: Env, user: Address) -> i128 {
+        let pause_state = storage::get_pause_state(&e);
+        if pause_state.deposits || pause_state.emergency {
+            return 0;
+        }
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        let capacity = pool::reserve_supply_capacity(&e, &pool, &asset);
+        let vault = vault::get_vault_updated(&e, &pool, &asset);
+        let user_shares = storage::get_vault_shares(&e, &user);
+        match vault::remaining_deposit_cap(&e, &vault, user_shares) {
+            Some(remaining) => capacity.min(remaining),
+            None => capacity,
+        }
+    }
+
+    /// Fetch the maximum amount of tokens a user can withdraw, based on their position and the
+    /// reserve's available liquidity
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    ///
+    /// ### Returns
+    /// * `i128` - The maximum amount of tokens that can be withdrawn, or 0 if the user has no shares
+    ///   or withdrawals are paused
+    pub fn max_withdraw(e: Env, user: Address) -> i128 {
+        let pause_state = storage::get_pause_state(&e);
+        if pause_state.withdrawals && !pause_state.emergency {
+            return 0;
+        }
+        let shares = storage::get_vault_shares(&e, &user);
+        if shares > 0 {
+            let pool = storage::get_pool(&e);
+            let asset = storage::get_asset(&e);
+            let vault = vault::get_vault_updated(&e, &pool, &asset);
+            let fee = storage::get_fee(&e);
+            let (underlying, _, _) = vault.preview_redeem(fee.exit_fee, shares);
+            underlying.min(pool::reserve_liquidity(&e, &pool, &asset))
+        } else {
+            0
+        }
+    }
+
     /// Fetch a user's rewards for a specific token. Does not update the user's rewards.
     ///
-    /// If the current claimable rewards is needed, it is recommended to simulate a claim
-    /// call to get the current claimable rewards.
+    /// If the current claimable rewards is needed, use `get_claimable_rewards`.
     ///
     /// ### Arguments
     /// * `user` - The address of the user
@@ -129,7 +264,22 @@ impl FeeVault {
     /// ### Returns
     /// * `Option<UserRewards>` - The user's rewards for the token, or None
     pub fn get_rewards(e: Env, user: Address, token: Address) -> Option<UserRewards> {
-        storage::get_user_rewards(&e, &user, &token)
+        // @dev the rewards module stores user rewards with the token as the first address
+        storage::get_user_rewards(&e, &token, &user)
+    }
+
+    /// Fetch the amount of a reward token the user can claim right now
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `token` - The address of the reward token
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of rewards the user can claim, or 0 if no rewards exist
+    pub fn get_claimable_rewards(e: Env, user: Address, token: Address) -> i128 {
+        let vault = storage::get_vault_data(&e);
+        let shares = storage::get_vault_shares(&e, &user);
+        rewards::load_claimable_rewards(&e, &token, vault.total_shares, &user, shares)
     }
 
     /// Fetch the admin balance in underlying tokens
@@ -143,6 +293,35 @@ impl FeeVault {
         vault.b_tokens_to_underlying_down(vault.admin_balance)
     }
 
+    /// Fetch a fee recipient's share of the fees in underlying tokens
+    ///
+    /// ### Arguments
+    /// * `recipient` - The address of the fee recipient
+    ///
+    /// ### Returns
+    /// * `i128` - The recipient's accrued fees in underlying tokens, or 0 if they have none
+    pub fn get_underlying_fee_balance(e: Env, recipient: Address) -> i128 {
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        let vault = vault::get_vault_updated(&e, &pool, &asset);
+        let b_tokens = vault.recipient_fee_balance(&e, &recipient);
+        vault.b_tokens_to_underlying_down(b_tokens)
+    }
+
+    /// Fetch a fee recipient's share of the fees in bTokens
+    ///
+    /// ### Arguments
+    /// * `recipient` - The address of the fee recipient
+    ///
+    /// ### Returns
+    /// * `i128` - The recipient's accrued fees in bTokens, or 0 if they have none
+    pub fn get_fee_balance(e: Env, recipient: Address) -> i128 {
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        let vault = vault::get_vault_updated(&e, &pool, &asset);
+        vault.recipient_fee_balance(&e, &recipient)
+    }
+
     /// Get the vault's blend pool it deposits into and the asset it supports.
     ///
     /// ### Returns
@@ -169,6 +348,49 @@ impl FeeVault {
         storage::get_fee(&e)
     }
 
+    /// Get the recipients the vault's fees are split between
+    ///
+    /// ### Returns
+    /// * `Vec<FeeRecipient>` - The fee recipients, or an empty vec if all fees accrue to the admin
+    pub fn get_fee_recipients(e: Env) -> Vec<FeeRecipient> {
+        storage::get_fee_recipients(&e)
+    }
+
+    /// Get the vault's storage layout version
+    ///
+    /// ### Returns
+    /// * `u32` - The storage layout version. If this is less than the version used by the contract
+    ///   code, `migrate` must be called.
+    pub fn get_storage_version(e: Env) -> u32 {
+        storage::get_storage_version(&e)
+    }
+
+    /// Get the vault's queued fee change
+    ///
+    /// ### Returns
+    /// * `Option<PendingFee>` - The queued fee and the timestamp it can be executed at, or None if
+    ///   no fee change is queued
+    pub fn get_pending_fee(e: Env) -> Option<PendingFee> {
+        storage::get_pending_fee(&e)
+    }
+
+    /// Get the minimum delay between queueing and executing a fee change
+    ///
+    /// ### Returns
+    /// * `u64` - The minimum delay, in seconds
+    pub fn get_fee_delay(e: Env) -> u64 {
+        storage::get_fee_delay(&e)
+    }
+
+    /// Get the vault's queued fee delay decrease
+    ///
+    /// ### Returns
+    /// * `Option<PendingFeeDelay>` - The queued fee delay and the timestamp it can be executed at, or
+    ///   None if no decrease is queued
+    pub fn get_pending_fee_delay(e: Env) -> Option<PendingFeeDelay> {
+        storage::get_pending_fee_delay(&e)
+    }
+
     /// Get the vault's admin
     ///
     /// ### Returns
@@ -177,6 +399,15 @@ impl FeeVault {
         storage::get_admin(&e)
     }
 
+    /// Get the vault's pending admin
+    ///
+    /// ### Returns
+    /// * `Option<PendingAdmin>` - The proposed admin and the expiration of the proposal, or None if
+    ///   no admin transfer has been proposed
+    pub fn get_pending_admin(e: Env) -> Option<PendingAdmin> {
+        storage::get_pending_admin(&e)
+    }
+
     /// Get the vault's signer
     ///
     /// ### Returns
@@ -185,12 +416,61 @@ impl FeeVault {
         storage::get_signer(&e)
     }
 
-    /// Get the current reward token for the fee vault
+    /// Get the address holding a role
+    ///
+    /// ### Arguments
+    /// * `role` - The role to fetch
+    ///
+    /// ### Returns
+    /// * `Option<Address>` - The address holding the role, or None if the role has not been granted.
+    ///   Roles other than the guardian that have not been granted are held by the admin.
+    pub fn get_role(e: Env, role: Role) -> Option<Address> {
+        storage::get_role(&e, role)
+    }
+
+    /// Get the vault's pause state
+    ///
+    /// ### Returns
+    /// * `PauseState` - The pause state of the vault
+    pub fn get_pause_state(e: Env) -> PauseState {
+        storage::get_pause_state(&e)
+    }
+
+    /// Get the deposit caps for the fee vault
+    ///
+    /// ### Returns
+    /// * `(Option<i128>, Option<i128>)` - (The cap on the vault's total underlying tokens, the cap on
+    ///   each user's underlying tokens). A cap of `None` means deposits are not capped.
+    pub fn get_deposit_caps(e: Env) -> (Option<i128>, Option<i128>) {
+        (
+            storage::get_deposit_cap(&e),
+            storage::get_user_deposit_cap(&e),
+        )
+    }
+
+    /// Get the primary reward token for the fee vault. This is the first reward token with active
+    /// rewards, or the first reward token if no rewards are active.
     ///
     /// ### Returns
-    /// * `Option<Address>` - The address of the reward token, or None if no reward token is set
+    /// * `Option<Address>` - The address of the reward token, or None if no rewards were set
     pub fn get_reward_token(e: Env) -> Option<Address> {
-        storage::get_reward_token(&e)
+        rewards::load_reward_token(&e)
+    }
+
+    /// Get the reward tokens for the fee vault
+    ///
+    /// ### Returns
+    /// * `Vec<Address>` - The addresses of the reward tokens, or an empty vector if no rewards were set
+    pub fn get_reward_tokens(e: Env) -> Vec<Address> {
+        storage::get_reward_tokens(&e)
+    }
+
+    /// Get the reward tokens that were removed from the fee vault, but can still be claimed
+    ///
+    /// ### Returns
+    /// * `Vec<Address>` - The addresses of the removed reward tokens
+    pub fn get_removed_reward_tokens(e: Env) -> Vec<Address> {
+        storage::get_removed_reward_tokens(&e)
     }
 
     /// Get the reward data for a specific token
@@ -217,10 +497,25 @@ impl FeeVault {
         VaultSummary::load(&e)
     }
 
+    /// NOT INTENDED FOR CONTRACT USE
+    ///
+    /// Get the user summary, which includes the user's shares, bTokens, underlying tokens, claimable
+    /// rewards, and share of the vault. Intended for use by dApps looking to fetch display data.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    ///
+    /// ### Returns
+    /// * `UserSummary` - The summary of the user's position
+    pub fn get_user_summary(e: Env, user: Address) -> UserSummary {
+        UserSummary::load(&e, &user)
+    }
+
     //********** Read-Write Admin Only ***********//
 
-    /// ADMIN ONLY
-    /// Sets the Fee mode for the fee vault
+    /// FEE MANAGER ONLY
+    /// Sets the fee policy for the fee vault to a preset rate type. Only allowed if the vault does not
+    /// have a fee delay, otherwise fee changes must be queued with `queue_set_fee`.
     ///
     /// ### Arguments
     /// * `e` - The environment object
@@ -228,27 +523,182 @@ impl FeeVault {
     ///     * 0 = take rate (admin earns a percentage of the vault's earnings)
     ///     * 1 = capped rate (vault earns at most the APR cap, with any additional returns going to the admin)
     ///     * 2 = fixed rate (vault always earns the fixed rate, with the admin either supplementing or earning the difference)
+    ///     * 3 = management fee (admin earns an annualized percentage of the vault's deposits, regardless of the interest earned)
+    ///     * 4 = performance fee (admin earns a percentage of the vault's earnings above the high-water mark share price)
+    ///     * 5 = floor rate (vault earns at least the floor rate, supplemented by the admin, and keeps any additional returns)
     /// * `rate` - The rate value, with 7 decimals (e.g. 1000000 for 10%)
     ///
     /// ### Panics
+    /// * `FeeTimelocked` - If the vault has a fee delay
     /// * `InvalidFeeRate` - If the value is not within 0 and 1_000_0000
-    /// * `InvalidFeeRateType` - If the rate type is not 0, 1, or 2
+    /// * `InvalidFeeRateType` - If the rate type is not 0, 1, 2, 3, 4, or 5
     pub fn set_fee(e: Env, rate_type: u32, rate: u32) {
         storage::extend_instance(&e);
-        storage::get_admin(&e).require_auth();
+        storage::get_role_or_admin(&e, Role::FeeManager).require_auth();
+        if storage::get_fee_delay(&e) > 0 {
+            panic_with_error!(&e, FeeVaultError::FeeTimelocked);
+        }
 
-        let fee = storage::Fee { rate_type, rate };
+        let fee = storage::Fee::from_rate_type(&e, rate_type, rate);
         require_valid_fee(&e, &fee);
+        update_fee(&e, fee);
+    }
 
-        // Accrue interest prior to updating the fee-mode, to avoid any retroactive effect
-        let pool = storage::get_pool(&e);
-        let asset = storage::get_asset(&e);
-        let vault = vault::get_vault_updated(&e, &pool, &asset);
-        storage::set_vault_data(&e, &vault);
+    /// FEE MANAGER ONLY
+    /// Sets the fee policy for the fee vault. Only allowed if the vault does not have a fee delay,
+    /// otherwise fee changes must be queued with `queue_set_fee_policy`.
+    ///
+    /// ### Arguments
+    /// * `fee` - The fee policy the vault will use
+    ///
+    /// ### Panics
+    /// * `FeeTimelocked` - If the vault has a fee delay
+    /// * `InvalidFeeRate` - If any rate is not within 0 and 1_000_0000, or the entry or exit fee exceeds 10%
+    /// * `InvalidFeePolicy` - If the APR floor exceeds the APR cap, a high-water mark or take rate tiers or bands
+    ///   are used without a take rate, the take rate tiers or bands are not ordered by increasing positive
+    ///   thresholds, or both take rate tiers and bands are used
+    pub fn set_fee_policy(e: Env, fee: storage::Fee) {
+        storage::extend_instance(&e);
+        storage::get_role_or_admin(&e, Role::FeeManager).require_auth();
+        if storage::get_fee_delay(&e) > 0 {
+            panic_with_error!(&e, FeeVaultError::FeeTimelocked);
+        }
 
-        storage::set_fee(&e, fee);
+        require_valid_fee(&e, &fee);
+        update_fee(&e, fee);
+    }
+
+    /// FEE MANAGER ONLY
+    /// Queues a fee change to a preset rate type for the fee vault. The fee change can be executed with
+    /// `execute_set_fee` once the vault's fee delay has passed. Replaces any existing queued fee change.
+    ///
+    /// ### Arguments
+    /// * `rate_type` - The rate type the vault will use
+    /// * `rate` - The rate value, with 7 decimals (e.g. 1000000 for 10%)
+    ///
+    /// ### Panics
+    /// * `InvalidFeeRate` - If the value is not within 0 and 1_000_0000
+    /// * `InvalidFeeRateType` - If the rate type is not 0, 1, 2, 3, 4, or 5
+    pub fn queue_set_fee(e: Env, rate_type: u32, rate: u32) {
+        storage::extend_instance(&e);
+        storage::get_role_or_admin(&e, Role::FeeManager).require_auth();
+
+        let fee = storage::Fee::from_rate_type(&e, rate_type, rate);
+        require_valid_fee(&e, &fee);
+        queue_fee(&e, fee);
+    }
+
+    /// FEE MANAGER ONLY
+    /// Queues a fee policy change for the fee vault. The fee change can be executed with `execute_set_fee`
+    /// once the vault's fee delay has passed. Replaces any existing queued fee change.
+    ///
+    /// ### Arguments
+    /// * `fee` - The fee policy the vault will use
+    ///
+    /// ### Panics
+    /// * `InvalidFeeRate` - If any rate is not within 0 and 1_000_0000, or the entry or exit fee exceeds 10%
+    /// * `InvalidFeePolicy` - If the APR floor exceeds the APR cap, a high-water mark or take rate tiers or bands
+    ///   are used without a take rate, the take rate tiers or bands are not ordered by increasing positive
+    ///   thresholds, or both take rate tiers and bands are used
+    pub fn queue_set_fee_policy(e: Env, fee: storage::Fee) {
+        storage::extend_instance(&e);
+        storage::get_role_or_admin(&e, Role::FeeManager).require_auth();
+
+        require_valid_fee(&e, &fee);
+        queue_fee(&e, fee);
+    }
+
+    /// FEE MANAGER ONLY
+    /// Executes the queued fee change for the fee vault
+    ///
+    /// ### Panics
+    /// * `NoPendingFee` - If no fee change is queued
+    /// * `FeeTimelocked` - If the fee change can not be executed yet
+    pub fn execute_set_fee(e: Env) {
+        storage::extend_instance(&e);
+        storage::get_role_or_admin(&e, Role::FeeManager).require_auth();
+
+        let pending_fee = match storage::get_pending_fee(&e) {
+            Some(pending_fee) => pending_fee,
+            None => panic_with_error!(&e, FeeVaultError::NoPendingFee),
+        };
+        if e.ledger().timestamp() < pending_fee.execute_after {
+            panic_with_error!(&e, FeeVaultError::FeeTimelocked);
+        }
+        storage::del_pending_fee(&e);
+        update_fee(&e, pending_fee.fee);
+    }
+
+    /// FEE MANAGER ONLY
+    /// Cancels the queued fee change for the fee vault
+    ///
+    /// ### Panics
+    /// * `NoPendingFee` - If no fee change is queued
+    pub fn cancel_set_fee(e: Env) {
+        storage::extend_instance(&e);
+        storage::get_role_or_admin(&e, Role::FeeManager).require_auth();
+
+        let pending_fee = match storage::get_pending_fee(&e) {
+            Some(pending_fee) => pending_fee,
+            None => panic_with_error!(&e, FeeVaultError::NoPendingFee),
+        };
+        storage::del_pending_fee(&e);
+
+        FeeVaultEvents::fee_cancelled(&e, &pending_fee.fee);
+    }
+
+    /// ADMIN ONLY
+    /// Sets the minimum delay between queueing and executing a fee change. A delay of 0 allows the
+    /// fee to be updated immediately with `set_fee`. Fee changes that are already queued keep their
+    /// original execution time.
+    ///
+    /// Increases to the delay take effect immediately and cancel any queued decrease. Decreases are
+    /// queued, and can be executed with `execute_set_fee_delay` once the current delay has passed.
+    ///
+    /// ### Arguments
+    /// * `delay` - The minimum delay, in seconds
+    pub fn set_fee_delay(e: Env, delay: u64) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
 
-        FeeVaultEvents::fee_update(&e, rate_type, rate);
+        let current_delay = storage::get_fee_delay(&e);
+        if delay >= current_delay {
+            storage::set_fee_delay(&e, delay);
+            storage::del_pending_fee_delay(&e);
+
+            FeeVaultEvents::fee_delay_update(&e, delay);
+        } else {
+            let pending_delay = PendingFeeDelay {
+                delay,
+                execute_after: e.ledger().timestamp() + current_delay,
+            };
+            storage::set_pending_fee_delay(&e, &pending_delay);
+
+            FeeVaultEvents::fee_delay_queued(&e, &pending_delay);
+        }
+    }
+
+    /// ADMIN ONLY
+    /// Executes the queued fee delay decrease for the fee vault
+    ///
+    /// ### Panics
+    /// * `NoPendingFee` - If no fee delay decrease is queued
+    /// * `FeeTimelocked` - If the fee delay decrease can not be executed yet
+    pub fn execute_set_fee_delay(e: Env) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let pending_delay = match storage::get_pending_fee_delay(&e) {
+            Some(pending_delay) => pending_delay,
+            None => panic_with_error!(&e, FeeVaultError::NoPendingFee),
+        };
+        if e.ledger().timestamp() < pending_delay.execute_after {
+            panic_with_error!(&e, FeeVaultError::FeeTimelocked);
+        }
+        storage::del_pending_fee_delay(&e);
+        storage::set_fee_delay(&e, pending_delay.delay);
+
+        FeeVaultEvents::fee_delay_update(&e, pending_delay.delay);
     }
 
     /// ADMIN ONLY
@@ -263,6 +713,73 @@ impl FeeVault {
         storage::get_admin(&e).require_auth();
         admin.require_auth();
         storage::set_admin(&e, admin);
+        storage::del_pending_admin(&e);
+    }
+
+    /// ADMIN ONLY
+    /// Proposes a new admin for the fee vault. The proposed admin must call `accept_admin` before
+    /// the proposal expires to become the admin. Replaces any existing proposal.
+    ///
+    /// ### Arguments
+    /// * `new_admin` - The proposed admin address
+    pub fn propose_admin(e: Env, new_admin: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let expiration = e.ledger().timestamp() + PENDING_ADMIN_EXPIRATION;
+        storage::set_pending_admin(
+            &e,
+            &PendingAdmin {
+                admin: new_admin.clone(),
+                expiration,
+            },
+        );
+
+        FeeVaultEvents::admin_proposed(&e, &admin, &new_admin, expiration);
+    }
+
+    /// Accepts a pending admin proposal, making the proposed admin the admin of the fee vault.
+    /// Requires a signature from the proposed admin.
+    ///
+    /// ### Panics
+    /// * `NoPendingAdmin` - If no admin transfer has been proposed
+    /// * `PendingAdminExpired` - If the proposal has expired
+    pub fn accept_admin(e: Env) {
+        storage::extend_instance(&e);
+        let pending_admin = match storage::get_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, FeeVaultError::NoPendingAdmin),
+        };
+        pending_admin.admin.require_auth();
+        if pending_admin.expiration < e.ledger().timestamp() {
+            panic_with_error!(&e, FeeVaultError::PendingAdminExpired);
+        }
+
+        let old_admin = storage::get_admin(&e);
+        storage::set_admin(&e, pending_admin.admin.clone());
+        storage::del_pending_admin(&e);
+
+        FeeVaultEvents::admin_accepted(&e, &old_admin, &pending_admin.admin);
+    }
+
+    /// ADMIN ONLY
+    /// Cancels a pending admin proposal
+    ///
+    /// ### Panics
+    /// * `NoPendingAdmin` - If no admin transfer has been proposed
+    pub fn cancel_admin_proposal(e: Env) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let pending_admin = match storage::get_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, FeeVaultError::NoPendingAdmin),
+        };
+        storage::del_pending_admin(&e);
+
+        FeeVaultEvents::admin_proposal_cancelled(&e, &admin, &pending_admin.admin);
     }
 
     /// ADMIN ONLY
@@ -286,9 +803,196 @@ impl FeeVault {
     }
 
     /// ADMIN ONLY
-    /// Claims emissions for the given reserves from the pool. This is a passthrough function
-    /// that invokes the pool's "claim" function as the contract. More details can be found
-    /// here: https://github.com/blend-capital/blend-contracts/blob/v1.0.0/pool/src/contract.rs#L192
+    /// Grants a role for the fee vault, replacing the current holder of the role. Requires a
+    /// signature from both the admin and the new role holder.
+    ///
+    /// ### Arguments
+    /// * `role` - The role to grant
+    ///     * 1 = fee manager (can update the vault's fee)
+    ///     * 2 = rewards manager (can set rewards for the vault's depositors)
+    ///     * 3 = treasury (can manage the admin balance and claim emissions)
+    ///     * 4 = guardian (can update the pause state alongside the admin)
+    /// * `address` - The address to grant the role to
+    ///
+    /// ### Panics
+    /// * `InvalidRole` - If the role is the owner. Use `set_admin` or `propose_admin` instead.
+    pub fn grant_role(e: Env, role: Role, address: Address) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+        if let Role::Owner = role {
+            panic_with_error!(&e, FeeVaultError::InvalidRole);
+        }
+        address.require_auth();
+        storage::set_role(&e, role, address.clone());
+
+        FeeVaultEvents::role_granted(&e, role, &address);
+    }
+
+    /// ADMIN ONLY
+    /// Revokes a role for the fee vault. Revoked roles other than the guardian are held by the admin.
+    ///
+    /// ### Arguments
+    /// * `role` - The role to revoke
+    ///
+    /// ### Panics
+    /// * `InvalidRole` - If the role is the owner
+    pub fn revoke_role(e: Env, role: Role) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+        if let Role::Owner = role {
+            panic_with_error!(&e, FeeVaultError::InvalidRole);
+        }
+        storage::del_role(&e, role);
+
+        FeeVaultEvents::role_revoked(&e, role);
+    }
+
+    /// ADMIN ONLY
+    /// Upgrades the fee vault's contract code. If the new contract code uses a newer storage layout,
+    /// `migrate` must be called after the upgrade.
+    ///
+    /// ### Arguments
+    /// * `new_wasm_hash` - The hash of the uploaded WASM to upgrade to
+    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        e.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        FeeVaultEvents::vault_upgrade(&e, &admin, &new_wasm_hash);
+    }
+
+    /// ADMIN ONLY
+    /// Migrates the fee vault's storage to the layout version used by the contract code
+    ///
+    /// ### Panics
+    /// * `AlreadyMigrated` - If the storage already uses the latest layout version
+    pub fn migrate(e: Env) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let from_version = migration::migrate(&e);
+
+        FeeVaultEvents::vault_migrate(&e, &admin, from_version, STORAGE_VERSION);
+    }
+
+    /// ADMIN ONLY
+    /// Sets the recipients the vault's fees are split between. Each recipient is credited their weight
+    /// of all fees accrued from now on, and any weight not assigned to a recipient accrues to the admin.
+    /// Fees already credited to a recipient remain withdrawable by them if they are removed.
+    ///
+    /// ### Arguments
+    /// * `recipients` - The fee recipients, with weights in basis points (e.g. 2500 for 25%)
+    ///
+    /// ### Panics
+    /// * `InvalidFeeRecipients` - If there are too many recipients, a recipient is repeated or has no weight,
+    ///                            or the weights sum to more than 10000
+    pub fn set_fee_recipients(e: Env, recipients: Vec<FeeRecipient>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+        require_valid_fee_recipients(&e, &recipients);
+
+        // Accrue interest and split the accrued fees prior to updating the fee split, to avoid any
+        // retroactive effect
+        vault::split_fees(&e, &storage::get_pool(&e), &storage::get_asset(&e));
+
+        storage::set_fee_recipients(&e, &recipients);
+
+        FeeVaultEvents::fee_recipients_update(&e, &recipients);
+    }
+
+    /// ADMIN ONLY
+    /// Sets the deposit caps for the fee vault. Deposits that would push the vault's total
+    /// underlying tokens, or a user's underlying tokens, above the respective cap are rejected.
+    ///
+    /// Passing `None` as a cap will remove that cap.
+    ///
+    /// ### Arguments
+    /// * `total_cap` - The cap on the vault's total underlying tokens, excluding the admin balance
+    /// * `user_cap` - The cap on each user's underlying tokens
+    ///
+    /// ### Panics
+    /// * `NegativeAmountError` - If a cap is negative
+    pub fn set_deposit_caps(e: Env, total_cap: Option<i128>, user_cap: Option<i128>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        if let Some(cap) = total_cap {
+            require_nonnegative(&e, cap);
+            storage::set_deposit_cap(&e, cap);
+        } else {
+            storage::del_deposit_cap(&e);
+        }
+        if let Some(cap) = user_cap {
+            require_nonnegative(&e, cap);
+            storage::set_user_deposit_cap(&e, cap);
+        } else {
+            storage::del_user_deposit_cap(&e);
+        }
+
+        FeeVaultEvents::deposit_caps_update(&e, total_cap, user_cap);
+    }
+
+    /// ADMIN OR GUARDIAN ONLY
+    /// Pauses or unpauses deposits, withdrawals, and reward claims for the fee vault.
+    ///
+    /// ### Arguments
+    /// * `caller` - The address of the admin or guardian
+    /// * `deposits` - Whether deposits are paused
+    /// * `withdrawals` - Whether withdrawals are paused. Ignored if the vault is in emergency mode.
+    /// * `claims` - Whether reward claims are paused
+    ///
+    /// ### Panics
+    /// * `UnauthorizedError` - If the caller is not the admin or guardian
+    pub fn set_pause_state(
+        e: Env,
+        caller: Address,
+        deposits: bool,
+        withdrawals: bool,
+        claims: bool,
+    ) {
+        storage::extend_instance(&e);
+        caller.require_auth();
+        require_admin_or_guardian(&e, &caller);
+
+        let mut pause_state = storage::get_pause_state(&e);
+        pause_state.deposits = deposits;
+        pause_state.withdrawals = withdrawals;
+        pause_state.claims = claims;
+        storage::set_pause_state(&e, &pause_state);
+
+        FeeVaultEvents::pause_update(&e, &caller, &pause_state);
+    }
+
+    /// ADMIN OR GUARDIAN ONLY
+    /// Enters or exits emergency mode for the fee vault. While in emergency mode, only withdrawals
+    /// are allowed, regardless of the pause state, so users can always exit the vault.
+    ///
+    /// ### Arguments
+    /// * `caller` - The address of the admin or guardian
+    /// * `emergency` - Whether the vault is in emergency mode
+    ///
+    /// ### Panics
+    /// * `UnauthorizedError` - If the caller is not the admin or guardian
+    pub fn set_emergency(e: Env, caller: Address, emergency: bool) {
+        storage::extend_instance(&e);
+        caller.require_auth();
+        require_admin_or_guardian(&e, &caller);
+
+        let mut pause_state = storage::get_pause_state(&e);
+        pause_state.emergency = emergency;
+        storage::set_pause_state(&e, &pause_state);
+
+        FeeVaultEvents::pause_update(&e, &caller, &pause_state);
+    }
+
+    /// TREASURY ONLY
+    /// Claims emissions for the given reserves from the pool. This is a passthrough function
+    /// that invokes the pool's "claim" function as the contract. More details can be found
+    /// here: https://github.com/blend-capital/blend-contracts/blob/v1.0.0/pool/src/contract.rs#L192
     ///
     /// ### Arguments
     /// * `reserve_token_ids` - The ids of the reserves to claiming emissions for
@@ -298,23 +1002,23 @@ impl FeeVault {
     /// * `i128` - The amount of blnd tokens claimed
     pub fn claim_emissions(e: Env, reserve_token_ids: Vec<u32>, to: Address) -> i128 {
         storage::extend_instance(&e);
-        let admin = storage::get_admin(&e);
-        admin.require_auth();
+        let treasury = storage::get_role_or_admin(&e, Role::Treasury);
+        treasury.require_auth();
         let pool = storage::get_pool(&e);
         let emissions = pool::claim(&e, &pool, &reserve_token_ids, &to);
 
-        FeeVaultEvents::vault_emissions_claim(&e, &pool, &admin, reserve_token_ids, emissions);
+        FeeVaultEvents::vault_emissions_claim(&e, &pool, &treasury, reserve_token_ids, emissions);
         emissions
     }
 
-    /// ADMIN ONLY
-    /// Deposit tokens into the vault's admin balance
+    /// TREASURY ONLY
+    /// Deposit tokens into the vault's admin balance. Any deficit owed to the vault is repaid first.
     ///
     /// ### Arguments
     /// * `amount` - The amount of tokens to deposit
     ///
     /// ### Returns
-    /// * `i128` - The number of b_tokens minted
+    /// * `i128` - The number of b_tokens minted, including any used to repay the deficit
     ///
     /// ### Panics
     /// * `InvalidAmount` - If the amount is less than or equal to 0
@@ -322,20 +1026,28 @@ impl FeeVault {
     /// * `BalanceError` - If the user does not have enough tokens
     pub fn admin_deposit(e: Env, amount: i128) -> i128 {
         storage::extend_instance(&e);
-        let admin = storage::get_admin(&e);
-        admin.require_auth();
+        let treasury = storage::get_role_or_admin(&e, Role::Treasury);
+        treasury.require_auth();
         require_positive(&e, amount, FeeVaultError::InvalidAmount);
 
         let pool = storage::get_pool(&e);
         let asset = storage::get_asset(&e);
-        pool::supply(&e, &pool, &asset, &admin, amount);
-        let b_tokens_minted = vault::admin_deposit(&e, &pool, &asset, amount);
+        pool::supply(&e, &pool, &asset, &treasury, amount);
+        let (credited_b_tokens, repaid_b_tokens) = vault::admin_deposit(&e, &pool, &asset, amount);
 
-        FeeVaultEvents::vault_admin_deposit(&e, &pool, &asset, &admin, amount, b_tokens_minted);
-        b_tokens_minted
+        FeeVaultEvents::vault_admin_deposit(
+            &e,
+            &pool,
+            &asset,
+            &treasury,
+            amount,
+            credited_b_tokens,
+            repaid_b_tokens,
+        );
+        credited_b_tokens + repaid_b_tokens
     }
 
-    /// ADMIN ONLY
+    /// TREASURY ONLY
     /// Withdraw tokens from the vault's admin balance
     ///
     /// ### Arguments
@@ -346,51 +1058,129 @@ impl FeeVault {
     ///
     /// ### Panics
     /// * `InvalidAmount` - If the amount is less than or equal to 0
-    /// * `BalanceError` - If the user does not have enough shares to withdraw the amount
+    /// * `BalanceError` - If the admin balance, excluding the fee recipients' balances, is not enough to withdraw the amount
     /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
     pub fn admin_withdraw(e: Env, amount: i128) -> i128 {
         storage::extend_instance(&e);
-        let admin = storage::get_admin(&e);
-        admin.require_auth();
+        let treasury = storage::get_role_or_admin(&e, Role::Treasury);
+        treasury.require_auth();
         require_positive(&e, amount, FeeVaultError::InvalidAmount);
 
         let pool = storage::get_pool(&e);
         let asset = storage::get_asset(&e);
-        pool::withdraw(&e, &pool, &asset, &admin, amount);
+        pool::withdraw(&e, &pool, &asset, &treasury, amount);
         let b_tokens_burnt = vault::admin_withdraw(&e, &pool, &asset, amount);
 
-        FeeVaultEvents::vault_admin_withdraw(&e, &pool, &asset, &admin, amount, b_tokens_burnt);
+        FeeVaultEvents::vault_admin_withdraw(&e, &pool, &asset, &treasury, amount, b_tokens_burnt);
         b_tokens_burnt
     }
 
-    /// ADMIN ONLY
+    /// Withdraw tokens from a fee recipient's share of the fees
+    ///
+    /// ### Arguments
+    /// * `recipient` - The fee recipient withdrawing their fees
+    /// * `amount` - The amount of underlying tokens to withdraw
+    ///
+    /// ### Returns
+    /// * `i128` - The number of b_tokens burnt
+    ///
+    /// ### Panics
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `BalanceError` - If the recipient does not have enough fees to withdraw the amount
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    pub fn withdraw_fees(e: Env, recipient: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        recipient.require_auth();
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+
+        let pool = storage::get_pool(&e);
+        let asset = storage::get_asset(&e);
+        pool::withdraw(&e, &pool, &asset, &recipient, amount);
+        let b_tokens_burnt = vault::withdraw_fees(&e, &pool, &asset, &recipient, amount);
+
+        FeeVaultEvents::vault_fee_withdraw(&e, &pool, &asset, &recipient, amount, b_tokens_burnt);
+        b_tokens_burnt
+    }
+
+    /// REWARDS MANAGER ONLY
     /// Sets rewards to be distributed to the fee vault depositors. The full `reward_amount` will be
     /// transferred to the vault to be distributed to the users until the `expiration` timestamp.
     ///
+    /// Multiple reward tokens can be distributed at the same time. If rewards for `token` are
+    /// already active, the new rewards are added to the remaining rewards.
+    ///
     /// ### Arguments
     /// * `token` - The address of the reward token
     /// * `reward_amount` - The amount of rewards to distribute
     /// * `expiration` - The timestamp when the rewards expire
     ///
     /// ### Panics
-    /// * `InvalidRewardConfig` - If the reward token cannot be changed, or if a valid reward period cannot be started
-    /// * `BalanceError` - If the admin does not have enough tokens to set the rewards
+    /// * `InvalidRewardConfig` - If a valid reward period cannot be started, or if `token` was removed
+    /// * `TooManyRewardTokens` - If `token` is new and the vault already has the maximum number of reward tokens
+    /// * `BalanceError` - If the rewards manager does not have enough tokens to set the rewards
     pub fn set_rewards(e: Env, token: Address, reward_amount: i128, expiration: u64) {
         storage::extend_instance(&e);
-        let admin = storage::get_admin(&e);
-        admin.require_auth();
+        let rewards_manager = storage::get_role_or_admin(&e, Role::RewardsManager);
+        rewards_manager.require_auth();
 
         let vault = storage::get_vault_data(&e);
         rewards::set_rewards(
             &e,
-            &admin,
+            &rewards_manager,
             vault.total_shares,
             &token,
             reward_amount,
             expiration,
         );
 
-        FeeVaultEvents::vault_rewards_set(&e, &admin, &token, reward_amount, expiration);
+        FeeVaultEvents::vault_rewards_set(&e, &rewards_manager, &token, reward_amount, expiration);
+    }
+
+    /// REWARDS MANAGER ONLY
+    /// Removes an expired reward token from the fee vault, so a new reward token can be added in its
+    /// place once the maximum number of reward tokens is reached.
+    ///
+    /// Users can still claim all the rewards accrued on the removed token, until its remaining balance
+    /// is swept with `sweep_reward_token`. A removed token can't be distributed again.
+    ///
+    /// ### Arguments
+    /// * `token` - The address of the reward token to remove
+    ///
+    /// ### Panics
+    /// * `NoRewardsConfigured` - If `token` is not one of the vault's reward tokens
+    /// * `InvalidRewardConfig` - If the rewards for `token` have not expired
+    /// * `TooManyRewardTokens` - If the vault already has the maximum number of removed reward tokens
+    pub fn remove_reward_token(e: Env, token: Address) {
+        storage::extend_instance(&e);
+        storage::get_role_or_admin(&e, Role::RewardsManager).require_auth();
+
+        let vault = storage::get_vault_data(&e);
+        rewards::remove_reward_token(&e, vault.total_shares, &token);
+
+        FeeVaultEvents::vault_reward_token_removed(&e, &token);
+    }
+
+    /// REWARDS MANAGER ONLY
+    /// Sweeps the remaining balance of a removed reward token from the fee vault. Any rewards users
+    /// have not claimed are forfeited, and the token can no longer be claimed.
+    ///
+    /// ### Arguments
+    /// * `token` - The address of the removed reward token
+    /// * `to` - The address to send the remaining balance to
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of tokens swept
+    ///
+    /// ### Panics
+    /// * `NoRewardsConfigured` - If `token` is not one of the vault's removed reward tokens
+    pub fn sweep_reward_token(e: Env, token: Address, to: Address) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_role_or_admin(&e, Role::RewardsManager).require_auth();
+
+        let amount = rewards::sweep_reward_token(&e, &token, &to);
+
+        FeeVaultEvents::vault_reward_token_swept(&e, &token, &to, amount);
+        amount
     }
 
     //********** Read-Write ***********//
@@ -406,6 +1196,8 @@ impl FeeVault {
     /// * `i128` - The number of shares minted for the user
     ///
     /// ### Panics
+    /// * `Paused` - If deposits are paused or the vault is in emergency mode
+    /// * `DepositCapExceeded` - If the deposit would exceed the vault or user deposit cap
     /// * `InvalidAmount` - If the amount is less than or equal to 0
     /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
     /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
@@ -417,23 +1209,125 @@ impl FeeVault {
             signer.require_auth();
         }
 
-        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+        execute_deposit(&e, &user, &user, amount)
+    }
+
+    /// Deposits tokens from `from` into the fee vault for a specific reserve, and credits the minted
+    /// shares to `receiver`. Requires the signer to sign the transaction if the signer is set.
+    ///
+    /// ### Arguments
+    /// * `from` - The address the tokens are deposited from
+    /// * `receiver` - The address receiving the minted shares
+    /// * `amount` - The amount of tokens to deposit
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares minted for the receiver
+    ///
+    /// ### Panics
+    /// * `Paused` - If deposits are paused or the vault is in emergency mode
+    /// * `DepositCapExceeded` - If the deposit would exceed the vault or user deposit cap
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
+    /// * `BalanceError` - If `from` does not have enough tokens
+    pub fn deposit_for(e: Env, from: Address, receiver: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        from.require_auth();
+        if let Some(signer) = storage::get_signer(&e) {
+            signer.require_auth();
+        }
+
+        execute_deposit(&e, &from, &receiver, amount)
+    }
+
+    /// Deposits tokens into the fee vault for a specific reserve, and verifies at least `min_shares`
+    /// are minted. Requires the signer to sign the transaction if the signer is set.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user making the deposit
+    /// * `amount` - The amount of tokens to deposit
+    /// * `min_shares` - The minimum number of shares that must be minted for the user
+    /// * `deadline` - The last timestamp the deposit can be executed at
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares minted for the user
+    ///
+    /// ### Panics
+    /// * `Paused` - If deposits are paused or the vault is in emergency mode
+    /// * `DepositCapExceeded` - If the deposit would exceed the vault or user deposit cap
+    /// * `DeadlineExpired` - If the ledger timestamp is past the deadline
+    /// * `SlippageExceeded` - If less than `min_shares` shares are minted
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
+    /// * `BalanceError` - If the user does not have enough tokens
+    pub fn deposit_with_min_shares(
+        e: Env,
+        user: Address,
+        amount: i128,
+        min_shares: i128,
+        deadline: u64,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        if let Some(signer) = storage::get_signer(&e) {
+            signer.require_auth();
+        }
+        require_before_deadline(&e, deadline);
+
+        let new_shares = execute_deposit(&e, &user, &user, amount);
+        if new_shares < min_shares {
+            panic_with_error!(&e, FeeVaultError::SlippageExceeded);
+        }
+        new_shares
+    }
+
+    /// Mints an exact amount of shares from the fee vault for a specific reserve. Requires the signer
+    /// to sign the transaction if the signer is set.
+    ///
+    /// The amount of tokens pulled from the user is rounded up in favor of the vault.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user minting shares
+    /// * `shares` - The amount of shares to mint
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of tokens deposited by the user
+    ///
+    /// ### Panics
+    /// * `Paused` - If deposits are paused or the vault is in emergency mode
+    /// * `DepositCapExceeded` - If the deposit would exceed the vault or user deposit cap
+    /// * `InvalidAmount` - If the amount of shares is less than or equal to 0
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    /// * `BalanceError` - If the user does not have enough tokens
+    pub fn mint(e: Env, user: Address, shares: i128) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        if let Some(signer) = storage::get_signer(&e) {
+            signer.require_auth();
+        }
+
+        require_deposits_allowed(&e);
+        require_positive(&e, shares, FeeVaultError::InvalidAmount);
 
         let pool = storage::get_pool(&e);
         let asset = storage::get_asset(&e);
+        let (amount, b_tokens_minted, fee_b_tokens) = vault::mint(&e, &pool, &asset, &user, shares);
         pool::supply(&e, &pool, &asset, &user, amount);
-        let (b_tokens_minted, new_shares) = vault::deposit(&e, &pool, &asset, &user, amount);
 
         FeeVaultEvents::vault_deposit(
             &e,
             &pool,
             &asset,
             &user,
+            &user,
             amount,
-            new_shares,
+            shares,
             b_tokens_minted,
+            fee_b_tokens,
         );
-        new_shares
+        FeeVaultEvents::mint(&e, &user, shares);
+        amount
     }
 
     /// Withdraws tokens from the fee vault for a specific reserve. If the input amount is greater
@@ -447,33 +1341,124 @@ impl FeeVault {
     /// * `i128` - The number of shares burnt
     ///
     /// ### Panics
+    /// * `Paused` - If withdrawals are paused and the vault is not in emergency mode
     /// * `InvalidAmount` - If the amount is less than or equal to 0
     /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
     /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
     pub fn withdraw(e: Env, user: Address, amount: i128) -> i128 {
         storage::extend_instance(&e);
         user.require_auth();
-        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+        execute_withdraw(&e, &user, &user, amount)
+    }
+
+    /// Withdraws tokens from the user's position in the fee vault for a specific reserve, and sends
+    /// them to `receiver`. If the input amount is greater than the user's underlying balance, the
+    /// user's full balance will be withdrawn.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user making the withdrawal
+    /// * `receiver` - The address receiving the withdrawn tokens
+    /// * `amount` - The amount of tokens to withdraw
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares burnt
+    ///
+    /// ### Panics
+    /// * `Paused` - If withdrawals are paused and the vault is not in emergency mode
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn withdraw_to(e: Env, user: Address, receiver: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+
+        execute_withdraw(&e, &user, &receiver, amount)
+    }
+
+    /// Withdraws tokens from the fee vault for a specific reserve, and verifies at most `max_shares`
+    /// are burnt. If the input amount is greater than the user's underlying balance, the user's full
+    /// balance will be withdrawn.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user making the withdrawal
+    /// * `amount` - The amount of tokens to withdraw
+    /// * `max_shares` - The maximum number of shares that can be burnt from the user
+    /// * `deadline` - The last timestamp the withdrawal can be executed at
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares burnt
+    ///
+    /// ### Panics
+    /// * `Paused` - If withdrawals are paused and the vault is not in emergency mode
+    /// * `DeadlineExpired` - If the ledger timestamp is past the deadline
+    /// * `SlippageExceeded` - If more than `max_shares` shares are burnt
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn withdraw_with_max_shares(
+        e: Env,
+        user: Address,
+        amount: i128,
+        max_shares: i128,
+        deadline: u64,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_before_deadline(&e, deadline);
+
+        let burnt_shares = execute_withdraw(&e, &user, &user, amount);
+        if burnt_shares > max_shares {
+            panic_with_error!(&e, FeeVaultError::SlippageExceeded);
+        }
+        burnt_shares
+    }
+
+    /// Redeems an exact amount of shares from the fee vault for a specific reserve.
+    ///
+    /// The amount of tokens sent to the user is rounded down in favor of the vault.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user redeeming shares
+    /// * `shares` - The amount of shares to redeem
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of tokens withdrawn to the user
+    ///
+    /// ### Panics
+    /// * `Paused` - If withdrawals are paused and the vault is not in emergency mode
+    /// * `InvalidAmount` - If the amount of shares is less than or equal to 0
+    /// * `BalanceError` - If the user does not have enough shares
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn redeem(e: Env, user: Address, shares: i128) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_withdrawals_allowed(&e);
+        require_positive(&e, shares, FeeVaultError::InvalidAmount);
 
         let pool = storage::get_pool(&e);
         let asset = storage::get_asset(&e);
-        let (withdraw_amount, b_tokens_burnt, burnt_shares) =
-            vault::withdraw(&e, &pool, &asset, &user, amount);
-        pool::withdraw(&e, &pool, &asset, &user, withdraw_amount);
+        let (amount, b_tokens_burnt, fee_b_tokens) =
+            vault::redeem(&e, &pool, &asset, &user, shares);
+        pool::withdraw(&e, &pool, &asset, &user, amount);
 
         FeeVaultEvents::vault_withdraw(
             &e,
             &pool,
             &asset,
             &user,
+            &user,
             amount,
-            burnt_shares,
+            shares,
             b_tokens_burnt,
+            fee_b_tokens,
         );
-        burnt_shares
+        FeeVaultEvents::burn(&e, &user, shares);
+        amount
     }
 
-    /// Claims rewards for the user from the fee vault.
+    /// Claims rewards of a reward token for the user from the fee vault. Rewards of historical reward
+    /// tokens that are no longer distributed by the vault can still be claimed.
     ///
     /// ### Arguments
     /// * `user` - The address of the user claiming rewards
@@ -484,17 +1469,319 @@ impl FeeVault {
     /// * `i128` - The amount of rewards claimed
     ///
     /// ### Panics
+    /// * `Paused` - If reward claims are paused or the vault is in emergency mode
     /// * `NoRewardsConfigured` - If no rewards are configured for the token
     pub fn claim_rewards(e: Env, user: Address, reward_token: Address, to: Address) -> i128 {
         storage::extend_instance(&e);
         user.require_auth();
+        require_claims_allowed(&e);
 
         let vault = storage::get_vault_data(&e);
         let shares = storage::get_vault_shares(&e, &user);
 
-        let claimed_rewards = rewards::claim_rewards(&e, vault.total_shares, &user, shares, &to);
+        let claimed_rewards =
+            rewards::claim_rewards(&e, vault.total_shares, &user, shares, &reward_token, &to);
 
         FeeVaultEvents::vault_rewards_claim(&e, &user, &reward_token, claimed_rewards);
         claimed_rewards
     }
+
+    /// Claims rewards of every reward token for the user from the fee vault, including the reward
+    /// tokens that were removed but can still be claimed.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user claiming rewards
+    /// * `to` - The address to send the claimed rewards to
+    ///
+    /// ### Returns
+    /// * `Vec<i128>` - The amount of rewards claimed for each reward token, in the same order as
+    ///   `get_reward_tokens` followed by `get_removed_reward_tokens`
+    ///
+    /// ### Panics
+    /// * `Paused` - If reward claims are paused or the vault is in emergency mode
+    pub fn claim_all_rewards(e: Env, user: Address, to: Address) -> Vec<i128> {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_claims_allowed(&e);
+
+        let vault = storage::get_vault_data(&e);
+        let shares = storage::get_vault_shares(&e, &user);
+
+        let mut reward_tokens = storage::get_reward_tokens(&e);
+        reward_tokens.append(&storage::get_removed_reward_tokens(&e));
+        let mut claimed = Vec::new(&e);
+        for reward_token in reward_tokens.iter() {
+            let claimed_rewards =
+                rewards::claim_rewards(&e, vault.total_shares, &user, shares, &reward_token, &to);
+            FeeVaultEvents::vault_rewards_claim(&e, &user, &reward_token, claimed_rewards);
+            claimed.push_back(claimed_rewards);
+        }
+        claimed
+    }
+}
+
+/// SEP-41 token interface over the vault's shares. Shares have the same decimals as the underlying
+/// asset.
+#[contractimpl]
+impl TokenInterface for FeeVault {
+    /// Fetch the number of shares `spender` can spend on behalf of `from`
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user that granted the allowance
+    /// * `spender` - The address of the spender
+    fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        token::get_allowance(&e, &from, &spender)
+    }
+
+    /// Allow `spender` to transfer or burn up to `amount` of `from`'s shares
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user granting the allowance
+    /// * `spender` - The address of the spender
+    /// * `amount` - The number of shares the spender can spend
+    /// * `expiration_ledger` - The ledger sequence number the allowance expires at
+    ///
+    /// ### Panics
+    /// * `NegativeAmountError` - If the amount is negative
+    /// * `AllowanceError` - If the amount is positive and the expiration ledger has already passed
+    fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        storage::extend_instance(&e);
+        from.require_auth();
+        require_nonnegative(&e, amount);
+
+        token::approve(&e, &from, &spender, amount, expiration_ledger);
+
+        FeeVaultEvents::approve(&e, &from, &spender, amount, expiration_ledger);
+    }
+
+    /// Fetch a user's position in shares
+    ///
+    /// ### Arguments
+    /// * `id` - The address of the user
+    fn balance(e: Env, id: Address) -> i128 {
+        storage::get_vault_shares(&e, &id)
+    }
+
+    /// Transfer shares from `from` to `to`. Requires the signer to sign the transaction if the
+    /// signer is set.
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user sending shares
+    /// * `to` - The address of the user receiving shares
+    /// * `amount` - The number of shares to transfer
+    ///
+    /// ### Panics
+    /// * `NegativeAmountError` - If the amount is negative
+    /// * `Paused` - If deposits are paused or the vault is in emergency mode
+    /// * `BalanceError` - If `from` does not have enough shares
+    /// * `DepositCapExceeded` - If `to` would hold more than the per-user deposit cap
+    fn transfer(e: Env, from: Address, to: MuxedAddress, amount: i128) {
+        storage::extend_instance(&e);
+        from.require_auth();
+        if let Some(signer) = storage::get_signer(&e) {
+            signer.require_auth();
+        }
+        require_nonnegative(&e, amount);
+        require_deposits_allowed(&e);
+
+        let to_address = to.address();
+        vault::transfer(
+            &e,
+            &storage::get_pool(&e),
+            &storage::get_asset(&e),
+            &from,
+            &to_address,
+            amount,
+        );
+
+        FeeVaultEvents::transfer(&e, &from, &to_address, to.id(), amount);
+    }
+
+    /// Transfer shares from `from` to `to` using the allowance granted to `spender`. Requires the
+    /// signer to sign the transaction if the signer is set.
+    ///
+    /// ### Arguments
+    /// * `spender` - The address spending the allowance
+    /// * `from` - The address of the user sending shares
+    /// * `to` - The address of the user receiving shares
+    /// * `amount` - The number of shares to transfer
+    ///
+    /// ### Panics
+    /// * `NegativeAmountError` - If the amount is negative
+    /// * `Paused` - If deposits are paused or the vault is in emergency mode
+    /// * `AllowanceError` - If the spender's allowance is insufficient or expired
+    /// * `BalanceError` - If `from` does not have enough shares
+    /// * `DepositCapExceeded` - If `to` would hold more than the per-user deposit cap
+    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        if let Some(signer) = storage::get_signer(&e) {
+            signer.require_auth();
+        }
+        require_nonnegative(&e, amount);
+        require_deposits_allowed(&e);
+
+        token::spend_allowance(&e, &from, &spender, amount);
+        vault::transfer(
+            &e,
+            &storage::get_pool(&e),
+            &storage::get_asset(&e),
+            &from,
+            &to,
+            amount,
+        );
+
+        FeeVaultEvents::transfer(&e, &from, &to, None, amount);
+    }
+
+    /// Burn shares from `from`. The underlying tokens backing the burnt shares are not withdrawn,
+    /// and are distributed to the remaining share holders.
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user burning shares
+    /// * `amount` - The number of shares to burn
+    ///
+    /// ### Panics
+    /// * `NegativeAmountError` - If the amount is negative
+    /// * `BalanceError` - If `from` does not have enough shares
+    /// * `InvalidSharesBurnt` - If the burn would leave underlying tokens in the vault with no shares
+    fn burn(e: Env, from: Address, amount: i128) {
+        storage::extend_instance(&e);
+        from.require_auth();
+        require_nonnegative(&e, amount);
+
+        vault::burn(
+            &e,
+            &storage::get_pool(&e),
+            &storage::get_asset(&e),
+            &from,
+            amount,
+        );
+
+        FeeVaultEvents::burn(&e, &from, amount);
+    }
+
+    /// Burn shares from `from` using the allowance granted to `spender`. The underlying tokens
+    /// backing the burnt shares are not withdrawn, and are distributed to the remaining share holders.
+    ///
+    /// ### Arguments
+    /// * `spender` - The address spending the allowance
+    /// * `from` - The address of the user burning shares
+    /// * `amount` - The number of shares to burn
+    ///
+    /// ### Panics
+    /// * `NegativeAmountError` - If the amount is negative
+    /// * `AllowanceError` - If the spender's allowance is insufficient or expired
+    /// * `BalanceError` - If `from` does not have enough shares
+    /// * `InvalidSharesBurnt` - If the burn would leave underlying tokens in the vault with no shares
+    fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        require_nonnegative(&e, amount);
+
+        token::spend_allowance(&e, &from, &spender, amount);
+        vault::burn(
+            &e,
+            &storage::get_pool(&e),
+            &storage::get_asset(&e),
+            &from,
+            amount,
+        );
+
+        FeeVaultEvents::burn(&e, &from, amount);
+    }
+
+    /// Fetch the number of decimals of the shares, which matches the underlying asset
+    fn decimals(e: Env) -> u32 {
+        TokenClient::new(&e, &storage::get_asset(&e)).decimals()
+    }
+
+    /// Fetch the name of the shares (e.g. "Fee Vault USDC")
+    fn name(e: Env) -> String {
+        token::name(&e, &storage::get_asset(&e))
+    }
+
+    /// Fetch the symbol of the shares (e.g. "fvUSDC")
+    fn symbol(e: Env) -> String {
+        token::symbol(&e, &storage::get_asset(&e))
+    }
+}
+
+/// Update the vault's fee, and emit the fee update event. The fee must be validated by the caller.
+fn update_fee(e: &Env, fee: storage::Fee) {
+    // Accrue interest prior to updating the fee-mode, to avoid any retroactive effect
+    let pool = storage::get_pool(e);
+    let asset = storage::get_asset(e);
+    let vault = vault::get_vault_updated(e, &pool, &asset);
+    storage::set_vault_data(e, &vault);
+
+    storage::set_fee(e, fee.clone());
+
+    FeeVaultEvents::fee_update(e, &fee);
+}
+
+/// Queue a fee change after the vault's fee delay, and emit the fee queued event. The fee must be
+/// validated by the caller.
+fn queue_fee(e: &Env, fee: storage::Fee) {
+    let pending_fee = PendingFee {
+        fee,
+        execute_after: e.ledger().timestamp() + storage::get_fee_delay(e),
+    };
+    storage::set_pending_fee(e, &pending_fee);
+
+    FeeVaultEvents::fee_queued(e, &pending_fee);
+}
+
+/// Deposit tokens from `from` into the vault, credit the minted shares to `receiver`, and emit the
+/// deposit event. Auth must be verified by the caller.
+fn execute_deposit(e: &Env, from: &Address, receiver: &Address, amount: i128) -> i128 {
+    require_deposits_allowed(e);
+    require_positive(e, amount, FeeVaultError::InvalidAmount);
+
+    let pool = storage::get_pool(e);
+    let asset = storage::get_asset(e);
+    pool::supply(e, &pool, &asset, from, amount);
+    let (b_tokens_minted, new_shares, fee_b_tokens) =
+        vault::deposit(e, &pool, &asset, receiver, amount);
+
+    FeeVaultEvents::vault_deposit(
+        e,
+        &pool,
+        &asset,
+        from,
+        receiver,
+        amount,
+        new_shares,
+        b_tokens_minted,
+        fee_b_tokens,
+    );
+    FeeVaultEvents::mint(e, receiver, new_shares);
+    new_shares
+}
+
+/// Withdraw tokens from the user's vault position to `receiver`, and emit the withdraw event. Auth
+/// must be verified by the caller.
+fn execute_withdraw(e: &Env, user: &Address, receiver: &Address, amount: i128) -> i128 {
+    require_withdrawals_allowed(e);
+    require_positive(e, amount, FeeVaultError::InvalidAmount);
+
+    let pool = storage::get_pool(e);
+    let asset = storage::get_asset(e);
+    let (withdraw_amount, b_tokens_burnt, burnt_shares, fee_b_tokens) =
+        vault::withdraw(e, &pool, &asset, user, amount);
+    pool::withdraw(e, &pool, &asset, receiver, withdraw_amount);
+
+    FeeVaultEvents::vault_withdraw(
+        e,
+        &pool,
+        &asset,
+        user,
+        receiver,
+        amount,
+        burnt_shares,
+        b_tokens_burnt,
+        fee_b_tokens,
+    );
+    FeeVaultEvents::burn(e, user, burnt_shares);
+    burnt_shares
 }
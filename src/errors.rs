@@ -6,6 +6,9 @@ use soroban_sdk::contracterror;
 #[repr(u32)]
 pub enum FeeVaultError {
     // Default errors to align with built-in contract
+    UnauthorizedError = 4,
+    NegativeAmountError = 8,
+    AllowanceError = 9,
     BalanceError = 10,
 
     ReserveNotFound = 100,
@@ -21,4 +24,17 @@ pub enum FeeVaultError {
     NoRewardsConfigured = 110,
     InvalidRewardConfig = 111,
     InvalidSharesBurnt = 112,
+    SlippageExceeded = 113,
+    DeadlineExpired = 114,
+    TooManyRewardTokens = 115,
+    Paused = 116,
+    DepositCapExceeded = 117,
+    NoPendingAdmin = 118,
+    PendingAdminExpired = 119,
+    InvalidRole = 120,
+    NoPendingFee = 121,
+    FeeTimelocked = 122,
+    AlreadyMigrated = 123,
+    InvalidFeeRecipients = 124,
+    InvalidFeePolicy = 125,
 }
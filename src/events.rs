@@ -1,42 +1,55 @@
-use soroban_sdk::{Address, Env, Symbol, Vec};
+use soroban_sdk::{map, Address, BytesN, Env, IntoVal, Map, Symbol, Val, Vec};
+
+use crate::storage::{Fee, FeeRecipient, PauseState, PendingFee, PendingFeeDelay, Role};
 
 pub struct FeeVaultEvents {}
 
 impl FeeVaultEvents {
-    /// Emitted when a deposit is performed against the vault
+    /// Emitted when a deposit is performed against the vault. `from` is the address that supplied
+    /// the tokens, and `receiver` is the address credited with the minted shares.
     ///
-    /// - topics - `["vault_deposit", pool: Address, reserve: Address, from: Address]`
-    /// - data - `[amount: i128, shares: i128, b_tokens: i128]`
+    /// - topics - `["vault_deposit", pool: Address, reserve: Address, receiver: Address]`
+    /// - data - `[from: Address, amount: i128, shares: i128, b_tokens: i128, fee_b_tokens: i128]`
+    #[allow(clippy::too_many_arguments)]
     pub fn vault_deposit(
         e: &Env,
         pool: &Address,
         reserve: &Address,
         from: &Address,
+        receiver: &Address,
         amount: i128,
         shares: i128,
         b_tokens: i128,
+        fee_b_tokens: i128,
     ) {
         let topics = (
             Symbol::new(&e, "vault_deposit"),
             pool.clone(),
             reserve.clone(),
-            from.clone(),
+            receiver.clone(),
+        );
+        e.events().publish(
+            topics,
+            (from.clone(), amount, shares, b_tokens, fee_b_tokens),
         );
-        e.events().publish(topics, (amount, shares, b_tokens));
     }
 
-    /// Emitted when a withdraw is performed against the vault
+    /// Emitted when a withdraw is performed against the vault. `from` is the address the shares
+    /// are burnt from, and `to` is the address that received the withdrawn tokens.
     ///
     /// - topics - `["vault_withdraw", pool: Address, reserve: Address, from: Address]`
-    /// - data - `[amount: i128, shares: i128, b_tokens: i128]`
+    /// - data - `[to: Address, amount: i128, shares: i128, b_tokens: i128, fee_b_tokens: i128]`
+    #[allow(clippy::too_many_arguments)]
     pub fn vault_withdraw(
         e: &Env,
         pool: &Address,
         reserve: &Address,
         from: &Address,
+        to: &Address,
         amount: i128,
         shares: i128,
         b_tokens: i128,
+        fee_b_tokens: i128,
     ) {
         let topics = (
             Symbol::new(&e, "vault_withdraw"),
@@ -44,13 +57,15 @@ impl FeeVaultEvents {
             reserve.clone(),
             from.clone(),
         );
-        e.events().publish(topics, (amount, shares, b_tokens));
+        e.events()
+            .publish(topics, (to.clone(), amount, shares, b_tokens, fee_b_tokens));
     }
 
-    /// Emitted when the admin adds b_tokens to the vault
+    /// Emitted when the admin adds b_tokens to the vault. `b_tokens` is the amount added to the
+    /// admin balance, and `repaid_b_tokens` is the amount used to repay the vault's deficit.
     ///
     /// - topics - `["vault_admin_deposit", pool: Address, reserve: Address, admin: Address]`
-    /// - data - `[amount: i128, b_tokens: i128]`
+    /// - data - `[amount: i128, b_tokens: i128, repaid_b_tokens: i128]`
     pub fn vault_admin_deposit(
         e: &Env,
         pool: &Address,
@@ -58,6 +73,7 @@ impl FeeVaultEvents {
         admin: &Address,
         amount: i128,
         b_tokens: i128,
+        repaid_b_tokens: i128,
     ) {
         let topics = (
             Symbol::new(&e, "vault_admin_deposit"),
@@ -65,7 +81,8 @@ impl FeeVaultEvents {
             reserve.clone(),
             admin.clone(),
         );
-        e.events().publish(topics, (amount, b_tokens));
+        e.events()
+            .publish(topics, (amount, b_tokens, repaid_b_tokens));
     }
 
     /// Emitted when the admin withdraws b_tokens from the vault
@@ -89,6 +106,66 @@ impl FeeVaultEvents {
         e.events().publish(topics, (amount, b_tokens));
     }
 
+    /// Emitted when the admin balance can't cover the vault's APR floor, and the unpaid supplement
+    /// is added to the deficit owed to the vault
+    ///
+    /// - topics - `["vault_deficit_created", pool: Address, reserve: Address]`
+    /// - data - `[b_tokens: i128, deficit: i128]`
+    pub fn vault_deficit_created(
+        e: &Env,
+        pool: &Address,
+        reserve: &Address,
+        b_tokens: i128,
+        deficit: i128,
+    ) {
+        let topics = (
+            Symbol::new(&e, "vault_deficit_created"),
+            pool.clone(),
+            reserve.clone(),
+        );
+        e.events().publish(topics, (b_tokens, deficit));
+    }
+
+    /// Emitted when the deficit owed to the vault is repaid from the admin's fees or deposits
+    ///
+    /// - topics - `["vault_deficit_settled", pool: Address, reserve: Address]`
+    /// - data - `[b_tokens: i128, deficit: i128]`
+    pub fn vault_deficit_settled(
+        e: &Env,
+        pool: &Address,
+        reserve: &Address,
+        b_tokens: i128,
+        deficit: i128,
+    ) {
+        let topics = (
+            Symbol::new(&e, "vault_deficit_settled"),
+            pool.clone(),
+            reserve.clone(),
+        );
+        e.events().publish(topics, (b_tokens, deficit));
+    }
+
+    /// Emitted when a fee recipient withdraws b_tokens from their share of the fees
+    ///
+    /// - topics - `["vault_fee_withdraw", pool: Address, reserve: Address, recipient: Address]`
+    /// - data - `[amount: i128, b_tokens: i128]`
+    pub fn vault_fee_withdraw(
+        e: &Env,
+        pool: &Address,
+        reserve: &Address,
+        recipient: &Address,
+        amount: i128,
+        b_tokens: i128,
+    ) {
+        let topics = (
+            Symbol::new(&e, "vault_fee_withdraw"),
+            pool.clone(),
+            reserve.clone(),
+            recipient.clone(),
+        );
+        e.events().publish(topics, (amount, b_tokens));
+    }
+
     /// Emitted when emissions are claimed
     ///
     /// - topics - `["vault_emissions_claim", pool: Address, admin: Address]`
@@ -108,14 +185,66 @@ impl FeeVaultEvents {
         e.events().publish(topics, (reserve_token_ids, amount));
     }
 
-    /// Emitted when the fee config is updated for the fee vault
+    /// Emitted when the fee policy is updated for the fee vault
     ///
     /// - topics - `["fee_update"]`
-    /// - data - `[rate_type: u32, rate: u32]`
-    pub fn fee_update(e: &Env, rate_type: u32, rate: u32) {
+    /// - data - `fee: Fee`
+    pub fn fee_update(e: &Env, fee: &Fee) {
         let topics = (Symbol::new(&e, "fee_update"),);
 
-        e.events().publish(topics, (rate_type, rate));
+        e.events().publish(topics, fee.clone());
+    }
+
+    /// Emitted when a fee change is queued for the fee vault
+    ///
+    /// - topics - `["fee_queued"]`
+    /// - data - `[fee: Fee, execute_after: u64]`
+    pub fn fee_queued(e: &Env, pending_fee: &PendingFee) {
+        let topics = (Symbol::new(&e, "fee_queued"),);
+
+        e.events()
+            .publish(topics, (pending_fee.fee.clone(), pending_fee.execute_after));
+    }
+
+    /// Emitted when a queued fee change is cancelled
+    ///
+    /// - topics - `["fee_cancelled"]`
+    /// - data - `fee: Fee`
+    pub fn fee_cancelled(e: &Env, fee: &Fee) {
+        let topics = (Symbol::new(&e, "fee_cancelled"),);
+
+        e.events().publish(topics, fee.clone());
+    }
+
+    /// Emitted when the minimum delay for fee changes is updated
+    ///
+    /// - topics - `["fee_delay_update"]`
+    /// - data - `delay: u64`
+    pub fn fee_delay_update(e: &Env, delay: u64) {
+        let topics = (Symbol::new(&e, "fee_delay_update"),);
+
+        e.events().publish(topics, delay);
+    }
+
+    /// Emitted when a decrease to the minimum delay for fee changes is queued
+    ///
+    /// - topics - `["fee_delay_queued"]`
+    /// - data - `[delay: u64, execute_after: u64]`
+    pub fn fee_delay_queued(e: &Env, pending_delay: &PendingFeeDelay) {
+        let topics = (Symbol::new(&e, "fee_delay_queued"),);
+
+        e.events()
+            .publish(topics, (pending_delay.delay, pending_delay.execute_after));
+    }
+
+    /// Emitted when the fee recipients of the vault are updated
+    ///
+    /// - topics - `["fee_recipients_update"]`
+    /// - data - `recipients: Vec<FeeRecipient>`
+    pub fn fee_recipients_update(e: &Env, recipients: &Vec<FeeRecipient>) {
+        let topics = (Symbol::new(&e, "fee_recipients_update"),);
+
+        e.events().publish(topics, recipients.clone());
     }
 
     /// Emitted when vault rewards are set
@@ -137,6 +266,28 @@ impl FeeVaultEvents {
         e.events().publish(topics, (reward_amount, expiration));
     }
 
+    /// Emitted when a reward token is removed from the vault
+    ///
+    /// - topics - `["vault_reward_token_removed", token: Address]`
+    /// - data - `()`
+    pub fn vault_reward_token_removed(e: &Env, token: &Address) {
+        let topics = (Symbol::new(&e, "vault_reward_token_removed"), token.clone());
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when the remaining balance of a removed reward token is swept from the vault
+    ///
+    /// - topics - `["vault_reward_token_swept", token: Address, to: Address]`
+    /// - data - `amount: i128`
+    pub fn vault_reward_token_swept(e: &Env, token: &Address, to: &Address, amount: i128) {
+        let topics = (
+            Symbol::new(&e, "vault_reward_token_swept"),
+            token.clone(),
+            to.clone(),
+        );
+        e.events().publish(topics, amount);
+    }
+
     /// Emitted when a user claims rewards from the vault
     ///
     /// - topics - `["vault_rewards_claim", user: Address, token: Address]`
@@ -149,4 +300,157 @@ impl FeeVaultEvents {
         );
         e.events().publish(topics, amount);
     }
+
+    /// Emitted when shares are transferred between users (SEP-41)
+    ///
+    /// - topics - `["transfer", from: Address, to: Address]`
+    /// - data - `amount: i128` or `{ amount: i128, to_muxed_id: u64 }` if `to` is muxed
+    pub fn transfer(e: &Env, from: &Address, to: &Address, to_muxed_id: Option<u64>, amount: i128) {
+        let topics = (Symbol::new(&e, "transfer"), from.clone(), to.clone());
+        match to_muxed_id {
+            Some(id) => {
+                let data: Map<Symbol, Val> = map![
+                    e,
+                    (Symbol::new(&e, "amount"), amount.into_val(e)),
+                    (Symbol::new(&e, "to_muxed_id"), id.into_val(e))
+                ];
+                e.events().publish(topics, data);
+            }
+            None => e.events().publish(topics, amount),
+        }
+    }
+
+    /// Emitted when a user approves a spender for their shares (SEP-41)
+    ///
+    /// - topics - `["approve", from: Address, spender: Address]`
+    /// - data - `[amount: i128, expiration_ledger: u32]`
+    pub fn approve(
+        e: &Env,
+        from: &Address,
+        spender: &Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
+        let topics = (Symbol::new(&e, "approve"), from.clone(), spender.clone());
+        e.events().publish(topics, (amount, expiration_ledger));
+    }
+
+    /// Emitted when shares are minted to a user on a deposit (SEP-41)
+    ///
+    /// - topics - `["mint", to: Address]`
+    /// - data - `amount: i128`
+    pub fn mint(e: &Env, to: &Address, amount: i128) {
+        let topics = (Symbol::new(&e, "mint"), to.clone());
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when a user burns shares, or when shares are burnt on a withdrawal (SEP-41)
+    ///
+    /// - topics - `["burn", from: Address]`
+    /// - data - `amount: i128`
+    pub fn burn(e: &Env, from: &Address, amount: i128) {
+        let topics = (Symbol::new(&e, "burn"), from.clone());
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when the pause state of the vault is updated
+    ///
+    /// - topics - `["pause_update", caller: Address]`
+    /// - data - `[deposits: bool, withdrawals: bool, claims: bool, emergency: bool]`
+    pub fn pause_update(e: &Env, caller: &Address, pause_state: &PauseState) {
+        let topics = (Symbol::new(&e, "pause_update"), caller.clone());
+        e.events().publish(
+            topics,
+            (
+                pause_state.deposits,
+                pause_state.withdrawals,
+                pause_state.claims,
+                pause_state.emergency,
+            ),
+        );
+    }
+
+    /// Emitted when the deposit caps of the vault are updated
+    ///
+    /// - topics - `["deposit_caps_update"]`
+    /// - data - `[total_cap: Option<i128>, user_cap: Option<i128>]`
+    pub fn deposit_caps_update(e: &Env, total_cap: Option<i128>, user_cap: Option<i128>) {
+        let topics = (Symbol::new(&e, "deposit_caps_update"),);
+        e.events().publish(topics, (total_cap, user_cap));
+    }
+
+    /// Emitted when the admin proposes a new admin
+    ///
+    /// - topics - `["admin_proposed", admin: Address, new_admin: Address]`
+    /// - data - `expiration: u64`
+    pub fn admin_proposed(e: &Env, admin: &Address, new_admin: &Address, expiration: u64) {
+        let topics = (
+            Symbol::new(&e, "admin_proposed"),
+            admin.clone(),
+            new_admin.clone(),
+        );
+        e.events().publish(topics, expiration);
+    }
+
+    /// Emitted when the admin cancels a pending admin proposal
+    ///
+    /// - topics - `["admin_proposal_cancelled", admin: Address, new_admin: Address]`
+    /// - data - `()`
+    pub fn admin_proposal_cancelled(e: &Env, admin: &Address, new_admin: &Address) {
+        let topics = (
+            Symbol::new(&e, "admin_proposal_cancelled"),
+            admin.clone(),
+            new_admin.clone(),
+        );
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when a proposed admin accepts the admin role
+    ///
+    /// - topics - `["admin_accepted", old_admin: Address, new_admin: Address]`
+    /// - data - `()`
+    pub fn admin_accepted(e: &Env, old_admin: &Address, new_admin: &Address) {
+        let topics = (
+            Symbol::new(&e, "admin_accepted"),
+            old_admin.clone(),
+            new_admin.clone(),
+        );
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when the owner grants a role
+    ///
+    /// - topics - `["role_granted", role: u32, address: Address]`
+    /// - data - `()`
+    pub fn role_granted(e: &Env, role: Role, address: &Address) {
+        let topics = (Symbol::new(&e, "role_granted"), role, address.clone());
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when the owner revokes a role
+    ///
+    /// - topics - `["role_revoked", role: u32]`
+    /// - data - `()`
+    pub fn role_revoked(e: &Env, role: Role) {
+        let topics = (Symbol::new(&e, "role_revoked"), role);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when the vault's contract code is upgraded
+    ///
+    /// - topics - `["vault_upgrade", admin: Address]`
+    /// - data - `new_wasm_hash: BytesN<32>`
+    pub fn vault_upgrade(e: &Env, admin: &Address, new_wasm_hash: &BytesN<32>) {
+        let topics = (Symbol::new(&e, "vault_upgrade"), admin.clone());
+        e.events().publish(topics, new_wasm_hash.clone());
+    }
+
+    /// Emitted when the vault's storage is migrated to a new layout version
+    ///
+    /// - topics - `["vault_migrate", admin: Address]`
+    /// - data - `[from_version: u32, to_version: u32]`
+    pub fn vault_migrate(e: &Env, admin: &Address, from_version: u32, to_version: u32) {
+        let topics = (Symbol::new(&e, "vault_migrate"), admin.clone());
+        e.events().publish(topics, (from_version, to_version));
+    }
 }
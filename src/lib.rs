@@ -9,10 +9,12 @@ pub mod constants;
 pub mod contract;
 pub mod errors;
 pub mod events;
+pub mod migration;
 pub mod pool;
 pub mod rewards;
 pub mod storage;
 pub mod summary;
+pub mod token;
 pub mod validator;
 pub mod vault;
 
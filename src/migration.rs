@@ -0,0 +1,86 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, Env};
+
+use crate::{
+    constants::STORAGE_VERSION,
+    errors::FeeVaultError,
+    storage::{self, Fee},
+    vault::VaultData,
+};
+
+/// The vault data layout used by storage version v0, before the version was stored
+#[derive(Clone)]
+#[contracttype]
+pub struct VaultDataV0 {
+    pub last_update_timestamp: u64,
+    pub b_rate: i128,
+    pub total_shares: i128,
+    pub total_b_tokens: i128,
+    pub admin_balance: i128,
+}
+
+/// The fee layout used by storage version v0, before the version was stored
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeV0 {
+    pub rate_type: u32,
+    pub rate: u32,
+}
+
+/// Migrate the vault's storage from the stored layout version to `STORAGE_VERSION`. Each
+/// migration step transforms the layout of a single version, and steps are applied in order.
+///
+/// ### Returns
+/// * `u32` - The storage layout version migrated from
+///
+/// ### Panics
+/// * `AlreadyMigrated` - If the storage is already at `STORAGE_VERSION`
+pub fn migrate(e: &Env) -> u32 {
+    let from_version = storage::get_storage_version(e);
+    if from_version >= STORAGE_VERSION {
+        panic_with_error!(e, FeeVaultError::AlreadyMigrated);
+    }
+
+    if from_version < 1 {
+        migrate_v1(e);
+    }
+
+    storage::set_storage_version(e, STORAGE_VERSION);
+    from_version
+}
+
+/// v0 -> v1: Move the legacy single reward token into the reward token list, convert the fee rate
+/// type and rate into the equivalent fee policy, and extend the vault data with the fee
+/// recipients' balances, the high-water mark, starting at the current share price, and the
+/// deficit owed to the vault depositors
+fn migrate_v1(e: &Env) {
+    let reward_tokens = storage::get_reward_tokens(e);
+    storage::set_reward_tokens(e, &reward_tokens);
+    storage::del_legacy_reward_token(e);
+
+    let legacy_fee = storage::get_fee_v0(e);
+    storage::set_fee(
+        e,
+        Fee::from_rate_type(e, legacy_fee.rate_type, legacy_fee.rate),
+    );
+
+    let legacy_vault = storage::get_vault_data_v0(e);
+    let mut vault = VaultData {
+        last_update_timestamp: legacy_vault.last_update_timestamp,
+        b_rate: legacy_vault.b_rate,
+        total_shares: legacy_vault.total_shares,
+        total_b_tokens: legacy_vault.total_b_tokens,
+        admin_balance: legacy_vault.admin_balance,
+        fee_recipient_balance: 0,
+        unsplit_fees: 0,
+        high_water_mark: 0,
+        deficit: 0,
+    };
+    if vault.total_shares > 0 {
+        vault.high_water_mark = vault
+            .total_b_tokens
+            .fixed_mul_floor(vault.b_rate, vault.total_shares)
+            .unwrap_optimized();
+    }
+    storage::set_vault_data(e, &vault);
+}
@@ -1,5 +1,8 @@
 use blend_contract_sdk::pool::{Client as PoolClient, Request};
-use soroban_sdk::{vec, Address, Env, Vec};
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env, Vec};
+
+use crate::constants::SCALAR_12;
 
 /// Executes a supply of a specific reserve into the underlying pool on behalf of the fee vault
 ///
@@ -74,3 +77,47 @@ pub fn claim(e: &Env, pool: &Address, reserve_token_ids: &Vec<u32>, to: &Address
 pub fn reserve_b_rate(e: &Env, pool: &Address, reserve: &Address) -> i128 {
     PoolClient::new(&e, &pool).get_reserve(reserve).data.b_rate
 }
+
+/// Fetches the amount of underlying tokens that can currently be withdrawn from the reserve
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `reserve` - The reserve address to fetch the liquidity for
+///
+/// ### Returns
+/// * `i128` - The reserve's supply not currently lent out to borrowers
+pub fn reserve_liquidity(e: &Env, pool: &Address, reserve: &Address) -> i128 {
+    let reserve = PoolClient::new(&e, &pool).get_reserve(reserve);
+    let supply = reserve
+        .data
+        .b_supply
+        .fixed_mul_floor(reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    let liabilities = reserve
+        .data
+        .d_supply
+        .fixed_mul_ceil(reserve.data.d_rate, SCALAR_12)
+        .unwrap_optimized();
+    (supply - liabilities).max(0)
+}
+
+/// Fetches the amount of underlying tokens that can currently be supplied to the reserve
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `reserve` - The reserve address to fetch the capacity for
+///
+/// ### Returns
+/// * `i128` - The remaining supply before the reserve's supply cap is reached, or 0 if the reserve is disabled
+pub fn reserve_supply_capacity(e: &Env, pool: &Address, reserve: &Address) -> i128 {
+    let reserve = PoolClient::new(&e, &pool).get_reserve(reserve);
+    if !reserve.config.enabled {
+        return 0;
+    }
+    let supply = reserve
+        .data
+        .b_supply
+        .fixed_mul_ceil(reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    (reserve.config.supply_cap - supply).max(0)
+}
@@ -2,59 +2,81 @@ use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{panic_with_error, token::TokenClient, unwrap::UnwrapOptimized, Address, Env};
 
 use crate::{
-    constants::SCALAR_7,
+    constants::{MAX_REWARD_TOKENS, SCALAR_7},
     errors::FeeVaultError,
     storage::{self, RewardData, UserRewards},
 };
 
-/// Update the rewards index for the user and pool. Must be invoked if any changes are made to the total shares
-/// or a user's shares in the vault.
+/// Update the rewards index for the user and pool for every reward token. Must be invoked if any changes
+/// are made to the total shares or a user's shares in the vault.
 ///
 /// ### Arguments
 /// * `total_shares` - The total number of shares in the vault
 /// * `user_id` - The address of the user
 /// * `user_shares` - The number of shares the user has in the vault
 pub fn update_rewards(e: &Env, total_shares: i128, user_id: &Address, user_shares: i128) {
-    if let Some(reward_token) = storage::get_reward_token(e) {
+    for reward_token in storage::get_reward_tokens(e).iter() {
         if let Some(reward_data) = update_reward_data(e, &reward_token, total_shares) {
             update_user_rewards(e, &reward_token, &reward_data, user_id, user_shares, false);
         }
     }
+    // the index of a removed reward token no longer changes, but the user's accrual up to it must be
+    // recorded before their shares change
+    for reward_token in storage::get_removed_reward_tokens(e).iter() {
+        if let Some(reward_data) = storage::get_reward_data(e, &reward_token) {
+            update_user_rewards(e, &reward_token, &reward_data, user_id, user_shares, false);
+        }
+    }
 }
 
-/// Claims rewards for a user
+/// Claims rewards of a reward token for a user. Removed reward tokens pay out the rewards accrued up to
+/// their final index. Historical reward tokens that are no longer tracked by the vault only pay out the
+/// user's stored accrued rewards.
 ///
 /// ### Arguments
 /// * `total_shares` - The total number of shares in the vault
 /// * `user_id` - The address of the user
 /// * `user_shares` - The number of shares the user has in the vault
+/// * `reward_token` - The address of the reward token to claim
 /// * `to` - The address to send the rewards to
 ///
 /// Returns the number of tokens that need to be transferred to `user`
 ///
-/// Panics if the vault never had rewards configured
+/// Panics if the vault never had rewards configured for `reward_token`, or if the remaining balance of
+/// `reward_token` was swept
 pub fn claim_rewards(
     e: &Env,
     total_shares: i128,
     user_id: &Address,
     user_shares: i128,
+    reward_token: &Address,
     to: &Address,
 ) -> i128 {
-    if let Some(reward_token) = storage::get_reward_token(e) {
-        if let Some(reward_data) = update_reward_data(e, &reward_token, total_shares) {
-            let reward_amount =
-                update_user_rewards(e, &reward_token, &reward_data, user_id, user_shares, true);
-            if reward_amount > 0 {
-                TokenClient::new(e, &reward_token).transfer(
-                    &e.current_contract_address(),
-                    to,
-                    &reward_amount,
-                );
+    let reward_amount = if storage::get_reward_tokens(e).contains(reward_token) {
+        match update_reward_data(e, reward_token, total_shares) {
+            Some(reward_data) => {
+                update_user_rewards(e, reward_token, &reward_data, user_id, user_shares, true)
+            }
+            None => panic_with_error!(e, FeeVaultError::NoRewardsConfigured),
+        }
+    } else if storage::get_removed_reward_tokens(e).contains(reward_token) {
+        match storage::get_reward_data(e, reward_token) {
+            Some(reward_data) => {
+                update_user_rewards(e, reward_token, &reward_data, user_id, user_shares, true)
             }
-            return reward_amount;
+            None => panic_with_error!(e, FeeVaultError::NoRewardsConfigured),
         }
+    } else {
+        claim_historical_rewards(e, reward_token, user_id)
+    };
+    if reward_amount > 0 {
+        TokenClient::new(e, reward_token).transfer(
+            &e.current_contract_address(),
+            to,
+            &reward_amount,
+        );
     }
-    panic_with_error!(e, FeeVaultError::NoRewardsConfigured)
+    reward_amount
 }
 
 /// Set the rewards for the vault.
@@ -79,6 +101,23 @@ pub fn set_rewards(
         panic_with_error!(e, FeeVaultError::InvalidRewardConfig);
     }
 
+    // Add the reward token to the vault's reward tokens if it is new
+    let mut reward_tokens = storage::get_reward_tokens(e);
+    if !reward_tokens.contains(reward_token) {
+        // removed reward tokens can't be distributed again, as users that never interacted with the vault
+        // would be paid the token's full index
+        if storage::get_removed_reward_tokens(e).contains(reward_token)
+            || storage::get_reward_token_swept(e, reward_token)
+        {
+            panic_with_error!(e, FeeVaultError::InvalidRewardConfig);
+        }
+        if reward_tokens.len() >= MAX_REWARD_TOKENS {
+            panic_with_error!(e, FeeVaultError::TooManyRewardTokens);
+        }
+        reward_tokens.push_back(reward_token.clone());
+        storage::set_reward_tokens(e, &reward_tokens);
+    }
+
     // Transfer token amount into the vault
     TokenClient::new(e, reward_token).transfer(
         &from,
@@ -86,15 +125,15 @@ pub fn set_rewards(
         &reward_amount,
     );
 
-    // Check if any rewards are active. If rewards are active, the reward token must match the current one,
-    // and the expiration must be greater than the current timestamp for the new rewards to be applied.
-    if let Some(cur_reward_token) = storage::get_reward_token(e) {
-        if let Some(cur_reward_data) = update_reward_data(e, &cur_reward_token, total_shares) {
+    // Check if rewards are active for the token. If rewards are active, the expiration must be greater than
+    // the current expiration for the new rewards to be applied.
+    let reward_index =
+        if let Some(cur_reward_data) = update_reward_data(e, reward_token, total_shares) {
             if cur_reward_data.expiration > e.ledger().timestamp() {
                 // active rewards!
 
                 // validate the new settings can be used to update the current rewards
-                if cur_reward_token != *reward_token || expiration < cur_reward_data.expiration {
+                if expiration < cur_reward_data.expiration {
                     panic_with_error!(e, FeeVaultError::InvalidRewardConfig);
                 }
 
@@ -112,23 +151,15 @@ pub fn set_rewards(
                 storage::set_reward_data(e, reward_token, &new_reward_data);
                 return; // return to prevent fallthrough to the next section
             }
-        }
-    }
-
-    // No active rewards found!
-    // Set new reward data based on config. We need to check if any
-    // old reward data exists, to persist the last calculated index.
-
-    // An extra read occurs here of `reward_data` for expired rewards, but is left to keep the implementation
-    // as simple as possible.
-    let reward_index =
-        if let Some(cur_reward_data) = update_reward_data(e, &reward_token, total_shares) {
+            // persist the last calculated index of expired rewards
             cur_reward_data.index
         } else {
             0
         };
+
+    // No active rewards found for the token!
+    // Set new reward data based on config.
     let new_eps = calculate_eps(e, reward_amount, reward_period);
-    // Set the new reward data and reward token
     let new_reward_data = RewardData {
         eps: new_eps as u64,
         expiration,
@@ -136,7 +167,88 @@ pub fn set_rewards(
         last_time: e.ledger().timestamp(),
     };
     storage::set_reward_data(e, reward_token, &new_reward_data);
-    storage::set_reward_token(e, reward_token);
+}
+
+/// Remove a reward token from the vault's reward tokens, so a new reward token can take its place.
+///
+/// The token's index is updated up to the rewards' expiration before it is removed, and no longer changes
+/// afterwards. Users can still claim all the rewards accrued to them up to the final index, until the
+/// token's remaining balance is swept.
+///
+/// ### Arguments
+/// * `total_shares` - The total number of shares in the vault
+/// * `reward_token` - The address of the reward token to remove
+///
+/// ### Panics
+/// * `NoRewardsConfigured` - If `reward_token` is not one of the vault's reward tokens
+/// * `InvalidRewardConfig` - If the rewards for `reward_token` have not expired
+/// * `TooManyRewardTokens` - If the vault already has the maximum number of removed reward tokens
+pub fn remove_reward_token(e: &Env, total_shares: i128, reward_token: &Address) {
+    let mut reward_tokens = storage::get_reward_tokens(e);
+    let index = match reward_tokens.first_index_of(reward_token) {
+        Some(index) => index,
+        None => panic_with_error!(e, FeeVaultError::NoRewardsConfigured),
+    };
+    if let Some(reward_data) = update_reward_data(e, reward_token, total_shares) {
+        if reward_data.expiration > e.ledger().timestamp() {
+            panic_with_error!(e, FeeVaultError::InvalidRewardConfig);
+        }
+    }
+    let mut removed_reward_tokens = storage::get_removed_reward_tokens(e);
+    if removed_reward_tokens.len() >= MAX_REWARD_TOKENS {
+        panic_with_error!(e, FeeVaultError::TooManyRewardTokens);
+    }
+    reward_tokens.remove(index);
+    storage::set_reward_tokens(e, &reward_tokens);
+    removed_reward_tokens.push_back(reward_token.clone());
+    storage::set_removed_reward_tokens(e, &removed_reward_tokens);
+}
+
+/// Sweep the remaining balance of a removed reward token. Any rewards users have not claimed yet are
+/// forfeited, and the token can no longer be claimed.
+///
+/// ### Arguments
+/// * `reward_token` - The address of the removed reward token
+/// * `to` - The address to send the remaining balance to
+///
+/// ### Returns
+/// * `i128` - The amount of tokens swept
+///
+/// ### Panics
+/// * `NoRewardsConfigured` - If `reward_token` is not one of the vault's removed reward tokens
+pub fn sweep_reward_token(e: &Env, reward_token: &Address, to: &Address) -> i128 {
+    let mut removed_reward_tokens = storage::get_removed_reward_tokens(e);
+    let index = match removed_reward_tokens.first_index_of(reward_token) {
+        Some(index) => index,
+        None => panic_with_error!(e, FeeVaultError::NoRewardsConfigured),
+    };
+    removed_reward_tokens.remove(index);
+    storage::set_removed_reward_tokens(e, &removed_reward_tokens);
+    storage::set_reward_token_swept(e, reward_token);
+
+    let token_client = TokenClient::new(e, reward_token);
+    let balance = token_client.balance(&e.current_contract_address());
+    if balance > 0 {
+        token_client.transfer(&e.current_contract_address(), to, &balance);
+    }
+    balance
+}
+
+/// Load the vault's primary reward token. This is the first reward token with active rewards, or the first
+/// reward token if no rewards are active.
+///
+/// ### Returns
+/// * `Option<Address>` - The address of the reward token, or None if the vault has no reward tokens
+pub fn load_reward_token(e: &Env) -> Option<Address> {
+    let reward_tokens = storage::get_reward_tokens(e);
+    for reward_token in reward_tokens.iter() {
+        if let Some(reward_data) = storage::get_reward_data(e, &reward_token) {
+            if reward_data.expiration > e.ledger().timestamp() {
+                return Some(reward_token);
+            }
+        }
+    }
+    reward_tokens.first()
 }
 
 /// Load an updated reward data for the given reward token.
@@ -187,6 +299,44 @@ pub fn load_updated_reward_data(
     }
 }
 
+/// Load the rewards a user can currently claim for the given reward token.
+///
+/// This does NOT write the updated reward data to storage.
+///
+/// ### Arguments
+/// * `reward_token` - The address of the reward token
+/// * `total_shares` - The total number of shares in the vault
+/// * `user` - The address of the user
+/// * `user_shares` - The number of shares the user has in the vault
+///
+/// ### Returns
+/// * `i128` - The number of reward tokens the user can claim, or 0 if no rewards exist
+pub fn load_claimable_rewards(
+    e: &Env,
+    reward_token: &Address,
+    total_shares: i128,
+    user: &Address,
+    user_shares: i128,
+) -> i128 {
+    let user_data = storage::get_user_rewards(e, reward_token, user);
+    if storage::get_reward_tokens(e).contains(reward_token) {
+        match load_updated_reward_data(e, reward_token, total_shares) {
+            Some(reward_data) => calculate_user_accrual(&reward_data, &user_data, user_shares),
+            None => 0,
+        }
+    } else if storage::get_removed_reward_tokens(e).contains(reward_token) {
+        match storage::get_reward_data(e, reward_token) {
+            Some(reward_data) => calculate_user_accrual(&reward_data, &user_data, user_shares),
+            None => 0,
+        }
+    } else if storage::get_reward_token_swept(e, reward_token) {
+        0
+    } else {
+        // historical reward tokens only pay out the stored accrued rewards
+        user_data.map_or(0, |user_data| user_data.accrued)
+    }
+}
+
 /***** Helper Functions *****/
 
 /// Update the vault rewards index for vault shares
@@ -220,8 +370,25 @@ fn update_user_rewards(
     user_shares: i128,
     to_claim: bool,
 ) -> i128 {
-    if let Some(user_data) = storage::get_user_rewards(e, token, user) {
-        if user_data.index != reward_data.index || to_claim {
+    let user_data = storage::get_user_rewards(e, token, user);
+    if let Some(cur_user_data) = &user_data {
+        if cur_user_data.index == reward_data.index && !to_claim {
+            // no accrual occurred and no claim requested
+            return 0;
+        }
+    }
+    let accrual = calculate_user_accrual(reward_data, &user_data, user_shares);
+    set_user_rewards(e, token, user, reward_data.index, accrual, to_claim)
+}
+
+/// Calculate the user's total accrued rewards at the reward data's index
+fn calculate_user_accrual(
+    reward_data: &RewardData,
+    user_data: &Option<UserRewards>,
+    user_shares: i128,
+) -> i128 {
+    match user_data {
+        Some(user_data) => {
             let mut accrual = user_data.accrued;
             if user_shares != 0 && reward_data.index > user_data.index {
                 let delta_index = reward_data.index - user_data.index;
@@ -230,19 +397,35 @@ fn update_user_rewards(
                     .unwrap_optimized();
                 accrual += to_accrue;
             }
-            return set_user_rewards(e, token, user, reward_data.index, accrual, to_claim);
+            accrual
         }
-        // no accrual occurred and no claim requested
-        return 0;
-    } else if user_shares == 0 {
         // first time the user registered an action since rewards were added
-        return set_user_rewards(e, token, user, reward_data.index, 0, to_claim);
-    } else {
+        None if user_shares == 0 => 0,
         // user had shares before rewards began, they are due any historical rewards
-        let to_accrue = user_shares
+        None => user_shares
             .fixed_mul_floor(reward_data.index, SCALAR_7)
-            .unwrap_optimized();
-        return set_user_rewards(e, token, user, reward_data.index, to_accrue, to_claim);
+            .unwrap_optimized(),
+    }
+}
+
+/// Claim the accrued rewards of a reward token that is no longer tracked by the vault. The token's index
+/// no longer updates with the user's shares, so only the stored accrued rewards can be claimed.
+fn claim_historical_rewards(e: &Env, reward_token: &Address, user: &Address) -> i128 {
+    if storage::get_reward_data(e, reward_token).is_none()
+        || storage::get_reward_token_swept(e, reward_token)
+    {
+        panic_with_error!(e, FeeVaultError::NoRewardsConfigured);
+    }
+    match storage::get_user_rewards(e, reward_token, user) {
+        Some(user_data) if user_data.accrued > 0 => set_user_rewards(
+            e,
+            reward_token,
+            user,
+            user_data.index,
+            user_data.accrued,
+            true,
+        ),
+        _ => 0,
     }
 }
 
@@ -278,6 +461,7 @@ mod tests {
     use soroban_sdk::{
         testutils::{Address as _, Ledger, LedgerInfo},
         token::StellarAssetClient,
+        vec, Symbol, Vec,
     };
 
     /********** update_rewards **********/
@@ -316,7 +500,7 @@ mod tests {
             accrued: 3,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
             storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
 
@@ -334,6 +518,129 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_update_rewards_multiple_tokens() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200 + 1234;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let reward_token_0 = Address::generate(&e);
+        let reward_token_1 = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let samwise = Address::generate(&e);
+
+        let last_timestamp = 1713139200;
+        let reward_data_0 = RewardData {
+            expiration: last_timestamp + 7 * 24 * 60 * 60,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: last_timestamp,
+        };
+        let reward_data_1 = RewardData {
+            expiration: last_timestamp + 7 * 24 * 60 * 60,
+            eps: 0_0500000,
+            index: 0,
+            last_time: last_timestamp,
+        };
+        let user_rewards_data = UserRewards {
+            index: 11111,
+            accrued: 3,
+        };
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(
+                &e,
+                &vec![&e, reward_token_0.clone(), reward_token_1.clone()],
+            );
+            storage::set_reward_data(&e, &reward_token_0, &reward_data_0);
+            storage::set_reward_data(&e, &reward_token_1, &reward_data_1);
+            storage::set_user_rewards(&e, &reward_token_0, &samwise, &user_rewards_data);
+
+            let total_shares: i128 = 150_0000000;
+            let user_balance: i128 = 9_0000000;
+            update_rewards(&e, total_shares, &samwise, user_balance);
+
+            let new_rewards_data_0 =
+                storage::get_reward_data(&e, &reward_token_0).unwrap_optimized();
+            let new_user_data_0 =
+                storage::get_user_rewards(&e, &reward_token_0, &samwise).unwrap_optimized();
+            assert_eq!(new_rewards_data_0.last_time, block_timestamp);
+            assert_eq!(new_rewards_data_0.index, 8248888);
+            assert_eq!(new_user_data_0.accrued, 7_4139996);
+            assert_eq!(new_user_data_0.index, 8248888);
+
+            let new_rewards_data_1 =
+                storage::get_reward_data(&e, &reward_token_1).unwrap_optimized();
+            let new_user_data_1 =
+                storage::get_user_rewards(&e, &reward_token_1, &samwise).unwrap_optimized();
+            assert_eq!(new_rewards_data_1.last_time, block_timestamp);
+            assert_eq!(new_rewards_data_1.index, 4113333);
+            assert_eq!(new_user_data_1.accrued, 3_7019997);
+            assert_eq!(new_user_data_1.index, 4113333);
+        });
+    }
+
+    #[test]
+    fn test_update_rewards_legacy_reward_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200 + 1234;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let samwise = Address::generate(&e);
+
+        let last_timestamp = 1713139200;
+        let reward_data = RewardData {
+            expiration: last_timestamp + 7 * 24 * 60 * 60,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: last_timestamp,
+        };
+        e.as_contract(&vault_address, || {
+            // vaults created before multiple reward tokens were supported only store a single token
+            e.storage()
+                .instance()
+                .set(&Symbol::new(&e, "RwdToken"), &reward_token);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+            assert_eq!(
+                storage::get_reward_tokens(&e),
+                vec![&e, reward_token.clone()]
+            );
+
+            let total_shares: i128 = 150_0000000;
+            let user_balance: i128 = 9_0000000;
+            update_rewards(&e, total_shares, &samwise, user_balance);
+
+            let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
+            assert_eq!(new_rewards_data.last_time, block_timestamp);
+            assert_eq!(new_rewards_data.index, 8248888);
+        });
+    }
+
     #[test]
     fn test_update_rewards_no_data() {
         let e = Env::default();
@@ -402,7 +709,7 @@ mod tests {
             accrued: 3,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
             storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
 
@@ -450,7 +757,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -497,7 +804,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -548,7 +855,7 @@ mod tests {
             accrued: 3,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
             storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
 
@@ -608,7 +915,7 @@ mod tests {
             accrued: 3,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
             storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
 
@@ -616,7 +923,14 @@ mod tests {
             let user_balance: i128 = 9_0000000;
             storage::set_vault_shares(&e, &samwise, user_balance);
 
-            let result = claim_rewards(&e, total_shares, &samwise, user_balance, &frodo);
+            let result = claim_rewards(
+                &e,
+                total_shares,
+                &samwise,
+                user_balance,
+                &reward_token,
+                &frodo,
+            );
 
             let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
             let new_user_data =
@@ -636,8 +950,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #110)")]
-    fn test_claim_rewards_no_data() {
+    fn test_claim_rewards_historical_token() {
         let e = Env::default();
         e.mock_all_auths();
         let block_timestamp = 1713139200 + 1234;
@@ -653,28 +966,71 @@ mod tests {
         });
 
         let bombadil = Address::generate(&e);
-        let reward_token = e
+        let old_reward_token = e
             .register_stellar_asset_contract_v2(bombadil.clone())
             .address();
+        let reward_token = Address::generate(&e);
         let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         let starting_balance = 100_000_0000000;
-        StellarAssetClient::new(&e, &reward_token).mint(&vault_address, &starting_balance);
+        StellarAssetClient::new(&e, &old_reward_token).mint(&vault_address, &starting_balance);
 
         let samwise = Address::generate(&e);
         let frodo = Address::generate(&e);
 
+        let last_timestamp = 1713139200;
+        let old_reward_data = RewardData {
+            expiration: last_timestamp - 1000,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: last_timestamp - 1000,
+        };
+        let user_rewards_data = UserRewards {
+            index: 11111,
+            accrued: 5_0000000,
+        };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            // the old reward token was replaced before multiple reward tokens were supported
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(&e, &old_reward_token, &old_reward_data);
+            storage::set_user_rewards(&e, &old_reward_token, &samwise, &user_rewards_data);
 
             let total_shares: i128 = 150_0000000;
             let user_balance: i128 = 9_0000000;
-            claim_rewards(&e, total_shares, &samwise, user_balance, &frodo);
+            let result = claim_rewards(
+                &e,
+                total_shares,
+                &samwise,
+                user_balance,
+                &old_reward_token,
+                &frodo,
+            );
+            assert_eq!(result, 5_0000000);
+
+            let new_user_data =
+                storage::get_user_rewards(&e, &old_reward_token, &samwise).unwrap_optimized();
+            assert_eq!(new_user_data.accrued, 0);
+            assert_eq!(new_user_data.index, 11111);
+            let token_client = TokenClient::new(&e, &old_reward_token);
+            assert_eq!(token_client.balance(&frodo), result);
+
+            // claiming again pays nothing
+            let result = claim_rewards(
+                &e,
+                total_shares,
+                &samwise,
+                user_balance,
+                &old_reward_token,
+                &frodo,
+            );
+            assert_eq!(result, 0);
+            assert_eq!(token_client.balance(&frodo), 5_0000000);
         });
     }
 
     #[test]
-    fn test_claim_rewards_zero_accrued() {
+    #[should_panic(expected = "Error(Contract, #110)")]
+    fn test_claim_rewards_no_data() {
         let e = Env::default();
         e.mock_all_auths();
         let block_timestamp = 1713139200 + 1234;
@@ -701,51 +1057,25 @@ mod tests {
         let samwise = Address::generate(&e);
         let frodo = Address::generate(&e);
 
-        let last_timestamp = 1713139200;
-        let reward_data = RewardData {
-            expiration: last_timestamp + 7 * 24 * 60 * 60,
-            eps: 0_1000000,
-            index: 22222,
-            last_time: last_timestamp,
-        };
-        let user_rewards_data = UserRewards {
-            index: 22222,
-            accrued: 0,
-        };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
-            storage::set_reward_data(&e, &reward_token, &reward_data);
-            storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
 
             let total_shares: i128 = 150_0000000;
-            let user_balance: i128 = 0;
-            storage::set_vault_shares(&e, &samwise, user_balance);
-
-            let result = claim_rewards(&e, total_shares, &samwise, user_balance, &frodo);
-
-            let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
-            let new_user_data =
-                storage::get_user_rewards(&e, &reward_token, &samwise).unwrap_optimized();
-            assert_eq!(new_rewards_data.last_time, block_timestamp);
-            assert_eq!(new_rewards_data.index, 8248888);
-            assert_eq!(result, 0);
-            assert_eq!(new_user_data.accrued, 0);
-            assert_eq!(new_user_data.index, 8248888);
-
-            let token_client = TokenClient::new(&e, &reward_token);
-            let frodo_balance = token_client.balance(&frodo);
-            assert_eq!(frodo_balance, result);
-            let contract_balance = token_client.balance(&vault_address);
-            assert_eq!(contract_balance, starting_balance);
+            let user_balance: i128 = 9_0000000;
+            claim_rewards(
+                &e,
+                total_shares,
+                &samwise,
+                user_balance,
+                &reward_token,
+                &frodo,
+            );
         });
     }
 
-    // @dev: The below tests should be impossible states to reach, but are left
-    //       in to ensure any bad state does not result in incorrect rewards.
-
     #[test]
-    #[should_panic(expected = "attempt to subtract with overflow")]
-    fn test_update_rewards_negative_time_dif() {
+    #[should_panic(expected = "Error(Contract, #110)")]
+    fn test_claim_rewards_unknown_token() {
         let e = Env::default();
         e.mock_all_auths();
         let block_timestamp = 1713139200 + 1234;
@@ -762,29 +1092,155 @@ mod tests {
 
         let bombadil = Address::generate(&e);
         let reward_token = Address::generate(&e);
+        let unknown_token = Address::generate(&e);
         let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
 
         let last_timestamp = 1713139200;
         let reward_data = RewardData {
             expiration: last_timestamp + 7 * 24 * 60 * 60,
             eps: 0_1000000,
             index: 22222,
-            last_time: block_timestamp + 1,
-        };
-        let user_rewards_data = UserRewards {
-            index: 11111,
-            accrued: 3,
+            last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
-            storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
 
             let total_shares: i128 = 150_0000000;
             let user_balance: i128 = 9_0000000;
-            update_rewards(&e, total_shares, &samwise, user_balance);
+            claim_rewards(
+                &e,
+                total_shares,
+                &samwise,
+                user_balance,
+                &unknown_token,
+                &frodo,
+            );
+        });
+    }
+
+    #[test]
+    fn test_claim_rewards_zero_accrued() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200 + 1234;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let reward_token = e
+            .register_stellar_asset_contract_v2(bombadil.clone())
+            .address();
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let starting_balance = 100_000_0000000;
+        StellarAssetClient::new(&e, &reward_token).mint(&vault_address, &starting_balance);
+
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let last_timestamp = 1713139200;
+        let reward_data = RewardData {
+            expiration: last_timestamp + 7 * 24 * 60 * 60,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: last_timestamp,
+        };
+        let user_rewards_data = UserRewards {
+            index: 22222,
+            accrued: 0,
+        };
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+            storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
+
+            let total_shares: i128 = 150_0000000;
+            let user_balance: i128 = 0;
+            storage::set_vault_shares(&e, &samwise, user_balance);
+
+            let result = claim_rewards(
+                &e,
+                total_shares,
+                &samwise,
+                user_balance,
+                &reward_token,
+                &frodo,
+            );
+
+            let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
+            let new_user_data =
+                storage::get_user_rewards(&e, &reward_token, &samwise).unwrap_optimized();
+            assert_eq!(new_rewards_data.last_time, block_timestamp);
+            assert_eq!(new_rewards_data.index, 8248888);
+            assert_eq!(result, 0);
+            assert_eq!(new_user_data.accrued, 0);
+            assert_eq!(new_user_data.index, 8248888);
+
+            let token_client = TokenClient::new(&e, &reward_token);
+            let frodo_balance = token_client.balance(&frodo);
+            assert_eq!(frodo_balance, result);
+            let contract_balance = token_client.balance(&vault_address);
+            assert_eq!(contract_balance, starting_balance);
+        });
+    }
+
+    // @dev: The below tests should be impossible states to reach, but are left
+    //       in to ensure any bad state does not result in incorrect rewards.
+
+    #[test]
+    #[should_panic(expected = "attempt to subtract with overflow")]
+    fn test_update_rewards_negative_time_dif() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200 + 1234;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let samwise = Address::generate(&e);
+
+        let last_timestamp = 1713139200;
+        let reward_data = RewardData {
+            expiration: last_timestamp + 7 * 24 * 60 * 60,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: block_timestamp + 1,
+        };
+        let user_rewards_data = UserRewards {
+            index: 11111,
+            accrued: 3,
+        };
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+            storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
+
+            let total_shares: i128 = 150_0000000;
+            let user_balance: i128 = 9_0000000;
+            update_rewards(&e, total_shares, &samwise, user_balance);
         });
     }
 
@@ -822,7 +1278,7 @@ mod tests {
             accrued: 3,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
             storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
 
@@ -840,6 +1296,85 @@ mod tests {
         });
     }
 
+    /********** load_claimable_rewards **********/
+
+    #[test]
+    fn test_load_claimable_rewards() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200 + 1234;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let old_reward_token = Address::generate(&e);
+        let unknown_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let last_timestamp = 1713139200;
+        let reward_data = RewardData {
+            expiration: last_timestamp + 7 * 24 * 60 * 60,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: last_timestamp,
+        };
+        let user_rewards_data = UserRewards {
+            index: 11111,
+            accrued: 3,
+        };
+        let old_user_rewards_data = UserRewards {
+            index: 11111,
+            accrued: 5_0000000,
+        };
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+            storage::set_reward_data(&e, &old_reward_token, &reward_data);
+            storage::set_user_rewards(&e, &reward_token, &samwise, &user_rewards_data);
+            storage::set_user_rewards(&e, &old_reward_token, &samwise, &old_user_rewards_data);
+
+            let total_shares: i128 = 150_0000000;
+            let user_balance: i128 = 9_0000000;
+
+            // matches the result of `test_claim_rewards`
+            let claimable =
+                load_claimable_rewards(&e, &reward_token, total_shares, &samwise, user_balance);
+            assert_eq!(claimable, 7_4139996);
+
+            // user with shares before rewards began
+            let claimable =
+                load_claimable_rewards(&e, &reward_token, total_shares, &frodo, user_balance);
+            assert_eq!(claimable, 7_4239992);
+
+            // historical reward tokens only pay out stored accrued rewards
+            let claimable =
+                load_claimable_rewards(&e, &old_reward_token, total_shares, &samwise, user_balance);
+            assert_eq!(claimable, 5_0000000);
+
+            let claimable =
+                load_claimable_rewards(&e, &unknown_token, total_shares, &samwise, user_balance);
+            assert_eq!(claimable, 0);
+
+            // no data was written
+            let rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
+            assert_eq!(rewards_data.index, 22222);
+            assert_eq!(rewards_data.last_time, last_timestamp);
+            assert!(storage::get_user_rewards(&e, &reward_token, &frodo).is_none());
+        });
+    }
+
     /********** set_rewards **********/
 
     #[test]
@@ -880,9 +1415,9 @@ mod tests {
                 expiration,
             );
 
-            let new_reward_token = storage::get_reward_token(&e).unwrap_optimized();
+            let new_reward_tokens = storage::get_reward_tokens(&e);
             let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
-            assert_eq!(new_reward_token, reward_token);
+            assert_eq!(new_reward_tokens, vec![&e, reward_token.clone()]);
 
             assert_eq!(new_rewards_data.last_time, block_timestamp);
             assert_eq!(new_rewards_data.index, 0);
@@ -929,7 +1464,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -942,9 +1477,9 @@ mod tests {
                 expiration,
             );
 
-            let new_reward_token = storage::get_reward_token(&e).unwrap_optimized();
+            let new_reward_tokens = storage::get_reward_tokens(&e);
             let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
-            assert_eq!(new_reward_token, reward_token);
+            assert_eq!(new_reward_tokens, vec![&e, reward_token.clone()]);
 
             assert_eq!(new_rewards_data.last_time, block_timestamp);
             assert_eq!(new_rewards_data.index, 8248888); // index updated from prev data
@@ -991,7 +1526,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -1004,9 +1539,9 @@ mod tests {
                 expiration,
             );
 
-            let new_reward_token = storage::get_reward_token(&e).unwrap_optimized();
+            let new_reward_tokens = storage::get_reward_tokens(&e);
             let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
-            assert_eq!(new_reward_token, reward_token);
+            assert_eq!(new_reward_tokens, vec![&e, reward_token.clone()]);
 
             assert_eq!(new_rewards_data.last_time, block_timestamp);
             assert_eq!(new_rewards_data.index, 123456789); // index maintained from prev data
@@ -1020,7 +1555,6 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #111)")]
     fn test_set_rewards_new_token_active() {
         let e = Env::default();
         e.mock_all_auths_allowing_non_root_auth();
@@ -1057,7 +1591,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &old_reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, old_reward_token.clone()]);
             storage::set_reward_data(&e, &old_reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -1069,6 +1603,68 @@ mod tests {
                 reward_amount,
                 expiration,
             );
+
+            let new_reward_tokens = storage::get_reward_tokens(&e);
+            assert_eq!(
+                new_reward_tokens,
+                vec![&e, old_reward_token.clone(), reward_token.clone()]
+            );
+
+            // old rewards are unchanged
+            let old_rewards_data =
+                storage::get_reward_data(&e, &old_reward_token).unwrap_optimized();
+            assert_eq!(old_rewards_data.last_time, last_timestamp);
+            assert_eq!(old_rewards_data.index, 22222);
+            assert_eq!(old_rewards_data.eps, 0_1000000);
+            assert_eq!(old_rewards_data.expiration, reward_data.expiration);
+
+            let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
+            assert_eq!(new_rewards_data.last_time, block_timestamp);
+            assert_eq!(new_rewards_data.index, 0);
+            assert_eq!(new_rewards_data.eps, 0_0385802);
+            assert_eq!(new_rewards_data.expiration, expiration);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #115)")]
+    fn test_set_rewards_too_many_tokens() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &samwise, 0, 0_1000000, None);
+
+        let reward_amount = 100_000_0000000;
+        let expiration = block_timestamp + 30 * 24 * 60 * 60;
+        e.as_contract(&vault_address, || {
+            let total_shares: i128 = 150_0000000;
+            for _ in 0..MAX_REWARD_TOKENS + 1 {
+                let reward_token = e
+                    .register_stellar_asset_contract_v2(bombadil.clone())
+                    .address();
+                StellarAssetClient::new(&e, &reward_token).mint(&samwise, &reward_amount);
+                set_rewards(
+                    &e,
+                    &samwise,
+                    total_shares,
+                    &reward_token,
+                    reward_amount,
+                    expiration,
+                );
+            }
         });
     }
 
@@ -1109,7 +1705,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &old_reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, old_reward_token.clone()]);
             storage::set_reward_data(&e, &old_reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -1122,9 +1718,12 @@ mod tests {
                 expiration,
             );
 
-            let new_reward_token = storage::get_reward_token(&e).unwrap_optimized();
+            let new_reward_tokens = storage::get_reward_tokens(&e);
             let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
-            assert_eq!(new_reward_token, reward_token);
+            assert_eq!(
+                new_reward_tokens,
+                vec![&e, old_reward_token.clone(), reward_token.clone()]
+            );
 
             assert_eq!(new_rewards_data.last_time, block_timestamp);
             assert_eq!(new_rewards_data.index, 0);
@@ -1180,7 +1779,7 @@ mod tests {
             last_time: block_timestamp - 180 * 24 * 60 * 60,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &old_reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, old_reward_token.clone()]);
             storage::set_reward_data(&e, &old_reward_token, &reward_data);
             storage::set_reward_data(&e, &reward_token, &reward_data_old);
 
@@ -1194,9 +1793,12 @@ mod tests {
                 expiration,
             );
 
-            let new_reward_token = storage::get_reward_token(&e).unwrap_optimized();
+            let new_reward_tokens = storage::get_reward_tokens(&e);
             let new_rewards_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
-            assert_eq!(new_reward_token, reward_token);
+            assert_eq!(
+                new_reward_tokens,
+                vec![&e, old_reward_token.clone(), reward_token.clone()]
+            );
 
             assert_eq!(new_rewards_data.last_time, block_timestamp);
             assert_eq!(new_rewards_data.index, 987654321);
@@ -1367,7 +1969,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -1417,7 +2019,7 @@ mod tests {
             last_time: last_timestamp,
         };
         e.as_contract(&vault_address, || {
-            storage::set_reward_token(&e, &reward_token);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
             storage::set_reward_data(&e, &reward_token, &reward_data);
 
             let total_shares: i128 = 150_0000000;
@@ -1431,4 +2033,544 @@ mod tests {
             );
         });
     }
+
+    /********** remove_reward_token **********/
+
+    #[test]
+    fn test_remove_reward_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let expired_token = Address::generate(&e);
+        let active_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let expired_reward_data = RewardData {
+            expiration: block_timestamp - 1000,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: block_timestamp - 2000,
+        };
+        let active_reward_data = RewardData {
+            expiration: block_timestamp + 1000,
+            eps: 0_1000000,
+            index: 33333,
+            last_time: block_timestamp - 2000,
+        };
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, expired_token.clone(), active_token.clone()]);
+            storage::set_reward_data(&e, &expired_token, &expired_reward_data);
+            storage::set_reward_data(&e, &active_token, &active_reward_data);
+
+            let total_shares: i128 = 150_0000000;
+            remove_reward_token(&e, total_shares, &expired_token);
+
+            assert_eq!(
+                storage::get_reward_tokens(&e),
+                vec![&e, active_token.clone()]
+            );
+            assert_eq!(
+                storage::get_removed_reward_tokens(&e),
+                vec![&e, expired_token.clone()]
+            );
+            // -> the index is updated up to the expiration before the token is removed
+            let new_reward_data = storage::get_reward_data(&e, &expired_token).unwrap_optimized();
+            assert_eq!(new_reward_data.index, 6688888);
+            assert_eq!(new_reward_data.last_time, block_timestamp);
+            assert_eq!(new_reward_data.expiration, expired_reward_data.expiration);
+            assert_eq!(new_reward_data.eps, expired_reward_data.eps);
+            // -> the active token is not touched
+            let active_data = storage::get_reward_data(&e, &active_token).unwrap_optimized();
+            assert_eq!(active_data.index, 33333);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #111)")]
+    fn test_remove_reward_token_active() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let reward_data = RewardData {
+            expiration: block_timestamp + 1,
+            eps: 0_1000000,
+            index: 22222,
+            last_time: block_timestamp - 2000,
+        };
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+
+            remove_reward_token(&e, 150_0000000, &reward_token);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #110)")]
+    fn test_remove_reward_token_unknown_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let unknown_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+
+            remove_reward_token(&e, 150_0000000, &unknown_token);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #115)")]
+    fn test_remove_reward_token_too_many_removed() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        e.as_contract(&vault_address, || {
+            let mut removed_reward_tokens = Vec::new(&e);
+            for _ in 0..MAX_REWARD_TOKENS {
+                removed_reward_tokens.push_back(Address::generate(&e));
+            }
+            storage::set_removed_reward_tokens(&e, &removed_reward_tokens);
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(
+                &e,
+                &reward_token,
+                &RewardData {
+                    expiration: block_timestamp - 1000,
+                    eps: 0_1000000,
+                    index: 22222,
+                    last_time: block_timestamp - 1000,
+                },
+            );
+
+            remove_reward_token(&e, 150_0000000, &reward_token);
+        });
+    }
+
+    #[test]
+    fn test_update_rewards_removed_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let reward_data = RewardData {
+            expiration: block_timestamp - 1000,
+            eps: 0_1000000,
+            index: 6688888,
+            last_time: block_timestamp - 500,
+        };
+        e.as_contract(&vault_address, || {
+            storage::set_removed_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+            storage::set_user_rewards(
+                &e,
+                &reward_token,
+                &samwise,
+                &UserRewards {
+                    index: 22222,
+                    accrued: 1_0000000,
+                },
+            );
+
+            // -> the user's accrual up to the final index is recorded before their shares change
+            update_rewards(&e, 150_0000000, &samwise, 9_0000000);
+
+            let user_data =
+                storage::get_user_rewards(&e, &reward_token, &samwise).unwrap_optimized();
+            assert_eq!(user_data.index, 6688888);
+            assert_eq!(user_data.accrued, 1_0000000 + 5_9999994);
+            // -> the index of the removed token does not change
+            let new_reward_data = storage::get_reward_data(&e, &reward_token).unwrap_optimized();
+            assert_eq!(new_reward_data.index, 6688888);
+            assert_eq!(new_reward_data.last_time, block_timestamp - 500);
+        });
+    }
+
+    #[test]
+    fn test_claim_rewards_removed_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reward_token = e
+            .register_stellar_asset_contract_v2(bombadil.clone())
+            .address();
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+        StellarAssetClient::new(&e, &reward_token).mint(&vault_address, &100_0000000);
+
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(
+                &e,
+                &reward_token,
+                &RewardData {
+                    expiration: block_timestamp - 1000,
+                    eps: 0_1000000,
+                    index: 22222,
+                    last_time: block_timestamp - 2000,
+                },
+            );
+            storage::set_user_rewards(
+                &e,
+                &reward_token,
+                &samwise,
+                &UserRewards {
+                    index: 22222,
+                    accrued: 0,
+                },
+            );
+
+            remove_reward_token(&e, 150_0000000, &reward_token);
+            assert_eq!(
+                load_claimable_rewards(&e, &reward_token, 150_0000000, &samwise, 9_0000000),
+                5_9999994
+            );
+
+            // -> the rewards accrued up to the final index are paid, even though the user did not interact
+            //    with the vault before the token was removed
+            let claimed =
+                claim_rewards(&e, 150_0000000, &samwise, 9_0000000, &reward_token, &frodo);
+            assert_eq!(claimed, 5_9999994);
+            assert_eq!(TokenClient::new(&e, &reward_token).balance(&frodo), claimed);
+
+            let user_data =
+                storage::get_user_rewards(&e, &reward_token, &samwise).unwrap_optimized();
+            assert_eq!(user_data.index, 6688888);
+            assert_eq!(user_data.accrued, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #111)")]
+    fn test_set_rewards_removed_token() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let reward_token = e
+            .register_stellar_asset_contract_v2(bombadil.clone())
+            .address();
+        let (vault_address, _, _) = create_test_fee_vault(&e, &samwise, 0, 0_1000000, None);
+        StellarAssetClient::new(&e, &reward_token).mint(&samwise, &100_0000000);
+
+        e.as_contract(&vault_address, || {
+            storage::set_removed_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(
+                &e,
+                &reward_token,
+                &RewardData {
+                    expiration: block_timestamp - 1000,
+                    eps: 0_1000000,
+                    index: 6688888,
+                    last_time: block_timestamp - 500,
+                },
+            );
+
+            set_rewards(
+                &e,
+                &samwise,
+                150_0000000,
+                &reward_token,
+                100_0000000,
+                block_timestamp + 1000,
+            );
+        });
+    }
+
+    /********** sweep_reward_token **********/
+
+    #[test]
+    fn test_sweep_reward_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reward_token = e
+            .register_stellar_asset_contract_v2(bombadil.clone())
+            .address();
+        let other_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+        StellarAssetClient::new(&e, &reward_token).mint(&vault_address, &100_0000000);
+
+        e.as_contract(&vault_address, || {
+            storage::set_removed_reward_tokens(
+                &e,
+                &vec![&e, other_token.clone(), reward_token.clone()],
+            );
+            storage::set_reward_data(
+                &e,
+                &reward_token,
+                &RewardData {
+                    expiration: block_timestamp - 1000,
+                    eps: 0_1000000,
+                    index: 6688888,
+                    last_time: block_timestamp - 500,
+                },
+            );
+            storage::set_user_rewards(
+                &e,
+                &reward_token,
+                &samwise,
+                &UserRewards {
+                    index: 22222,
+                    accrued: 1_0000000,
+                },
+            );
+
+            let swept = sweep_reward_token(&e, &reward_token, &frodo);
+            assert_eq!(swept, 100_0000000);
+            let token_client = TokenClient::new(&e, &reward_token);
+            assert_eq!(token_client.balance(&frodo), 100_0000000);
+            assert_eq!(token_client.balance(&vault_address), 0);
+
+            assert_eq!(
+                storage::get_removed_reward_tokens(&e),
+                vec![&e, other_token.clone()]
+            );
+            assert!(storage::get_reward_token_swept(&e, &reward_token));
+            // -> the unclaimed rewards are forfeited
+            assert_eq!(
+                load_claimable_rewards(&e, &reward_token, 150_0000000, &samwise, 9_0000000),
+                0
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #110)")]
+    fn test_sweep_reward_token_not_removed() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        e.as_contract(&vault_address, || {
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+
+            sweep_reward_token(&e, &reward_token, &frodo);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #110)")]
+    fn test_claim_rewards_swept_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reward_token = e
+            .register_stellar_asset_contract_v2(bombadil.clone())
+            .address();
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        e.as_contract(&vault_address, || {
+            storage::set_removed_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(
+                &e,
+                &reward_token,
+                &RewardData {
+                    expiration: block_timestamp - 1000,
+                    eps: 0_1000000,
+                    index: 6688888,
+                    last_time: block_timestamp - 500,
+                },
+            );
+            storage::set_user_rewards(
+                &e,
+                &reward_token,
+                &samwise,
+                &UserRewards {
+                    index: 22222,
+                    accrued: 1_0000000,
+                },
+            );
+            sweep_reward_token(&e, &reward_token, &frodo);
+
+            claim_rewards(&e, 150_0000000, &samwise, 9_0000000, &reward_token, &frodo);
+        });
+    }
+
+    /********** load_reward_token **********/
+
+    #[test]
+    fn test_load_reward_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 23,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let expired_token = Address::generate(&e);
+        let active_token = Address::generate(&e);
+        let (vault_address, _, _) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        e.as_contract(&vault_address, || {
+            // -> no reward tokens
+            assert_eq!(load_reward_token(&e), None);
+
+            storage::set_reward_tokens(&e, &vec![&e, expired_token.clone(), active_token.clone()]);
+            storage::set_reward_data(
+                &e,
+                &expired_token,
+                &RewardData {
+                    expiration: block_timestamp,
+                    eps: 0_1000000,
+                    index: 22222,
+                    last_time: block_timestamp - 2000,
+                },
+            );
+            storage::set_reward_data(
+                &e,
+                &active_token,
+                &RewardData {
+                    expiration: block_timestamp + 1,
+                    eps: 0_1000000,
+                    index: 33333,
+                    last_time: block_timestamp - 2000,
+                },
+            );
+
+            // -> the first active reward token is preferred
+            assert_eq!(load_reward_token(&e), Some(active_token.clone()));
+
+            // -> the first reward token is returned if no rewards are active
+            e.ledger().set_timestamp(block_timestamp + 1);
+            assert_eq!(load_reward_token(&e), Some(expired_token.clone()));
+        });
+    }
 }
@@ -1,6 +1,12 @@
-use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env, Symbol};
+use soroban_sdk::{
+    contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env, Symbol, Vec,
+};
 
-use crate::{errors::FeeVaultError, vault::VaultData};
+use crate::{
+    errors::FeeVaultError,
+    migration::{FeeV0, VaultDataV0},
+    vault::VaultData,
+};
 
 //********** Storage Keys **********//
 
@@ -8,9 +14,25 @@ const POOL_KEY: &str = "Pool";
 const ADMIN_KEY: &str = "Admin";
 const ASSET_KEY: &str = "Asset";
 const FEE_KEY: &str = "Fee";
+const PENDING_FEE_KEY: &str = "PendFee";
+const FEE_DELAY_KEY: &str = "FeeDelay";
+const PENDING_FEE_DELAY_KEY: &str = "PendFeeDly";
+const FEE_RECIPIENTS_KEY: &str = "FeeRcpts";
 const SIGNER_KEY: &str = "Signer";
+const FEE_MANAGER_KEY: &str = "FeeMgr";
+const REWARDS_MANAGER_KEY: &str = "RwdMgr";
+const TREASURY_KEY: &str = "Treasury";
+const GUARDIAN_KEY: &str = "Guardian";
+const PENDING_ADMIN_KEY: &str = "PendAdmin";
+const PAUSE_KEY: &str = "Pause";
+const DEPOSIT_CAP_KEY: &str = "DepCap";
+const USER_DEPOSIT_CAP_KEY: &str = "UserDepCap";
 const VAULT_DATA_KEY: &str = "Vault";
+// legacy key for vaults that only supported a single reward token
 const REWARD_TOKEN_KEY: &str = "RwdToken";
+const REWARD_TOKENS_KEY: &str = "RwdTokens";
+const REMOVED_REWARD_TOKENS_KEY: &str = "RmvdRwdTkns";
+const STORAGE_VERSION_KEY: &str = "Version";
 
 #[derive(Clone)]
 #[contracttype]
@@ -18,20 +40,207 @@ pub enum FeeVaultDataKey {
     Shares(Address),
     Rwd(Address),
     UserRwd(UserRewardKey),
+    Allowance(AllowanceKey),
+    FeeBalance(Address),
+    RwdSwept(Address),
 }
 
+/// The vault's fee policy. Each component is optional, and the enabled components are applied
+/// in order on each accrual:
+/// 1. `take_rate` - the admin takes a percentage of the interest earned, adjusted by the vault's TVL
+///    if `take_rate_tiers` are set, or by the realized APR if `take_rate_bands` are set
+/// 2. `apr_cap` - the admin takes any remaining interest that exceeds the APR cap
+/// 3. `apr_floor` - the admin supplements the vault from their balance up to the APR floor
+/// 4. `management_fee` - the admin takes an annualized percentage of the vault's deposits
+///
+/// The `entry_fee` and `exit_fee` are charged on deposits and withdrawals instead of on accrual.
 #[derive(Clone)]
 #[contracttype]
 pub struct Fee {
-    /// The vault's fee mode
-    /// * 0 = take rate (admin earns a percentage of the vault's earnings)
-    /// * 1 = capped rate (vault earns at most the APR cap, with any additional returns going to the admin)
-    /// * 2 = fixed rate (vault always earns the fixed rate, with the admin either supplementing or earning the difference)
-    pub rate_type: u32,
-    /// The vault's fee rate, with 7 decimals (e.g. 1000000 = 10%)
+    /// The percentage of the vault's earnings taken by the admin, with 7 decimals (e.g. 1000000 = 10%)
+    pub take_rate: Option<u32>,
+    /// The take rates used once the vault's TVL reaches a threshold, ordered by increasing threshold. The
+    /// highest tier reached replaces the `take_rate`.
+    pub take_rate_tiers: Vec<TakeRateTier>,
+    /// The take rates used once the vault's realized APR reaches a threshold, ordered by increasing APR. The
+    /// highest band reached replaces the `take_rate`. Can't be combined with `take_rate_tiers`.
+    pub take_rate_bands: Vec<TakeRateBand>,
+    /// If true, the take rate is only charged on gains above the vault's high-water mark share price
+    pub high_water_mark: bool,
+    /// The maximum APR earned by the vault, with 7 decimals. Any additional returns go to the admin.
+    pub apr_cap: Option<u32>,
+    /// The minimum APR earned by the vault, with 7 decimals. Any shortfall is supplemented by the admin's balance.
+    pub apr_floor: Option<u32>,
+    /// The annualized fee charged on the vault's deposits, with 7 decimals, regardless of the interest earned
+    pub management_fee: Option<u32>,
+    /// The fee charged on the bTokens deposited into the vault, in basis points (e.g. 50 = 0.5%)
+    pub entry_fee: Option<u32>,
+    /// The fee charged on the bTokens withdrawn from the vault, in basis points (e.g. 50 = 0.5%)
+    pub exit_fee: Option<u32>,
+}
+
+impl Fee {
+    /// Build the fee policy for a preset rate type
+    ///
+    /// ### Arguments
+    /// * `rate_type` - The rate type
+    ///     * 0 = take rate (admin earns a percentage of the vault's earnings)
+    ///     * 1 = capped rate (vault earns at most the APR cap, with any additional returns going to the admin)
+    ///     * 2 = fixed rate (vault always earns the fixed rate, with the admin either supplementing or earning the difference)
+    ///     * 3 = management fee (admin earns an annualized percentage of the vault's deposits, regardless of the interest earned)
+    ///     * 4 = performance fee (admin earns a percentage of the vault's earnings above the high-water mark share price)
+    ///     * 5 = floor rate (vault earns at least the floor rate, supplemented by the admin, and keeps any additional returns)
+    /// * `rate` - The rate value, with 7 decimals (e.g. 1000000 for 10%)
+    ///
+    /// ### Panics
+    /// * `InvalidFeeRateType` - If the rate type is not 0, 1, 2, 3, 4, or 5
+    pub fn from_rate_type(e: &Env, rate_type: u32, rate: u32) -> Fee {
+        let mut fee = Fee {
+            take_rate: None,
+            take_rate_tiers: Vec::new(e),
+            take_rate_bands: Vec::new(e),
+            high_water_mark: false,
+            apr_cap: None,
+            apr_floor: None,
+            management_fee: None,
+            entry_fee: None,
+            exit_fee: None,
+        };
+        match rate_type {
+            0 => fee.take_rate = Some(rate),
+            1 => fee.apr_cap = Some(rate),
+            2 => {
+                fee.apr_cap = Some(rate);
+                fee.apr_floor = Some(rate);
+            }
+            3 => fee.management_fee = Some(rate),
+            4 => {
+                fee.take_rate = Some(rate);
+                fee.high_water_mark = true;
+            }
+            5 => fee.apr_floor = Some(rate),
+            _ => panic_with_error!(e, FeeVaultError::InvalidFeeRateType),
+        }
+        fee
+    }
+
+    /// Fetch the take rate charged for a vault TVL and realized APR, based on the take rate tiers and bands
+    ///
+    /// ### Arguments
+    /// * `tvl` - The vault's TVL in underlying tokens
+    /// * `apr` - The vault's realized APR, with 7 decimals
+    ///
+    /// ### Returns
+    /// * `Option<u32>` - The take rate, or None if the policy does not have a take rate
+    pub fn current_take_rate(&self, tvl: i128, apr: i128) -> Option<u32> {
+        let mut take_rate = self.take_rate?;
+        for tier in self.take_rate_tiers.iter() {
+            if tvl < tier.threshold {
+                break;
+            }
+            take_rate = tier.rate;
+        }
+        for band in self.take_rate_bands.iter() {
+            if apr < band.apr as i128 {
+                break;
+            }
+            take_rate = band.rate;
+        }
+        Some(take_rate)
+    }
+}
+
+/// A take rate applied once the vault's TVL reaches a threshold
+#[derive(Clone)]
+#[contracttype]
+pub struct TakeRateTier {
+    // The vault TVL in underlying tokens the tier starts at
+    pub threshold: i128,
+    // The take rate, with 7 decimals (e.g. 1000000 = 10%)
     pub rate: u32,
 }
 
+/// A take rate applied once the vault's realized APR reaches a threshold
+#[derive(Clone)]
+#[contracttype]
+pub struct TakeRateBand {
+    // The realized APR the band starts at, with 7 decimals (e.g. 1000000 = 10%)
+    pub apr: u32,
+    // The take rate, with 7 decimals (e.g. 1000000 = 10%)
+    pub rate: u32,
+}
+
+/// A queued fee change that has not yet been executed
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingFee {
+    // The fee the vault will use once executed
+    pub fee: Fee,
+    // The timestamp the fee change can be executed at
+    pub execute_after: u64,
+}
+
+/// A queued decrease to the fee delay that has not yet been executed
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingFeeDelay {
+    // The fee delay the vault will use once executed, in seconds
+    pub delay: u64,
+    // The timestamp the fee delay change can be executed at
+    pub execute_after: u64,
+}
+
+/// A recipient of a share of the vault's fees
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeRecipient {
+    // The address that can withdraw the recipient's share of the fees
+    pub address: Address,
+    // The recipient's share of the fees, in basis points (e.g. 2500 = 25%)
+    pub weight: u32,
+}
+
+/// The roles that can be held for the fee vault
+#[derive(Clone, Copy)]
+#[contracttype]
+#[repr(u32)]
+pub enum Role {
+    // The vault admin. Can grant and revoke all other roles.
+    Owner = 0,
+    // Can update the vault's fee
+    FeeManager = 1,
+    // Can set rewards for the vault's depositors
+    RewardsManager = 2,
+    // Can manage the admin balance and claim emissions
+    Treasury = 3,
+    // Can update the pause state alongside the owner
+    Guardian = 4,
+}
+
+/// A proposed admin that has not yet accepted the role
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdmin {
+    // The proposed admin address
+    pub admin: Address,
+    // The timestamp the proposal expires at
+    pub expiration: u64,
+}
+
+/// The vault's pause state
+#[derive(Clone)]
+#[contracttype]
+pub struct PauseState {
+    // Whether deposits are paused
+    pub deposits: bool,
+    // Whether withdrawals are paused. Ignored in emergency mode.
+    pub withdrawals: bool,
+    // Whether reward claims are paused
+    pub claims: bool,
+    // Whether the vault is in emergency mode, where only withdrawals are allowed
+    pub emergency: bool,
+}
+
 /// The vault's reward data
 #[derive(Clone)]
 #[contracttype]
@@ -63,6 +272,23 @@ pub struct UserRewards {
     pub accrued: i128,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceKey {
+    pub from: Address,
+    pub spender: Address,
+}
+
+/// The share allowance a user has granted to a spender
+#[derive(Clone)]
+#[contracttype]
+pub struct Allowance {
+    // The number of shares the spender can transfer or burn
+    pub amount: i128,
+    // The ledger sequence number the allowance expires at
+    pub expiration_ledger: u32,
+}
+
 //********** Storage Utils **********//
 
 pub const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5 seconds per ledger on average
@@ -127,7 +353,7 @@ pub fn set_asset(e: &Env, asset: Address) {
         .set::<Symbol, Address>(&Symbol::new(e, ASSET_KEY), &asset);
 }
 
-/// Get the fee mode for the fee vault
+/// Get the fee policy for the fee vault
 pub fn get_fee(e: &Env) -> Fee {
     e.storage()
         .instance()
@@ -135,13 +361,114 @@ pub fn get_fee(e: &Env) -> Fee {
         .unwrap_optimized()
 }
 
-/// Set the fee mode for the fee vault
+/// Set the fee policy for the fee vault
 pub fn set_fee(e: &Env, fee: Fee) {
     e.storage()
         .instance()
         .set::<Symbol, Fee>(&Symbol::new(e, FEE_KEY), &fee);
 }
 
+/// Get the fee mode stored with the storage version 0 layout
+pub fn get_fee_v0(e: &Env) -> FeeV0 {
+    e.storage()
+        .instance()
+        .get::<Symbol, FeeV0>(&Symbol::new(e, FEE_KEY))
+        .unwrap_optimized()
+}
+
+/// Get the pending admin. Can be None if no admin transfer has been proposed.
+pub fn get_pending_admin(e: &Env) -> Option<PendingAdmin> {
+    e.storage()
+        .instance()
+        .get::<Symbol, PendingAdmin>(&Symbol::new(e, PENDING_ADMIN_KEY))
+}
+
+/// Set the pending admin.
+pub fn set_pending_admin(e: &Env, pending_admin: &PendingAdmin) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PendingAdmin>(&Symbol::new(e, PENDING_ADMIN_KEY), pending_admin);
+}
+
+/// Delete the pending admin.
+pub fn del_pending_admin(e: &Env) {
+    e.storage()
+        .instance()
+        .remove::<Symbol>(&Symbol::new(e, PENDING_ADMIN_KEY));
+}
+
+/// Get the pending fee change. Can be None if no fee change is queued.
+pub fn get_pending_fee(e: &Env) -> Option<PendingFee> {
+    e.storage()
+        .instance()
+        .get::<Symbol, PendingFee>(&Symbol::new(e, PENDING_FEE_KEY))
+}
+
+/// Set the pending fee change
+pub fn set_pending_fee(e: &Env, pending_fee: &PendingFee) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PendingFee>(&Symbol::new(e, PENDING_FEE_KEY), pending_fee);
+}
+
+/// Delete the pending fee change
+pub fn del_pending_fee(e: &Env) {
+    e.storage()
+        .instance()
+        .remove::<Symbol>(&Symbol::new(e, PENDING_FEE_KEY));
+}
+
+/// Get the minimum delay, in seconds, between queueing and executing a fee change. Defaults to 0.
+pub fn get_fee_delay(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<Symbol, u64>(&Symbol::new(e, FEE_DELAY_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the minimum delay, in seconds, between queueing and executing a fee change
+pub fn set_fee_delay(e: &Env, delay: u64) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u64>(&Symbol::new(e, FEE_DELAY_KEY), &delay);
+}
+
+/// Get the pending fee delay decrease. Can be None if no decrease is queued.
+pub fn get_pending_fee_delay(e: &Env) -> Option<PendingFeeDelay> {
+    e.storage()
+        .instance()
+        .get::<Symbol, PendingFeeDelay>(&Symbol::new(e, PENDING_FEE_DELAY_KEY))
+}
+
+/// Set the pending fee delay decrease
+pub fn set_pending_fee_delay(e: &Env, pending_delay: &PendingFeeDelay) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PendingFeeDelay>(&Symbol::new(e, PENDING_FEE_DELAY_KEY), pending_delay);
+}
+
+/// Delete the pending fee delay decrease
+pub fn del_pending_fee_delay(e: &Env) {
+    e.storage()
+        .instance()
+        .remove::<Symbol>(&Symbol::new(e, PENDING_FEE_DELAY_KEY));
+}
+
+/// Get the fee recipients. Defaults to no recipients, where all fees accrue to the admin.
+pub fn get_fee_recipients(e: &Env) -> Vec<FeeRecipient> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Vec<FeeRecipient>>(&Symbol::new(e, FEE_RECIPIENTS_KEY))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Set the fee recipients
+pub fn set_fee_recipients(e: &Env, recipients: &Vec<FeeRecipient>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<FeeRecipient>>(&Symbol::new(e, FEE_RECIPIENTS_KEY), recipients);
+}
+
 /// Get the signer address. Can be None if no signer is set.
 pub fn get_signer(e: &Env) -> Option<Address> {
     e.storage()
@@ -163,18 +490,163 @@ pub fn del_signer(e: &Env) {
         .remove::<Symbol>(&Symbol::new(e, SIGNER_KEY));
 }
 
-/// Get the reward token address. Can be None if no reward has been set.
-pub fn get_reward_token(e: &Env) -> Option<Address> {
+fn role_key(e: &Env, role: Role) -> Symbol {
+    match role {
+        Role::Owner => Symbol::new(e, ADMIN_KEY),
+        Role::FeeManager => Symbol::new(e, FEE_MANAGER_KEY),
+        Role::RewardsManager => Symbol::new(e, REWARDS_MANAGER_KEY),
+        Role::Treasury => Symbol::new(e, TREASURY_KEY),
+        Role::Guardian => Symbol::new(e, GUARDIAN_KEY),
+    }
+}
+
+/// Get the address holding a role. Can be None if the role has not been granted.
+pub fn get_role(e: &Env, role: Role) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&role_key(e, role))
+}
+
+/// Get the address holding a role, defaulting to the admin if the role has not been granted
+pub fn get_role_or_admin(e: &Env, role: Role) -> Address {
+    get_role(e, role).unwrap_or_else(|| get_admin(e))
+}
+
+/// Set the address holding a role
+pub fn set_role(e: &Env, role: Role, address: Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&role_key(e, role), &address);
+}
+
+/// Delete the address holding a role
+pub fn del_role(e: &Env, role: Role) {
+    e.storage().instance().remove::<Symbol>(&role_key(e, role));
+}
+
+/// Get the pause state. Defaults to nothing paused.
+pub fn get_pause_state(e: &Env) -> PauseState {
+    e.storage()
+        .instance()
+        .get::<Symbol, PauseState>(&Symbol::new(e, PAUSE_KEY))
+        .unwrap_or(PauseState {
+            deposits: false,
+            withdrawals: false,
+            claims: false,
+            emergency: false,
+        })
+}
+
+/// Set the pause state
+pub fn set_pause_state(e: &Env, pause_state: &PauseState) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PauseState>(&Symbol::new(e, PAUSE_KEY), pause_state);
+}
+
+/// Get the cap on the vault's total underlying tokens. Can be None if no cap is set.
+pub fn get_deposit_cap(e: &Env) -> Option<i128> {
+    e.storage()
+        .instance()
+        .get::<Symbol, i128>(&Symbol::new(e, DEPOSIT_CAP_KEY))
+}
+
+/// Set the cap on the vault's total underlying tokens
+pub fn set_deposit_cap(e: &Env, cap: i128) {
+    e.storage()
+        .instance()
+        .set::<Symbol, i128>(&Symbol::new(e, DEPOSIT_CAP_KEY), &cap);
+}
+
+/// Delete the cap on the vault's total underlying tokens
+pub fn del_deposit_cap(e: &Env) {
+    e.storage()
+        .instance()
+        .remove::<Symbol>(&Symbol::new(e, DEPOSIT_CAP_KEY));
+}
+
+/// Get the cap on each user's underlying tokens. Can be None if no cap is set.
+pub fn get_user_deposit_cap(e: &Env) -> Option<i128> {
+    e.storage()
+        .instance()
+        .get::<Symbol, i128>(&Symbol::new(e, USER_DEPOSIT_CAP_KEY))
+}
+
+/// Set the cap on each user's underlying tokens
+pub fn set_user_deposit_cap(e: &Env, cap: i128) {
     e.storage()
+        .instance()
+        .set::<Symbol, i128>(&Symbol::new(e, USER_DEPOSIT_CAP_KEY), &cap);
+}
+
+/// Delete the cap on each user's underlying tokens
+pub fn del_user_deposit_cap(e: &Env) {
+    e.storage()
+        .instance()
+        .remove::<Symbol>(&Symbol::new(e, USER_DEPOSIT_CAP_KEY));
+}
+
+/// Get the reward token addresses. Includes vaults that only have the legacy single reward token set.
+pub fn get_reward_tokens(e: &Env) -> Vec<Address> {
+    if let Some(reward_tokens) = e
+        .storage()
+        .instance()
+        .get::<Symbol, Vec<Address>>(&Symbol::new(e, REWARD_TOKENS_KEY))
+    {
+        return reward_tokens;
+    }
+    match e
+        .storage()
         .instance()
         .get::<Symbol, Address>(&Symbol::new(e, REWARD_TOKEN_KEY))
+    {
+        Some(reward_token) => Vec::from_array(e, [reward_token]),
+        None => Vec::new(e),
+    }
+}
+
+/// Set the reward token addresses
+pub fn set_reward_tokens(e: &Env, tokens: &Vec<Address>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<Address>>(&Symbol::new(e, REWARD_TOKENS_KEY), tokens);
+}
+
+/// Get the reward tokens that were removed from the vault, but can still be claimed
+pub fn get_removed_reward_tokens(e: &Env) -> Vec<Address> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Vec<Address>>(&Symbol::new(e, REMOVED_REWARD_TOKENS_KEY))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Set the reward tokens that were removed from the vault, but can still be claimed
+pub fn set_removed_reward_tokens(e: &Env, tokens: &Vec<Address>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<Address>>(&Symbol::new(e, REMOVED_REWARD_TOKENS_KEY), tokens);
 }
 
-/// Set the reward token address
-pub fn set_reward_token(e: &Env, token: &Address) {
+/// Delete the legacy reward token, stored by vaults created before multiple reward tokens were supported
+pub fn del_legacy_reward_token(e: &Env) {
     e.storage()
         .instance()
-        .set::<Symbol, Address>(&Symbol::new(e, REWARD_TOKEN_KEY), token);
+        .remove::<Symbol>(&Symbol::new(e, REWARD_TOKEN_KEY));
+}
+
+/// Get the storage layout version. Defaults to 0 for vaults created before the version was stored.
+pub fn get_storage_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<Symbol, u32>(&Symbol::new(e, STORAGE_VERSION_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the storage layout version
+pub fn set_storage_version(e: &Env, version: u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, STORAGE_VERSION_KEY), &version);
 }
 
 /********** Persistent **********/
@@ -205,6 +677,15 @@ pub fn get_vault_data(e: &Env) -> VaultData {
         .unwrap_or_else(|| panic_with_error!(e, FeeVaultError::ReserveNotFound))
 }
 
+/// Get the vault data stored with the storage version 0 layout
+pub fn get_vault_data_v0(e: &Env) -> VaultDataV0 {
+    let key = Symbol::new(e, VAULT_DATA_KEY);
+    e.storage()
+        .persistent()
+        .get::<Symbol, VaultDataV0>(&key)
+        .unwrap_or_else(|| panic_with_error!(e, FeeVaultError::ReserveNotFound))
+}
+
 /// Set the number of vault shares a user owns. Shares are stored with 7 decimal places of precision.
 ///
 /// ### Arguments
@@ -232,6 +713,33 @@ pub fn get_vault_shares(e: &Env, user: &Address) -> i128 {
         .unwrap_or(0)
 }
 
+/// Get the bTokens owed to a fee recipient that have been split into their balance
+///
+/// ### Arguments
+/// * `recipient` - The address of the fee recipient
+pub fn get_fee_balance(e: &Env, recipient: &Address) -> i128 {
+    let key = FeeVaultDataKey::FeeBalance(recipient.clone());
+    e.storage()
+        .persistent()
+        .get::<FeeVaultDataKey, i128>(&key)
+        .unwrap_or(0)
+}
+
+/// Set the bTokens owed to a fee recipient
+///
+/// ### Arguments
+/// * `recipient` - The address of the fee recipient
+/// * `b_tokens` - The bTokens owed to the fee recipient
+pub fn set_fee_balance(e: &Env, recipient: &Address, b_tokens: i128) {
+    let key = FeeVaultDataKey::FeeBalance(recipient.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, i128>(&key, &b_tokens);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
 /// Set the reward data
 ///
 /// ### Arguments
@@ -258,6 +766,32 @@ pub fn get_reward_data(e: &Env, token: &Address) -> Option<RewardData> {
         .get::<FeeVaultDataKey, RewardData>(&key)
 }
 
+/// Check if the remaining balance of a reward token was swept, so its rewards can no longer be claimed
+///
+/// ### Arguments
+/// * `token` - The address of the reward token
+pub fn get_reward_token_swept(e: &Env, token: &Address) -> bool {
+    let key = FeeVaultDataKey::RwdSwept(token.clone());
+    e.storage()
+        .persistent()
+        .get::<FeeVaultDataKey, bool>(&key)
+        .unwrap_or(false)
+}
+
+/// Mark a reward token as swept
+///
+/// ### Arguments
+/// * `token` - The address of the reward token
+pub fn set_reward_token_swept(e: &Env, token: &Address) {
+    let key = FeeVaultDataKey::RwdSwept(token.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, bool>(&key, &true);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
 /// Set the user's reward data
 ///
 /// ### Arguments
@@ -291,3 +825,45 @@ pub fn get_user_rewards(e: &Env, user: &Address, token: &Address) -> Option<User
         .persistent()
         .get::<FeeVaultDataKey, UserRewards>(&key)
 }
+
+/********** Temporary **********/
+
+/// Get the share allowance `from` has granted to `spender`. Returns an empty allowance if none exists.
+///
+/// ### Arguments
+/// * `from` - The address of the user granting the allowance
+/// * `spender` - The address of the spender
+pub fn get_allowance(e: &Env, from: &Address, spender: &Address) -> Allowance {
+    let key = FeeVaultDataKey::Allowance(AllowanceKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    e.storage()
+        .temporary()
+        .get::<FeeVaultDataKey, Allowance>(&key)
+        .unwrap_or(Allowance {
+            amount: 0,
+            expiration_ledger: 0,
+        })
+}
+
+/// Set the share allowance `from` has granted to `spender`. The entry is kept alive until the
+/// allowance's expiration ledger if the amount is positive.
+///
+/// ### Arguments
+/// * `from` - The address of the user granting the allowance
+/// * `spender` - The address of the spender
+/// * `allowance` - The allowance
+pub fn set_allowance(e: &Env, from: &Address, spender: &Address, allowance: &Allowance) {
+    let key = FeeVaultDataKey::Allowance(AllowanceKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    e.storage()
+        .temporary()
+        .set::<FeeVaultDataKey, Allowance>(&key, allowance);
+    if allowance.amount > 0 {
+        let live_for = allowance.expiration_ledger - e.ledger().sequence();
+        e.storage().temporary().extend_ttl(&key, live_for, live_for);
+    }
+}
@@ -1,11 +1,11 @@
 use blend_contract_sdk::pool::Client as PoolClient;
 use soroban_fixed_point_math::SorobanFixedPoint;
-use soroban_sdk::{contracttype, Address, Env};
+use soroban_sdk::{contracttype, Address, Env, Map, Vec};
 
 use crate::{
     constants::{SCALAR_12, SCALAR_7},
-    rewards::load_updated_reward_data,
-    storage::{self, Fee, RewardData},
+    rewards::{load_claimable_rewards, load_updated_reward_data},
+    storage::{self, Fee, PendingFee, RewardData},
     vault::{self, VaultData},
 };
 
@@ -27,17 +27,24 @@ pub struct VaultSummary {
     pub admin: Address,
     // The signer address, or None if no signer is used
     pub signer: Option<Address>,
-    // The fee mode and rate for the vault
+    // The fee policy for the vault
     pub fee: Fee,
+    // The queued fee change, or None if no fee change is queued
+    pub pending_fee: Option<PendingFee>,
     // The vault data containing the current state of the vault
     pub vault: VaultData,
+    // The take rate charged at the vault's current TVL and estimated APR, or None if the fee policy has no take rate
+    pub take_rate: Option<u32>,
     // The estimate APR earned by suppliers to the vault
     pub est_apr: i128,
-    // The reward token address, if any
-    pub reward_token: Option<Address>,
-    // The reward data for the reward_token, if any.
-    // If none, the data contains all zeros due to Soroban option limitations
-    pub reward_data: RewardData,
+    // The reward token addresses
+    pub reward_tokens: Vec<Address>,
+    // The reward data of each reward token, keyed by the reward token address
+    pub reward_data: Map<Address, RewardData>,
+    // The cap on the vault's total underlying tokens, or None if not capped
+    pub deposit_cap: Option<i128>,
+    // The cap on each user's underlying tokens, or None if not capped
+    pub user_deposit_cap: Option<i128>,
 }
 
 impl VaultSummary {
@@ -48,14 +55,18 @@ impl VaultSummary {
         let admin = storage::get_admin(e);
         let signer = storage::get_signer(e);
         let fee = storage::get_fee(e);
+        let pending_fee = storage::get_pending_fee(e);
+        let deposit_cap = storage::get_deposit_cap(e);
+        let user_deposit_cap = storage::get_user_deposit_cap(e);
         let vault = vault::get_vault_updated(e, &pool, &asset);
 
-        let reward_token = storage::get_reward_token(e);
-        let reward_data = if let Some(unwrapped_r_token) = reward_token.clone() {
-            load_updated_reward_data(e, &unwrapped_r_token, vault.total_shares)
-        } else {
-            None
-        };
+        let reward_tokens = storage::get_reward_tokens(e);
+        let mut reward_data = Map::new(e);
+        for reward_token in reward_tokens.iter() {
+            if let Some(data) = load_updated_reward_data(e, &reward_token, vault.total_shares) {
+                reward_data.set(reward_token, data);
+            }
+        }
 
         let reserve = PoolClient::new(e, &pool).get_reserve(&asset);
         let pool_config = PoolClient::new(e, &pool).get_config();
@@ -113,34 +124,41 @@ impl VaultSummary {
             .fixed_mul_floor(e, &cur_util, &SCALAR_7)
             .fixed_mul_floor(e, &(SCALAR_7 - (pool_config.bstop_rate as i128)), &SCALAR_7);
 
-        // check vault fee to get final est apr
-        let est_apr = match fee.rate_type {
-            0 => {
-                // take rate
-                supply_apr.fixed_mul_floor(e, &(SCALAR_7 - (fee.rate as i128)), &SCALAR_7)
-            }
-            1 => {
-                // capped rate
-                if supply_apr > (fee.rate as i128) {
-                    fee.rate as i128 // capped rate
-                } else {
-                    supply_apr // no cap applied
-                }
+        let take_rate = fee.current_take_rate(
+            vault
+                .total_b_tokens
+                .fixed_mul_floor(e, &vault.b_rate, &SCALAR_12),
+            supply_apr,
+        );
+
+        // apply the vault's fee policy components in order to get the final est apr
+        let mut est_apr = supply_apr;
+        if let Some(take_rate) = take_rate {
+            // take rate
+            // with a high-water mark, no fees are taken until the share price recovers above it
+            let below_high_water_mark = fee.high_water_mark
+                && vault.total_shares > 0
+                && vault.share_price() < vault.high_water_mark;
+            if !below_high_water_mark {
+                est_apr = est_apr.fixed_mul_floor(e, &(SCALAR_7 - (take_rate as i128)), &SCALAR_7);
             }
-            2 => {
-                // fixed rate
-                // rate applies if the admin has a balance or if the supply
-                // apr exceeds the fixed rate
-                if vault.admin_balance > 0 || (fee.rate as i128) < supply_apr {
-                    fee.rate as i128
-                } else {
-                    // no admin balance and supply apr is less than the fixed rate
-                    // behaves like a capped rate vault
-                    supply_apr
-                }
+        }
+        if let Some(apr_cap) = fee.apr_cap {
+            // capped rate
+            est_apr = est_apr.min(apr_cap as i128);
+        }
+        if let Some(apr_floor) = fee.apr_floor {
+            // floor rate
+            // the floor only applies if the admin has their own fees to supplement the vault with
+            if vault.admin_fee_balance(e) > 0 && est_apr < (apr_floor as i128) {
+                est_apr = apr_floor as i128;
             }
-            _ => 0,
-        };
+        }
+        if let Some(management_fee) = fee.management_fee {
+            // management fee
+            // the fee is charged regardless of the interest earned, so the APR can be negative
+            est_apr -= management_fee as i128;
+        }
 
         VaultSummary {
             pool,
@@ -148,15 +166,83 @@ impl VaultSummary {
             admin,
             signer,
             fee,
+            pending_fee,
             vault,
+            take_rate,
             est_apr,
-            reward_token,
-            reward_data: reward_data.unwrap_or(RewardData {
-                expiration: 0,
-                eps: 0,
-                last_time: 0,
-                index: 0,
-            }),
+            reward_tokens,
+            reward_data,
+            deposit_cap,
+            user_deposit_cap,
+        }
+    }
+}
+
+/**
+ * @dev
+ *
+ * Summary of a user's position in the vault. This is intended for offchain services like a dApp to easily
+ * display information about a user. It is not intended to be used for onchain logic.
+ */
+
+#[derive(Clone)]
+#[contracttype]
+pub struct UserSummary {
+    // The user's shares
+    pub shares: i128,
+    // The user's position in bTokens
+    pub b_tokens: i128,
+    // The user's position in underlying tokens
+    pub underlying: i128,
+    // The reward token addresses
+    pub reward_tokens: Vec<Address>,
+    // The user's claimable rewards for each reward token, in the same order as `reward_tokens`
+    pub claimable_rewards: Vec<i128>,
+    // The user's share of the vault, with 7 decimals (e.g. 1000000 = 10%)
+    pub vault_share: i128,
+}
+
+impl UserSummary {
+    /// Create a new UserSummary
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    pub fn load(e: &Env, user: &Address) -> Self {
+        let pool = storage::get_pool(e);
+        let asset = storage::get_asset(e);
+        let vault = vault::get_vault_updated(e, &pool, &asset);
+        let shares = storage::get_vault_shares(e, user);
+
+        let (b_tokens, underlying, vault_share) = if shares > 0 {
+            let b_tokens = vault.shares_to_b_tokens_down(shares);
+            (
+                b_tokens,
+                vault.b_tokens_to_underlying_down(b_tokens),
+                shares.fixed_div_floor(e, &vault.total_shares, &SCALAR_7),
+            )
+        } else {
+            (0, 0, 0)
+        };
+
+        let reward_tokens = storage::get_reward_tokens(e);
+        let mut claimable_rewards = Vec::new(e);
+        for reward_token in reward_tokens.iter() {
+            claimable_rewards.push_back(load_claimable_rewards(
+                e,
+                &reward_token,
+                vault.total_shares,
+                user,
+                shares,
+            ));
+        }
+
+        UserSummary {
+            shares,
+            b_tokens,
+            underlying,
+            reward_tokens,
+            claimable_rewards,
+            vault_share,
         }
     }
 }
@@ -164,12 +250,16 @@ impl VaultSummary {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::{TakeRateBand, TakeRateTier};
     use crate::testutils::{
         assert_approx_eq_abs,
-        mockpool::{register_mock_pool_with_config_and_data, ReserveConfig, ReserveData},
+        mockpool::{
+            register_mock_pool_with_b_rate, register_mock_pool_with_config_and_data, ReserveConfig,
+            ReserveData,
+        },
         register_fee_vault, EnvTestUtils,
     };
-    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{testutils::Address as _, vec};
 
     #[test]
     fn test_vault_summary() {
@@ -180,6 +270,8 @@ mod tests {
 
         let bombadil = Address::generate(&e);
         let token = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+        let token_no_data = Address::generate(&e);
 
         let backstop_rate: u32 = 0_100_0000; // 10%
         let reserve_config = ReserveConfig {
@@ -234,6 +326,10 @@ mod tests {
                 b_rate: 1_500_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -242,20 +338,54 @@ mod tests {
             assert_eq!(summary.asset, token);
             assert_eq!(summary.admin, bombadil);
             assert_eq!(summary.signer, None);
-            assert_eq!(summary.fee.rate_type, rate_type);
-            assert_eq!(summary.fee.rate, rate);
+            assert_eq!(summary.fee.take_rate, Some(rate));
+            assert_eq!(summary.fee.apr_cap, None);
+            assert_eq!(summary.take_rate, Some(rate));
             assert_eq!(summary.vault.total_b_tokens, 1000_0000000);
             assert_eq!(summary.vault.total_shares, 1200_0000000);
             assert_eq!(summary.vault.b_rate, 1_500_000_000_000);
             assert_eq!(summary.vault.last_update_timestamp, e.ledger().timestamp());
             assert_eq!(summary.vault.admin_balance, 0);
-            assert!(summary.reward_token.is_none());
-            assert_eq!(summary.reward_data.eps, 0);
-            assert_eq!(summary.reward_data.index, 0);
-            assert_eq!(summary.reward_data.last_time, 0);
-            assert_eq!(summary.reward_data.expiration, 0);
+            assert_eq!(summary.reward_tokens.len(), 0);
+            assert_eq!(summary.reward_data.len(), 0);
             // 0.325 * 0.85 * (1 - 0.1) * (1 - 0.1)
             assert_approx_eq_abs(summary.est_apr, 0_2237625, 0_0001000);
+            assert!(summary.pending_fee.is_none());
+
+            // queued fee changes are included in the summary
+            storage::set_pending_fee(
+                &e,
+                &PendingFee {
+                    fee: Fee::from_rate_type(&e, 1, 0_050_0000),
+                    execute_after: e.ledger().timestamp() + 1000,
+                },
+            );
+            let summary = VaultSummary::load(&e);
+            assert_eq!(summary.fee.take_rate, Some(rate));
+            let pending_fee = summary.pending_fee.unwrap();
+            assert_eq!(pending_fee.fee.take_rate, None);
+            assert_eq!(pending_fee.fee.apr_cap, Some(0_050_0000));
+            assert_eq!(pending_fee.execute_after, e.ledger().timestamp() + 1000);
+
+            // reward data is keyed by reward token, so tokens without reward data are skipped
+            let reward_data = RewardData {
+                expiration: e.ledger().timestamp() + 1000,
+                eps: 0_1000000,
+                index: 22222,
+                last_time: e.ledger().timestamp(),
+            };
+            storage::set_reward_tokens(&e, &vec![&e, token_no_data.clone(), reward_token.clone()]);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+            let summary = VaultSummary::load(&e);
+            assert_eq!(
+                summary.reward_tokens,
+                vec![&e, token_no_data.clone(), reward_token.clone()]
+            );
+            assert_eq!(summary.reward_data.len(), 1);
+            assert_eq!(summary.reward_data.get(token_no_data.clone()), None);
+            let summary_reward_data = summary.reward_data.get(reward_token.clone()).unwrap();
+            assert_eq!(summary_reward_data.index, 22222);
+            assert_eq!(summary_reward_data.eps, 0_1000000);
         });
     }
 
@@ -322,6 +452,10 @@ mod tests {
                 b_rate: 1_500_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -395,6 +529,10 @@ mod tests {
                 b_rate: 1_500_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -468,6 +606,10 @@ mod tests {
                 b_rate: 1_500_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -478,6 +620,418 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_vault_summary_take_rate_and_capped_rate() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let bombadil = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        let backstop_rate: u32 = 0_200_0000;
+        let reserve_config = ReserveConfig {
+            c_factor: 900_0000,
+            decimals: 7,
+            index: 0,
+            l_factor: 900_0000,
+            max_util: 900_0000,
+            reactivity: 0,
+            r_base: 30_0000,
+            r_one: 60_0000,
+            r_two: 120_0000,
+            r_three: 5_000_0000,
+            util: 0_800_0000,
+            supply_cap: i64::MAX as i128,
+            enabled: true,
+        };
+        // 96% util, 0.75x ir mod
+        let reserve_data = ReserveData {
+            b_supply: 100_0000000,
+            b_rate: 1_500_000_000_000,
+            d_supply: 72_0000000,
+            d_rate: 2_000_000_000_000,
+            ir_mod: 0_750_0000,
+            backstop_credit: 0,
+            last_time: e.ledger().timestamp(),
+        };
+        // expected borrow ir is ~115.75%
+        let pool_client = register_mock_pool_with_config_and_data(
+            &e,
+            backstop_rate,
+            reserve_config,
+            reserve_data,
+        );
+
+        let rate_type = 1;
+        let rate = 0_150_0000;
+        let fee_vault = register_fee_vault(
+            &e,
+            &bombadil,
+            &pool_client.address,
+            &token,
+            rate_type,
+            rate,
+            None,
+        );
+
+        e.as_contract(&fee_vault, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_500_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            let mut fee = Fee::from_rate_type(&e, 0, 0_100_0000);
+            fee.apr_cap = Some(0_120_0000);
+            storage::set_fee(&e, fee);
+
+            let summary = VaultSummary::load(&e);
+            // non apr summary result validated in test_vault_summary
+            // 115.75 * 0.96 * 0.8 * (1 - 0.1), capped at 12%
+            assert_eq!(summary.est_apr, 0_120_0000);
+        });
+    }
+
+    #[test]
+    fn test_vault_summary_tiered_take_rate() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let bombadil = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        let backstop_rate: u32 = 0_100_0000; // 10%
+        let reserve_config = ReserveConfig {
+            c_factor: 900_0000,
+            decimals: 7,
+            index: 0,
+            l_factor: 900_0000,
+            max_util: 900_0000,
+            reactivity: 0,
+            r_base: 30_0000,
+            r_one: 60_0000,
+            r_two: 120_0000,
+            r_three: 5_000_0000,
+            util: 0_800_0000,
+            supply_cap: i64::MAX as i128,
+            enabled: true,
+        };
+        // 85% util, 2.5x ir mod
+        let reserve_data = ReserveData {
+            b_supply: 100_0000000,
+            b_rate: 1_500_000_000_000,
+            d_supply: 63_7500000,
+            d_rate: 2_000_000_000_000,
+            ir_mod: 2_500_0000,
+            backstop_credit: 0,
+            last_time: e.ledger().timestamp(),
+        };
+        // expected borrow ir is ~32.5%
+        let pool_client = register_mock_pool_with_config_and_data(
+            &e,
+            backstop_rate,
+            reserve_config,
+            reserve_data,
+        );
+
+        let fee_vault = register_fee_vault(
+            &e,
+            &bombadil,
+            &pool_client.address,
+            &token,
+            0,
+            0_100_0000,
+            None,
+        );
+
+        e.as_contract(&fee_vault, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_500_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            let mut fee = Fee::from_rate_type(&e, 0, 0_100_0000);
+            fee.take_rate_tiers = vec![
+                &e,
+                TakeRateTier {
+                    threshold: 1000_0000000,
+                    rate: 0_050_0000,
+                },
+                TakeRateTier {
+                    threshold: 2000_0000000,
+                    rate: 0_020_0000,
+                },
+            ];
+            storage::set_fee(&e, fee);
+
+            let summary = VaultSummary::load(&e);
+            // the vault's TVL is 1500 tokens, so the first tier applies
+            assert_eq!(summary.take_rate, Some(0_050_0000));
+            // 0.325 * 0.85 * (1 - 0.1) * (1 - 0.05)
+            assert_approx_eq_abs(summary.est_apr, 0_2361937, 0_0001000);
+        });
+    }
+
+    #[test]
+    fn test_vault_summary_banded_take_rate() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let bombadil = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        let backstop_rate: u32 = 0_100_0000; // 10%
+        let reserve_config = ReserveConfig {
+            c_factor: 900_0000,
+            decimals: 7,
+            index: 0,
+            l_factor: 900_0000,
+            max_util: 900_0000,
+            reactivity: 0,
+            r_base: 30_0000,
+            r_one: 60_0000,
+            r_two: 120_0000,
+            r_three: 5_000_0000,
+            util: 0_800_0000,
+            supply_cap: i64::MAX as i128,
+            enabled: true,
+        };
+        // 85% util, 2.5x ir mod
+        let reserve_data = ReserveData {
+            b_supply: 100_0000000,
+            b_rate: 1_500_000_000_000,
+            d_supply: 63_7500000,
+            d_rate: 2_000_000_000_000,
+            ir_mod: 2_500_0000,
+            backstop_credit: 0,
+            last_time: e.ledger().timestamp(),
+        };
+        // expected borrow ir is ~32.5%
+        let pool_client = register_mock_pool_with_config_and_data(
+            &e,
+            backstop_rate,
+            reserve_config,
+            reserve_data,
+        );
+
+        let fee_vault = register_fee_vault(
+            &e,
+            &bombadil,
+            &pool_client.address,
+            &token,
+            0,
+            0_100_0000,
+            None,
+        );
+
+        e.as_contract(&fee_vault, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_500_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            let mut fee = Fee::from_rate_type(&e, 0, 0);
+            fee.take_rate_bands = vec![
+                &e,
+                TakeRateBand {
+                    apr: 0_100_0000,
+                    rate: 0_200_0000,
+                },
+                TakeRateBand {
+                    apr: 0_300_0000,
+                    rate: 0_500_0000,
+                },
+            ];
+            storage::set_fee(&e, fee);
+
+            let summary = VaultSummary::load(&e);
+            // the estimated supply APR is ~24.9%, so the first band applies
+            assert_eq!(summary.take_rate, Some(0_200_0000));
+            // 0.325 * 0.85 * (1 - 0.1) * (1 - 0.2)
+            assert_approx_eq_abs(summary.est_apr, 0_1989000, 0_0001000);
+        });
+    }
+
+    #[test]
+    fn test_vault_summary_management_fee() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let bombadil = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        let backstop_rate: u32 = 0_200_0000;
+        let reserve_config = ReserveConfig {
+            c_factor: 900_0000,
+            decimals: 7,
+            index: 0,
+            l_factor: 900_0000,
+            max_util: 900_0000,
+            reactivity: 0,
+            r_base: 30_0000,
+            r_one: 60_0000,
+            r_two: 120_0000,
+            r_three: 5_000_0000,
+            util: 0_800_0000,
+            supply_cap: i64::MAX as i128,
+            enabled: true,
+        };
+        // 96% util, 0.75x ir mod
+        let reserve_data = ReserveData {
+            b_supply: 100_0000000,
+            b_rate: 1_500_000_000_000,
+            d_supply: 72_0000000,
+            d_rate: 2_000_000_000_000,
+            ir_mod: 0_750_0000,
+            backstop_credit: 0,
+            last_time: e.ledger().timestamp(),
+        };
+        // expected borrow ir is ~115.75%
+        let pool_client = register_mock_pool_with_config_and_data(
+            &e,
+            backstop_rate,
+            reserve_config,
+            reserve_data,
+        );
+
+        let rate_type = 3;
+        let rate = 0_020_0000;
+        let fee_vault = register_fee_vault(
+            &e,
+            &bombadil,
+            &pool_client.address,
+            &token,
+            rate_type,
+            rate,
+            None,
+        );
+
+        e.as_contract(&fee_vault, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_500_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            let summary = VaultSummary::load(&e);
+            // non apr summary result validated in test_vault_summary
+            // 115.75 * 0.96 * 0.8, less the 2% management fee
+            assert_approx_eq_abs(summary.est_apr, 0_8689600, 0_0001000);
+        });
+    }
+
+    #[test]
+    fn test_vault_summary_performance_fee_below_high_water_mark() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let bombadil = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        let backstop_rate: u32 = 0_200_0000;
+        let reserve_config = ReserveConfig {
+            c_factor: 900_0000,
+            decimals: 7,
+            index: 0,
+            l_factor: 900_0000,
+            max_util: 900_0000,
+            reactivity: 0,
+            r_base: 30_0000,
+            r_one: 60_0000,
+            r_two: 120_0000,
+            r_three: 5_000_0000,
+            util: 0_800_0000,
+            supply_cap: i64::MAX as i128,
+            enabled: true,
+        };
+        // 96% util, 0.75x ir mod
+        let reserve_data = ReserveData {
+            b_supply: 100_0000000,
+            b_rate: 1_500_000_000_000,
+            d_supply: 72_0000000,
+            d_rate: 2_000_000_000_000,
+            ir_mod: 0_750_0000,
+            backstop_credit: 0,
+            last_time: e.ledger().timestamp(),
+        };
+        // expected borrow ir is ~115.75%
+        let pool_client = register_mock_pool_with_config_and_data(
+            &e,
+            backstop_rate,
+            reserve_config,
+            reserve_data,
+        );
+
+        let rate_type = 4;
+        let rate = 0_100_0000;
+        let fee_vault = register_fee_vault(
+            &e,
+            &bombadil,
+            &pool_client.address,
+            &token,
+            rate_type,
+            rate,
+            None,
+        );
+
+        e.as_contract(&fee_vault, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_500_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 1_300_000_000_000,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            let summary = VaultSummary::load(&e);
+            // non apr summary result validated in test_vault_summary
+            // 115.75 * 0.96 * 0.8, with no fee taken until the share price recovers
+            assert_approx_eq_abs(summary.est_apr, 0_8889600, 0_0001000);
+        });
+    }
+
     #[test]
     fn test_vault_summary_fixed_rate_below_cap_and_admin_balance() {
         let e = Env::default();
@@ -541,6 +1095,87 @@ mod tests {
                 b_rate: 1_500_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 10000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            let summary = VaultSummary::load(&e);
+            // non apr summary result validated in test_vault_summary
+            // 0.09 * 0.8, boosted to 0.07
+            assert_eq!(summary.est_apr, 0_070_0000);
+        });
+    }
+
+    #[test]
+    fn test_vault_summary_floor_rate() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let bombadil = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        let backstop_rate: u32 = 0_200_0000;
+        let reserve_config = ReserveConfig {
+            c_factor: 900_0000,
+            decimals: 7,
+            index: 0,
+            l_factor: 900_0000,
+            max_util: 900_0000,
+            reactivity: 0,
+            r_base: 30_0000,
+            r_one: 60_0000,
+            r_two: 120_0000,
+            r_three: 5_000_0000,
+            util: 0_800_0000,
+            supply_cap: i64::MAX as i128,
+            enabled: true,
+        };
+        // 80% util, 1x ir mod
+        let reserve_data = ReserveData {
+            b_supply: 100_0000000,
+            b_rate: 1_500_000_000_000,
+            d_supply: 60_0000000,
+            d_rate: 2_000_000_000_000,
+            ir_mod: 1_000_0000,
+            backstop_credit: 0,
+            last_time: e.ledger().timestamp(),
+        };
+        // expected borrow ir is ~9%
+        let pool_client = register_mock_pool_with_config_and_data(
+            &e,
+            backstop_rate,
+            reserve_config,
+            reserve_data,
+        );
+
+        let rate_type = 5;
+        let rate = 0_070_0000;
+        let fee_vault = register_fee_vault(
+            &e,
+            &bombadil,
+            &pool_client.address,
+            &token,
+            rate_type,
+            rate,
+            None,
+        );
+
+        e.as_contract(&fee_vault, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_500_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 10000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -548,6 +1183,25 @@ mod tests {
             // non apr summary result validated in test_vault_summary
             // 0.09 * 0.8, boosted to 0.07
             assert_eq!(summary.est_apr, 0_070_0000);
+
+            // above the floor, users keep the full APR
+            storage::set_fee(&e, Fee::from_rate_type(&e, 5, 0_050_0000));
+            let summary = VaultSummary::load(&e);
+            // 0.09 * 0.8 * 0.8
+            assert_approx_eq_abs(summary.est_apr, 0_057_6000, 0_000_1000);
+
+            // fees owed to the fee recipients can't supplement the vault, so the floor isn't applied
+            storage::set_fee(&e, Fee::from_rate_type(&e, 5, 0_070_0000));
+            storage::set_vault_data(
+                &e,
+                &VaultData {
+                    fee_recipient_balance: 10000,
+                    ..vault_data
+                },
+            );
+            let summary = VaultSummary::load(&e);
+            // 0.09 * 0.8 * 0.8, no boost applied
+            assert_approx_eq_abs(summary.est_apr, 0_057_6000, 0_000_1000);
         });
     }
 
@@ -614,6 +1268,10 @@ mod tests {
                 b_rate: 1_500_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 5_0000000,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -621,6 +1279,73 @@ mod tests {
             // non apr summary result validated in test_vault_summary
             // 0.09 * 0.8 * 0.8, no boost applied
             assert_approx_eq_abs(summary.est_apr, 0_0576000, 0_0001000);
+            // the supplements the admin could not cover are owed to the vault
+            assert_eq!(summary.vault.deficit, 5_0000000);
+        });
+    }
+
+    #[test]
+    fn test_user_summary() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let token = Address::generate(&e);
+        let reward_token = Address::generate(&e);
+
+        let pool_client = register_mock_pool_with_b_rate(&e, 1_500_000_000_000);
+        let fee_vault = register_fee_vault(
+            &e,
+            &bombadil,
+            &pool_client.address,
+            &token,
+            0,
+            0_100_0000,
+            None,
+        );
+
+        e.as_contract(&fee_vault, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_500_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            storage::set_vault_shares(&e, &samwise, 120_0000000);
+
+            let reward_data = RewardData {
+                expiration: e.ledger().timestamp() + 1000,
+                eps: 0_1000000,
+                index: 22222,
+                last_time: e.ledger().timestamp() - 1234,
+            };
+            storage::set_reward_tokens(&e, &vec![&e, reward_token.clone()]);
+            storage::set_reward_data(&e, &reward_token, &reward_data);
+
+            let summary = UserSummary::load(&e, &samwise);
+            assert_eq!(summary.shares, 120_0000000);
+            assert_eq!(summary.b_tokens, 100_0000000);
+            assert_eq!(summary.underlying, 150_0000000);
+            assert_eq!(summary.vault_share, 0_1000000);
+            assert_eq!(summary.reward_tokens, vec![&e, reward_token.clone()]);
+            assert_eq!(summary.claimable_rewards, vec![&e, 12_6066600]);
+
+            let summary = UserSummary::load(&e, &frodo);
+            assert_eq!(summary.shares, 0);
+            assert_eq!(summary.b_tokens, 0);
+            assert_eq!(summary.underlying, 0);
+            assert_eq!(summary.vault_share, 0);
+            assert_eq!(summary.claimable_rewards, vec![&e, 0]);
         });
     }
 }
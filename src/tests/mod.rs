@@ -1,5 +1,13 @@
 mod test_default;
+mod test_deposit_caps;
 mod test_entrypoints;
 mod test_fee_accrual;
+mod test_fee_recipients;
 mod test_happy_path;
+mod test_pause;
+mod test_receivers;
 mod test_rewards;
+mod test_roles;
+mod test_slippage;
+mod test_token;
+mod test_upgrade;
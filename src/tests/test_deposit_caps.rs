@@ -0,0 +1,142 @@
+#![cfg(test)]
+
+use crate::testutils::{assert_approx_eq_abs, create_blend_pool, register_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_deposit_caps() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    usdc_client.mint(&frodo, &1_000_0000000);
+    usdc_client.mint(&samwise, &1_000_0000000);
+
+    // -> no caps are set by default
+    assert_eq!(fee_vault_client.get_deposit_caps(), (None, None));
+    let uncapped_max_deposit = fee_vault_client.max_deposit(&frodo);
+
+    /*
+     * Set the caps
+     */
+    fee_vault_client.set_deposit_caps(&Some(150_0000000), &Some(120_0000000));
+    assert_eq!(
+        e.auths()[0],
+        (
+            gandalf.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "set_deposit_caps"),
+                    vec![
+                        &e,
+                        Some(150_0000000_i128).into_val(&e),
+                        Some(120_0000000_i128).into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(
+        fee_vault_client.get_deposit_caps(),
+        (Some(150_0000000), Some(120_0000000))
+    );
+    assert_eq!(fee_vault_client.max_deposit(&frodo), 120_0000000);
+
+    // -> frodo deposits, and is limited by the user cap
+    fee_vault_client.deposit(&frodo, &100_0000000);
+    assert_approx_eq_abs(fee_vault_client.max_deposit(&frodo), 20_0000000, 0_0000010);
+    let result = fee_vault_client.try_deposit(&frodo, &30_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(117))));
+    let result = fee_vault_client.try_mint(&frodo, &30_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(117))));
+
+    // -> samwise is limited by the total cap
+    assert_approx_eq_abs(
+        fee_vault_client.max_deposit(&samwise),
+        50_0000000,
+        0_0000010,
+    );
+    let result = fee_vault_client.try_deposit(&samwise, &60_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(117))));
+    fee_vault_client.deposit(&samwise, &40_0000000);
+    assert_approx_eq_abs(
+        fee_vault_client.max_deposit(&samwise),
+        10_0000000,
+        0_0000010,
+    );
+    assert_approx_eq_abs(fee_vault_client.max_deposit(&frodo), 10_0000000, 0_0000010);
+
+    // -> withdrawing frees up capacity
+    fee_vault_client.withdraw(&frodo, &50_0000000);
+    assert_approx_eq_abs(fee_vault_client.max_deposit(&frodo), 60_0000000, 0_0000010);
+    fee_vault_client.deposit(&frodo, &50_0000000);
+
+    // -> transfers can't push the receiver above the user cap
+    let samwise_shares = fee_vault_client.balance(&samwise);
+    let result = fee_vault_client.try_transfer(&samwise, &frodo, &samwise_shares);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(117))));
+    fee_vault_client.approve(
+        &samwise,
+        &frodo,
+        &samwise_shares,
+        &(e.ledger().sequence() + 100),
+    );
+    let result = fee_vault_client.try_transfer_from(&frodo, &samwise, &frodo, &samwise_shares);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(117))));
+
+    // -> transfers within the user cap are allowed
+    let frodo_shares = fee_vault_client.balance(&frodo);
+    fee_vault_client.transfer(&frodo, &samwise, &(frodo_shares / 10));
+    assert_eq!(
+        fee_vault_client.balance(&frodo),
+        frodo_shares - frodo_shares / 10
+    );
+    assert_eq!(
+        fee_vault_client.balance(&samwise),
+        samwise_shares + frodo_shares / 10
+    );
+
+    /*
+     * Remove the caps
+     */
+    fee_vault_client.set_deposit_caps(&None, &None);
+    assert_eq!(fee_vault_client.get_deposit_caps(), (None, None));
+    assert!(fee_vault_client.max_deposit(&frodo) > 10_0000000);
+    assert!(fee_vault_client.max_deposit(&frodo) <= uncapped_max_deposit);
+    fee_vault_client.deposit(&frodo, &100_0000000);
+
+    // -> caps must be non-negative
+    let result = fee_vault_client.try_set_deposit_caps(&Some(-1), &None);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(8))));
+    let result = fee_vault_client.try_set_deposit_caps(&None, &Some(-1));
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(8))));
+}
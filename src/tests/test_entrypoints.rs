@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use crate::{
-    constants::SCALAR_12,
+    constants::{PENDING_ADMIN_EXPIRATION, SCALAR_12},
     storage,
     testutils::{assert_approx_eq_rel, mockpool, register_fee_vault, EnvTestUtils},
     vault::VaultData,
@@ -66,8 +66,8 @@ fn test_constructor_ok() {
     assert_eq!(client.get_admin(), samwise);
     assert_eq!(client.get_signer(), Some(frodo));
     let fee = client.get_fee();
-    assert_eq!(fee.rate_type, rate_type);
-    assert_eq!(fee.rate, rate);
+    assert_eq!(fee.take_rate, Some(rate));
+    assert_eq!(fee.apr_cap, None);
     let vault_data = client.get_vault();
     assert_eq!(vault_data.total_b_tokens, 0);
     assert_eq!(vault_data.total_shares, 0);
@@ -156,6 +156,10 @@ fn test_get_b_tokens() {
             b_rate: init_b_rate,
             last_update_timestamp: e.ledger().timestamp(),
             admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
         };
         storage::set_vault_data(&e, &vault_data);
 
@@ -227,6 +231,10 @@ fn test_underlying_wrappers() {
             b_rate: init_b_rate,
             last_update_timestamp: e.ledger().timestamp(),
             admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
         };
         storage::set_vault_data(&e, &vault_data);
         // samwise owns 10% of the pool, frodo owns 90%
@@ -283,6 +291,243 @@ fn test_underlying_wrappers() {
     assert_eq!(vault_client.get_underlying_tokens(&non_existent_user), 0);
 }
 
+#[test]
+fn test_previews() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+
+    let init_b_rate = 1_100_000_000_000;
+    let pool = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate).address;
+    let reserve = Address::generate(&e);
+    let rate: u32 = 100_0000;
+    let rate_type: u32 = 0;
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, rate_type, rate, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+    let mock_client = mockpool::MockPoolClient::new(&e, &pool);
+
+    e.as_contract(&vault_address, || {
+        let vault_data = VaultData {
+            total_b_tokens: 1000_0000000,
+            total_shares: 1200_0000000,
+            b_rate: init_b_rate,
+            last_update_timestamp: e.ledger().timestamp(),
+            admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
+        };
+        storage::set_vault_data(&e, &vault_data);
+    });
+
+    // previews round in favor of the vault
+    assert_eq!(vault_client.preview_deposit(&100_0000000), 109_0909090);
+    assert_eq!(vault_client.preview_mint(&100_0000000), 91_6666668);
+    assert_eq!(vault_client.preview_withdraw(&100_0000000), 109_0909092);
+    assert_eq!(vault_client.preview_redeem(&100_0000000), 91_6666666);
+
+    // b_rate is increased by 10%. `take_rate` is 10%
+    mock_client.set_b_rate(&1_210_000_000_000);
+    e.jump(5);
+
+    // previews use the updated vault
+    let vault_data = vault_client.get_vault();
+    assert_eq!(
+        vault_client.preview_deposit(&100_0000000),
+        vault_data.underlying_to_shares_down(100_0000000)
+    );
+    assert_eq!(
+        vault_client.preview_mint(&100_0000000),
+        vault_data.shares_to_underlying_up(100_0000000)
+    );
+    assert_eq!(
+        vault_client.preview_withdraw(&100_0000000),
+        vault_data.underlying_to_shares_up(100_0000000)
+    );
+    assert_eq!(
+        vault_client.preview_redeem(&100_0000000),
+        vault_data.shares_to_underlying_down(100_0000000)
+    );
+
+    // The view functions shouldn't mutate the state
+    e.as_contract(&vault_address, || {
+        let vault_data = storage::get_vault_data(&e);
+        assert_eq!(vault_data.admin_balance, 0);
+        assert_eq!(vault_data.total_b_tokens, 1000_0000000);
+        assert_eq!(vault_data.b_rate, init_b_rate);
+    });
+}
+
+#[test]
+fn test_previews_empty_vault() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+
+    let init_b_rate = 1_100_000_000_000;
+    let pool = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate).address;
+    let reserve = Address::generate(&e);
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, 0, 100_0000, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // shares are priced 1:1 with bTokens before the first deposit
+    assert_eq!(vault_client.preview_deposit(&110_0000000), 100_0000000);
+    assert_eq!(vault_client.preview_mint(&100_0000000), 110_0000000);
+    assert_eq!(vault_client.preview_withdraw(&110_0000000), 100_0000000);
+    assert_eq!(vault_client.preview_redeem(&100_0000000), 110_0000000);
+    assert_eq!(vault_client.max_withdraw(&samwise), 0);
+}
+
+#[test]
+fn test_previews_with_transaction_fees() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+
+    let init_b_rate = 1_100_000_000_000;
+    let pool = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate).address;
+    let reserve = Address::generate(&e);
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, 0, 100_0000, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    e.as_contract(&vault_address, || {
+        let vault_data = VaultData {
+            total_b_tokens: 1000_0000000,
+            total_shares: 1200_0000000,
+            b_rate: init_b_rate,
+            last_update_timestamp: e.ledger().timestamp(),
+            admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
+        };
+        storage::set_vault_data(&e, &vault_data);
+    });
+
+    // 1% entry fee and 0.5% exit fee
+    let mut policy = storage::Fee::from_rate_type(&e, 0, 100_0000);
+    policy.entry_fee = Some(100);
+    policy.exit_fee = Some(50);
+    vault_client.set_fee_policy(&policy);
+    let fee = vault_client.get_fee();
+    assert_eq!(fee.entry_fee, Some(100));
+    assert_eq!(fee.exit_fee, Some(50));
+
+    // previews include the fees
+    assert_eq!(vault_client.preview_deposit(&100_0000000), 107_9999998);
+    assert_eq!(vault_client.preview_mint(&100_0000000), 92_5925928);
+    assert_eq!(vault_client.preview_withdraw(&100_0000000), 109_6391049);
+    assert_eq!(vault_client.preview_redeem(&100_0000000), 91_2083332);
+
+    let vault_data = vault_client.get_vault();
+    assert_eq!(
+        vault_client.preview_redeem(&100_0000000),
+        vault_data.preview_redeem(Some(50), 100_0000000).0
+    );
+    assert!(
+        vault_client.preview_deposit(&100_0000000)
+            < vault_data.underlying_to_shares_down(100_0000000)
+    );
+}
+
+#[test]
+fn test_max_deposit_and_withdraw() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let reserve_config = mockpool::ReserveConfig {
+        c_factor: 900_0000,
+        decimals: 7,
+        index: 0,
+        l_factor: 900_0000,
+        max_util: 900_0000,
+        reactivity: 0,
+        r_base: 30_0000,
+        r_one: 60_0000,
+        r_two: 120_0000,
+        r_three: 5_000_0000,
+        util: 0_800_0000,
+        supply_cap: 500_0000000,
+        enabled: true,
+    };
+    // 150 tokens supplied, 127.5 tokens borrowed
+    let reserve_data = mockpool::ReserveData {
+        b_supply: 100_0000000,
+        b_rate: 1_500_000_000_000,
+        d_supply: 63_7500000,
+        d_rate: 2_000_000_000_000,
+        ir_mod: 1_000_0000,
+        backstop_credit: 0,
+        last_time: e.ledger().timestamp(),
+    };
+    let mock_client = mockpool::register_mock_pool_with_config_and_data(
+        &e,
+        0,
+        reserve_config.clone(),
+        reserve_data,
+    );
+    let pool = mock_client.address.clone();
+    let reserve = Address::generate(&e);
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, 0, 100_0000, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    e.as_contract(&vault_address, || {
+        let vault_data = VaultData {
+            total_b_tokens: 1000_0000000,
+            total_shares: 1200_0000000,
+            b_rate: 1_500_000_000_000,
+            last_update_timestamp: e.ledger().timestamp(),
+            admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
+        };
+        storage::set_vault_data(&e, &vault_data);
+        storage::set_vault_shares(&e, &samwise, 12_0000000);
+        storage::set_vault_shares(&e, &frodo, 1188_0000000);
+    });
+
+    // samwise can withdraw their full position
+    assert_eq!(vault_client.max_withdraw(&samwise), 15_0000000);
+    // frodo is limited by the reserve's available liquidity
+    assert_eq!(vault_client.max_withdraw(&frodo), 22_5000000);
+    // users without shares can't withdraw
+    let non_existent_user = Address::generate(&e);
+    assert_eq!(vault_client.max_withdraw(&non_existent_user), 0);
+
+    // deposits are limited by the reserve's supply cap
+    assert_eq!(vault_client.max_deposit(&samwise), 350_0000000);
+
+    // deposits are not possible once the supply cap is exceeded
+    let mut config = reserve_config.clone();
+    config.supply_cap = 100_0000000;
+    mock_client.set_config(&config);
+    assert_eq!(vault_client.max_deposit(&samwise), 0);
+
+    // deposits are not possible into a disabled reserve
+    let mut config = reserve_config;
+    config.enabled = false;
+    mock_client.set_config(&config);
+    assert_eq!(vault_client.max_deposit(&samwise), 0);
+}
+
 #[test]
 fn test_set_fee_mode() {
     let e = Env::default();
@@ -324,25 +569,374 @@ fn test_set_fee_mode() {
     );
     e.as_contract(&vault_address, || {
         let fee = storage::get_fee(&e);
-        assert_eq!(fee.rate_type, 1);
-        assert_eq!(fee.rate, take_rate);
+        assert_eq!(fee.take_rate, None);
+        assert_eq!(fee.apr_cap, Some(take_rate));
     });
     // Setting the value to 0 or 100% should be possible
     vault_client.set_fee(&0, &0);
     e.as_contract(&vault_address, || {
         let fee = storage::get_fee(&e);
-        assert_eq!(fee.rate_type, 0);
-        assert_eq!(fee.rate, 0);
+        assert_eq!(fee.take_rate, Some(0));
+        assert_eq!(fee.apr_cap, None);
     });
 
     vault_client.set_fee(&1, &1_000_0000);
     e.as_contract(&vault_address, || {
         let fee = storage::get_fee(&e);
-        assert_eq!(fee.rate_type, 1);
-        assert_eq!(fee.rate, 1_000_0000);
+        assert_eq!(fee.take_rate, None);
+        assert_eq!(fee.apr_cap, Some(1_000_0000));
     });
 }
 
+#[test]
+fn test_timelocked_fee_change() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+
+    let init_b_rate = 1_000_000_000_000;
+    let pool = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate).address;
+    let reserve = Address::generate(&e);
+    let rate: u32 = 100_0000;
+    let rate_type: u32 = 0;
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, rate_type, rate, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // -> no delay by default
+    assert_eq!(vault_client.get_fee_delay(), 0);
+    assert!(vault_client.get_pending_fee().is_none());
+
+    let delay: u64 = 3 * 24 * 60 * 60;
+    vault_client.set_fee_delay(&delay);
+    assert_eq!(vault_client.get_fee_delay(), delay);
+
+    // -> fees can no longer be set immediately
+    assert_eq!(
+        vault_client.try_set_fee(&0, &1_000_0000).err(),
+        Some(Ok(Error::from_contract_error(122)))
+    );
+    assert_eq!(
+        vault_client.try_execute_set_fee().err(),
+        Some(Ok(Error::from_contract_error(121)))
+    );
+    assert_eq!(
+        vault_client.try_queue_set_fee(&0, &1_000_0001).err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+
+    /*
+     * Queue and execute a fee change
+     */
+    vault_client.queue_set_fee(&1, &500_0000);
+    assert_eq!(
+        e.auths()[0],
+        (
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    vault_address.clone(),
+                    Symbol::new(&e, "queue_set_fee"),
+                    vec![&e, 1u32.into_val(&e), 500_0000u32.into_val(&e),]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let pending_fee = vault_client.get_pending_fee().unwrap();
+    assert_eq!(pending_fee.fee.take_rate, None);
+    assert_eq!(pending_fee.fee.apr_cap, Some(500_0000));
+    assert_eq!(pending_fee.execute_after, e.ledger().timestamp() + delay);
+
+    // -> the fee is unchanged until the change is executed
+    e.jump_time(delay - 1);
+    assert_eq!(
+        vault_client.try_execute_set_fee().err(),
+        Some(Ok(Error::from_contract_error(122)))
+    );
+    assert_eq!(vault_client.get_fee().take_rate, Some(rate));
+
+    e.jump_time(1);
+    vault_client.execute_set_fee();
+    let fee = vault_client.get_fee();
+    assert_eq!(fee.take_rate, None);
+    assert_eq!(fee.apr_cap, Some(500_0000));
+    assert!(vault_client.get_pending_fee().is_none());
+
+    /*
+     * Cancel a fee change
+     */
+    vault_client.queue_set_fee(&0, &1_000_0000);
+    vault_client.cancel_set_fee();
+    assert!(vault_client.get_pending_fee().is_none());
+    e.jump_time(delay);
+    assert_eq!(
+        vault_client.try_execute_set_fee().err(),
+        Some(Ok(Error::from_contract_error(121)))
+    );
+    assert_eq!(
+        vault_client.try_cancel_set_fee().err(),
+        Some(Ok(Error::from_contract_error(121)))
+    );
+    assert_eq!(vault_client.get_fee().apr_cap, Some(500_0000));
+
+    // -> removing the delay allows fees to be set immediately again once the current delay has passed
+    vault_client.set_fee_delay(&0);
+    e.jump_time(delay);
+    vault_client.execute_set_fee_delay();
+    vault_client.set_fee(&0, &200_0000);
+    assert_eq!(vault_client.get_fee().take_rate, Some(200_0000));
+}
+
+#[test]
+fn test_set_fee_delay_decrease_is_timelocked() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+
+    let init_b_rate = 1_000_000_000_000;
+    let pool = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate).address;
+    let reserve = Address::generate(&e);
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, 0, 100_0000, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    let delay: u64 = 3 * 24 * 60 * 60;
+    vault_client.set_fee_delay(&delay);
+    assert_eq!(
+        vault_client.try_execute_set_fee_delay().err(),
+        Some(Ok(Error::from_contract_error(121)))
+    );
+
+    // -> the delay can't be removed and the fee set in the same transaction
+    vault_client.set_fee_delay(&0);
+    assert_eq!(vault_client.get_fee_delay(), delay);
+    let pending_delay = vault_client.get_pending_fee_delay().unwrap();
+    assert_eq!(pending_delay.delay, 0);
+    assert_eq!(pending_delay.execute_after, e.ledger().timestamp() + delay);
+    assert_eq!(
+        vault_client.try_set_fee(&0, &1_000_0000).err(),
+        Some(Ok(Error::from_contract_error(122)))
+    );
+    assert_eq!(
+        vault_client.try_execute_set_fee_delay().err(),
+        Some(Ok(Error::from_contract_error(122)))
+    );
+
+    // -> increasing the delay takes effect immediately and cancels the queued decrease
+    vault_client.set_fee_delay(&(delay * 2));
+    assert_eq!(vault_client.get_fee_delay(), delay * 2);
+    assert!(vault_client.get_pending_fee_delay().is_none());
+
+    // -> the decrease can be executed once the current delay has passed
+    vault_client.set_fee_delay(&delay);
+    e.jump_time(delay * 2 - 1);
+    assert_eq!(
+        vault_client.try_execute_set_fee_delay().err(),
+        Some(Ok(Error::from_contract_error(122)))
+    );
+    e.jump_time(1);
+    vault_client.execute_set_fee_delay();
+    assert_eq!(vault_client.get_fee_delay(), delay);
+    assert!(vault_client.get_pending_fee_delay().is_none());
+    assert_eq!(
+        vault_client.try_set_fee(&0, &1_000_0000).err(),
+        Some(Ok(Error::from_contract_error(122)))
+    );
+}
+
+#[test]
+fn test_set_fee_policy() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+
+    let init_b_rate = 1_000_000_000_000;
+    let pool = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate).address;
+    let reserve = Address::generate(&e);
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, 0, 100_0000, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // -> the rate type presets map to fee policies
+    assert_eq!(
+        vault_client.try_set_fee(&6, &100_0000).err(),
+        Some(Ok(Error::from_contract_error(109)))
+    );
+    vault_client.set_fee(&5, &500_0000);
+    let fee = vault_client.get_fee();
+    assert_eq!(fee.take_rate, None);
+    assert_eq!(fee.apr_cap, None);
+    assert_eq!(fee.apr_floor, Some(500_0000));
+    assert_eq!(fee.management_fee, None);
+    vault_client.set_fee(&2, &500_0000);
+    let fee = vault_client.get_fee();
+    assert_eq!(fee.take_rate, None);
+    assert_eq!(fee.apr_cap, Some(500_0000));
+    assert_eq!(fee.apr_floor, Some(500_0000));
+    assert_eq!(fee.management_fee, None);
+
+    // -> a take rate combined with an APR cap
+    let mut policy = storage::Fee::from_rate_type(&e, 0, 100_0000);
+    policy.apr_cap = Some(120_0000);
+    vault_client.set_fee_policy(&policy);
+    assert_eq!(
+        e.auths()[0],
+        (
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    vault_address.clone(),
+                    Symbol::new(&e, "set_fee_policy"),
+                    vec![&e, policy.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let fee = vault_client.get_fee();
+    assert_eq!(fee.take_rate, Some(100_0000));
+    assert!(!fee.high_water_mark);
+    assert_eq!(fee.apr_cap, Some(120_0000));
+    assert_eq!(fee.apr_floor, None);
+    assert_eq!(fee.management_fee, None);
+
+    // -> invalid policies are rejected
+    let mut policy = storage::Fee::from_rate_type(&e, 1, 500_0000);
+    policy.management_fee = Some(1_000_0001);
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 1, 500_0000);
+    policy.apr_floor = Some(600_0000);
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 1, 500_0000);
+    policy.high_water_mark = true;
+    assert_eq!(
+        vault_client.try_queue_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 1, 500_0000);
+    policy.take_rate_tiers = vec![
+        &e,
+        storage::TakeRateTier {
+            threshold: 1_000_0000000,
+            rate: 50_0000,
+        },
+    ];
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 0, 100_0000);
+    policy.take_rate_tiers = vec![
+        &e,
+        storage::TakeRateTier {
+            threshold: 1_000_0000000,
+            rate: 50_0000,
+        },
+        storage::TakeRateTier {
+            threshold: 1_000_0000000,
+            rate: 20_0000,
+        },
+    ];
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 0, 100_0000);
+    policy.take_rate_tiers = vec![
+        &e,
+        storage::TakeRateTier {
+            threshold: 1_000_0000000,
+            rate: 1_000_0001,
+        },
+    ];
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 0, 0);
+    policy.take_rate_bands = vec![
+        &e,
+        storage::TakeRateBand {
+            apr: 100_0000,
+            rate: 100_0000,
+        },
+        storage::TakeRateBand {
+            apr: 50_0000,
+            rate: 200_0000,
+        },
+    ];
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 0, 0);
+    policy.take_rate_bands = vec![
+        &e,
+        storage::TakeRateBand {
+            apr: 100_0000,
+            rate: 100_0000,
+        },
+    ];
+    policy.take_rate_tiers = vec![
+        &e,
+        storage::TakeRateTier {
+            threshold: 1_000_0000000,
+            rate: 50_0000,
+        },
+    ];
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 1, 500_0000);
+    policy.entry_fee = Some(1_001);
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+    let mut policy = storage::Fee::from_rate_type(&e, 1, 500_0000);
+    policy.exit_fee = Some(1_001);
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+
+    // -> fee policies can be queued once the vault has a fee delay
+    let delay: u64 = 24 * 60 * 60;
+    vault_client.set_fee_delay(&delay);
+    let mut policy = storage::Fee::from_rate_type(&e, 3, 100_0000);
+    policy.take_rate = Some(500_0000);
+    assert_eq!(
+        vault_client.try_set_fee_policy(&policy).err(),
+        Some(Ok(Error::from_contract_error(122)))
+    );
+    vault_client.queue_set_fee_policy(&policy);
+    let pending_fee = vault_client.get_pending_fee().unwrap();
+    assert_eq!(pending_fee.fee.take_rate, Some(500_0000));
+    assert_eq!(pending_fee.fee.management_fee, Some(100_0000));
+    assert_eq!(pending_fee.execute_after, e.ledger().timestamp() + delay);
+
+    e.jump_time(delay);
+    vault_client.execute_set_fee();
+    let fee = vault_client.get_fee();
+    assert_eq!(fee.take_rate, Some(500_0000));
+    assert_eq!(fee.apr_cap, None);
+    assert_eq!(fee.management_fee, Some(100_0000));
+}
+
 #[test]
 fn test_ensure_b_rate_gets_update_pre_fee_mode_update() {
     let e = Env::default();
@@ -371,6 +965,10 @@ fn test_ensure_b_rate_gets_update_pre_fee_mode_update() {
                 b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             },
         );
 
@@ -482,6 +1080,105 @@ fn test_set_admin() {
     );
 }
 
+#[test]
+fn test_two_step_admin_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let init_b_rate = 1_000_000_000_000;
+    let pool = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate).address;
+    let reserve = Address::generate(&e);
+    let rate: u32 = 1_000_0000;
+    let rate_type: u32 = 0;
+
+    let vault_address = register_fee_vault(&e, &samwise, &pool, &reserve, rate_type, rate, None);
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // -> accepting without a proposal fails
+    let result = vault_client.try_accept_admin();
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(118))));
+    let result = vault_client.try_cancel_admin_proposal();
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(118))));
+
+    /*
+     * Propose and accept
+     */
+    vault_client.propose_admin(&frodo);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    vault_address.clone(),
+                    Symbol::new(&e, "propose_admin"),
+                    vec![&e, frodo.into_val(&e)],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    let pending_admin = vault_client.get_pending_admin().unwrap();
+    assert_eq!(pending_admin.admin, frodo);
+    assert_eq!(
+        pending_admin.expiration,
+        e.ledger().timestamp() + PENDING_ADMIN_EXPIRATION
+    );
+    // -> the admin is unchanged until the proposal is accepted
+    assert_eq!(vault_client.get_admin(), samwise);
+
+    e.jump_time(PENDING_ADMIN_EXPIRATION);
+    vault_client.accept_admin();
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            frodo.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    vault_address.clone(),
+                    Symbol::new(&e, "accept_admin"),
+                    vec![&e],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(vault_client.get_admin(), frodo);
+    assert!(vault_client.get_pending_admin().is_none());
+
+    /*
+     * Proposals expire
+     */
+    vault_client.propose_admin(&merry);
+    e.jump_time(PENDING_ADMIN_EXPIRATION + 1);
+    let result = vault_client.try_accept_admin();
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(119))));
+    assert_eq!(vault_client.get_admin(), frodo);
+
+    /*
+     * Proposals can be cancelled
+     */
+    vault_client.propose_admin(&merry);
+    vault_client.cancel_admin_proposal();
+    assert_eq!(e.auths()[0].0, frodo.clone(),);
+    assert!(vault_client.get_pending_admin().is_none());
+    let result = vault_client.try_accept_admin();
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(118))));
+
+    /*
+     * The single-step path clears any pending proposal
+     */
+    vault_client.propose_admin(&merry);
+    vault_client.set_admin(&samwise);
+    assert_eq!(vault_client.get_admin(), samwise);
+    assert!(vault_client.get_pending_admin().is_none());
+}
+
 #[test]
 fn test_set_signer() {
     let e = Env::default();
@@ -0,0 +1,238 @@
+#![cfg(test)]
+
+use crate::storage::FeeRecipient;
+use crate::testutils::{assert_approx_eq_abs, create_blend_pool, register_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_fee_recipients() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+    let pippin = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    pool_client.submit(
+        &bombadil,
+        &bombadil,
+        &bombadil,
+        &vec![
+            &e,
+            Request {
+                address: usdc.clone(),
+                amount: 200_000_0000000,
+                request_type: 2,
+            },
+            Request {
+                address: usdc.clone(),
+                amount: 100_000_0000000,
+                request_type: 4,
+            },
+        ],
+    );
+
+    usdc_client.mint(&frodo, &1_000_0000000);
+    fee_vault_client.deposit(&frodo, &1_000_0000000);
+
+    // -> all fees accrue to the admin by default
+    assert_eq!(fee_vault_client.get_fee_recipients().len(), 0);
+
+    /*
+     * Set the fee recipients
+     */
+    let recipients = vec![
+        &e,
+        FeeRecipient {
+            address: merry.clone(),
+            weight: 5000,
+        },
+        FeeRecipient {
+            address: pippin.clone(),
+            weight: 2500,
+        },
+    ];
+    fee_vault_client.set_fee_recipients(&recipients);
+    assert_eq!(
+        e.auths()[0],
+        (
+            gandalf.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "set_fee_recipients"),
+                    vec![&e, recipients.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let stored_recipients = fee_vault_client.get_fee_recipients();
+    assert_eq!(stored_recipients.len(), 2);
+    assert_eq!(stored_recipients.get_unchecked(0).address, merry);
+    assert_eq!(stored_recipients.get_unchecked(0).weight, 5000);
+    assert_eq!(stored_recipients.get_unchecked(1).address, pippin);
+    assert_eq!(stored_recipients.get_unchecked(1).weight, 2500);
+
+    // -> invalid fee splits are rejected
+    let result = fee_vault_client.try_set_fee_recipients(&vec![
+        &e,
+        FeeRecipient {
+            address: merry.clone(),
+            weight: 7500,
+        },
+        FeeRecipient {
+            address: pippin.clone(),
+            weight: 2501,
+        },
+    ]);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(124))));
+    let result = fee_vault_client.try_set_fee_recipients(&vec![
+        &e,
+        FeeRecipient {
+            address: merry.clone(),
+            weight: 0,
+        },
+    ]);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(124))));
+    let result = fee_vault_client.try_set_fee_recipients(&vec![
+        &e,
+        FeeRecipient {
+            address: merry.clone(),
+            weight: 2500,
+        },
+        FeeRecipient {
+            address: merry.clone(),
+            weight: 2500,
+        },
+    ]);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(124))));
+
+    /*
+     * Accrue interest and split the fees
+     */
+    e.jump_time(30 * 86400);
+    // supply to the pool to cause a b_rate update
+    pool_client.submit(
+        &bombadil,
+        &bombadil,
+        &bombadil,
+        &vec![
+            &e,
+            Request {
+                address: usdc.clone(),
+                amount: 1_0000000,
+                request_type: 2,
+            },
+        ],
+    );
+
+    let total_fees = fee_vault_client.get_underlying_admin_balance();
+    assert!(total_fees > 0);
+    let merry_fees = fee_vault_client.get_underlying_fee_balance(&merry);
+    let pippin_fees = fee_vault_client.get_underlying_fee_balance(&pippin);
+    assert_approx_eq_abs(merry_fees, total_fees / 2, 0_0000010);
+    assert_approx_eq_abs(pippin_fees, total_fees / 4, 0_0000010);
+    assert_eq!(fee_vault_client.get_underlying_fee_balance(&frodo), 0);
+
+    // -> the admin can only withdraw the fees not owed to a recipient
+    let admin_fees = total_fees - merry_fees - pippin_fees;
+    let result = fee_vault_client.try_admin_withdraw(&(admin_fees + 0_0000100));
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(10))));
+    fee_vault_client.admin_withdraw(&(admin_fees - 0_0000100));
+    assert_eq!(usdc_client.balance(&gandalf), admin_fees - 0_0000100);
+
+    /*
+     * Recipients withdraw their fees
+     */
+    fee_vault_client.withdraw_fees(&merry, &merry_fees);
+    assert_eq!(
+        e.auths()[0],
+        (
+            merry.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "withdraw_fees"),
+                    vec![&e, merry.to_val(), merry_fees.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(usdc_client.balance(&merry), merry_fees);
+    assert_approx_eq_abs(fee_vault_client.get_underlying_fee_balance(&merry), 0, 2);
+
+    // -> recipients can't withdraw more than their share
+    let result = fee_vault_client.try_withdraw_fees(&pippin, &(pippin_fees + 0_0000100));
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(10))));
+    let result = fee_vault_client.try_withdraw_fees(&frodo, &1_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(10))));
+
+    // -> removed recipients keep their accrued fees, but stop earning new fees
+    let pippin_b_tokens = fee_vault_client.get_fee_balance(&pippin);
+    let merry_b_tokens = fee_vault_client.get_fee_balance(&merry);
+    fee_vault_client.set_fee_recipients(&vec![
+        &e,
+        FeeRecipient {
+            address: merry.clone(),
+            weight: 5000,
+        },
+    ]);
+    e.jump_time(30 * 86400);
+    pool_client.submit(
+        &bombadil,
+        &bombadil,
+        &bombadil,
+        &vec![
+            &e,
+            Request {
+                address: usdc.clone(),
+                amount: 1_0000000,
+                request_type: 2,
+            },
+        ],
+    );
+    assert_eq!(fee_vault_client.get_fee_balance(&pippin), pippin_b_tokens);
+    assert!(fee_vault_client.get_fee_balance(&merry) > merry_b_tokens);
+
+    let pippin_fees = fee_vault_client.get_underlying_fee_balance(&pippin);
+    fee_vault_client.withdraw_fees(&pippin, &pippin_fees);
+    assert_eq!(usdc_client.balance(&pippin), pippin_fees);
+    assert_eq!(fee_vault_client.get_fee_balance(&pippin), 0);
+
+    // -> frodo's position is unaffected by the fee split
+    let frodo_underlying = fee_vault_client.get_underlying_tokens(&frodo);
+    assert!(frodo_underlying > 1_000_0000000);
+    fee_vault_client.withdraw(&frodo, &frodo_underlying);
+    assert_eq!(usdc_client.balance(&frodo), frodo_underlying);
+}
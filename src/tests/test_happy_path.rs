@@ -303,8 +303,8 @@ fn test_happy_path() {
     assert_eq!(vault_summary.asset, usdc);
     assert_eq!(vault_summary.admin, gandalf);
     assert_eq!(vault_summary.signer, Some(bombadil.clone()));
-    assert_eq!(vault_summary.fee.rate_type, 0);
-    assert_eq!(vault_summary.fee.rate, 100_0000);
+    assert_eq!(vault_summary.fee.take_rate, Some(100_0000));
+    assert_eq!(vault_summary.fee.apr_cap, None);
     let frodo_shares = fee_vault_client.get_shares(&frodo);
     let samwise_shares = fee_vault_client.get_shares(&samwise);
     assert_eq!(
@@ -0,0 +1,154 @@
+#![cfg(test)]
+
+use crate::storage::Role;
+use crate::testutils::{create_blend_pool, register_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_pause_and_emergency() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let radagast = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // -> setup rewards and a position for frodo
+    let xlm_rewards: i128 = 1_000_0000000;
+    xlm_client.mint(&gandalf, &xlm_rewards);
+    fee_vault_client.set_rewards(&xlm, &xlm_rewards, &(e.ledger().timestamp() + 1000));
+    usdc_client.mint(&frodo, &200_0000000);
+    fee_vault_client.deposit(&frodo, &100_0000000);
+    e.jump_time(100);
+
+    // -> admin grants radagast the guardian role
+    fee_vault_client.grant_role(&Role::Guardian, &radagast);
+    assert_eq!(
+        fee_vault_client.get_role(&Role::Guardian),
+        Some(radagast.clone())
+    );
+
+    /*
+     * Pause deposits and claims
+     */
+    fee_vault_client.set_pause_state(&radagast, &true, &false, &true);
+    assert_eq!(
+        e.auths()[0],
+        (
+            radagast.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "set_pause_state"),
+                    vec![
+                        &e,
+                        radagast.to_val(),
+                        true.into_val(&e),
+                        false.into_val(&e),
+                        true.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let pause_state = fee_vault_client.get_pause_state();
+    assert!(pause_state.deposits);
+    assert!(!pause_state.withdrawals);
+    assert!(pause_state.claims);
+    assert!(!pause_state.emergency);
+
+    let result = fee_vault_client.try_deposit(&frodo, &10_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    let result = fee_vault_client.try_mint(&frodo, &10_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    let result = fee_vault_client.try_claim_rewards(&frodo, &xlm, &frodo);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    let result = fee_vault_client.try_claim_all_rewards(&frodo, &frodo);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    assert_eq!(fee_vault_client.max_deposit(&frodo), 0);
+
+    // -> share transfers add to the receiver's position, so they follow the deposit pause
+    let result = fee_vault_client.try_transfer(&frodo, &radagast, &1_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    let result = fee_vault_client.try_transfer_from(&frodo, &frodo, &radagast, &1_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+
+    // -> withdrawals are still allowed
+    fee_vault_client.withdraw(&frodo, &10_0000000);
+
+    /*
+     * Pause withdrawals
+     */
+    fee_vault_client.set_pause_state(&gandalf, &true, &true, &true);
+    let result = fee_vault_client.try_withdraw(&frodo, &10_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    let result = fee_vault_client.try_redeem(&frodo, &10_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    assert_eq!(fee_vault_client.max_withdraw(&frodo), 0);
+
+    /*
+     * Emergency mode only allows withdrawals
+     */
+    fee_vault_client.set_emergency(&radagast, &true);
+    assert!(fee_vault_client.get_pause_state().emergency);
+
+    // -> unpausing deposits and claims has no effect in emergency mode
+    fee_vault_client.set_pause_state(&radagast, &false, &true, &false);
+    let result = fee_vault_client.try_deposit(&frodo, &10_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+    let result = fee_vault_client.try_claim_rewards(&frodo, &xlm, &frodo);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(116))));
+
+    // -> withdrawals are allowed, even though they are paused
+    assert!(fee_vault_client.max_withdraw(&frodo) > 0);
+    fee_vault_client.withdraw(&frodo, &10_0000000);
+    fee_vault_client.redeem(&frodo, &10_0000000);
+
+    /*
+     * Exit emergency mode and unpause
+     */
+    fee_vault_client.set_emergency(&gandalf, &false);
+    fee_vault_client.set_pause_state(&gandalf, &false, &false, &false);
+    fee_vault_client.deposit(&frodo, &10_0000000);
+    fee_vault_client.claim_rewards(&frodo, &xlm, &frodo);
+    assert!(xlm_client.balance(&frodo) > 0);
+
+    /*
+     * Only the admin or guardian can update the pause state
+     */
+    let result = fee_vault_client.try_set_pause_state(&frodo, &true, &true, &true);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(4))));
+    let result = fee_vault_client.try_set_emergency(&frodo, &true);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(4))));
+
+    // -> a removed guardian can no longer update the pause state
+    fee_vault_client.revoke_role(&Role::Guardian);
+    assert_eq!(fee_vault_client.get_role(&Role::Guardian), None);
+    let result = fee_vault_client.try_set_emergency(&radagast, &true);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(4))));
+}
@@ -0,0 +1,203 @@
+#![cfg(test)]
+
+use crate::testutils::{create_blend_pool, register_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events};
+use soroban_sdk::{vec, Address, Env, IntoVal, Symbol};
+
+#[test]
+fn test_deposit_for_and_withdraw_to() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = register_fee_vault(&e, &bombadil, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    /*
+     * Deposit for another address
+     * -> gandalf (treasury) funds frodo's position
+     */
+    let deposit_amount = 100_0000000;
+    usdc_client.mint(&gandalf, &deposit_amount);
+
+    let shares = fee_vault_client.deposit_for(&gandalf, &frodo, &deposit_amount);
+    // -> verify deposit_for auth is required from the funding address
+    let deposit_request = vec![
+        &e,
+        Request {
+            request_type: 0,
+            address: usdc.clone(),
+            amount: deposit_amount,
+        },
+    ];
+    assert_eq!(
+        e.auths(),
+        [(
+            gandalf.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "deposit_for"),
+                    vec![
+                        &e,
+                        gandalf.to_val(),
+                        frodo.to_val(),
+                        deposit_amount.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        pool.clone(),
+                        Symbol::new(&e, "submit"),
+                        vec![
+                            &e,
+                            fee_vault.to_val(),
+                            gandalf.to_val(),
+                            gandalf.to_val(),
+                            deposit_request.to_val(),
+                        ]
+                    )),
+                    sub_invocations: std::vec![AuthorizedInvocation {
+                        function: AuthorizedFunction::Contract((
+                            usdc.clone(),
+                            Symbol::new(&e, "transfer"),
+                            vec![
+                                &e,
+                                gandalf.to_val(),
+                                pool.to_val(),
+                                deposit_amount.into_val(&e)
+                            ]
+                        )),
+                        sub_invocations: std::vec![]
+                    }]
+                }]
+            }
+        )]
+    );
+
+    // -> verify the deposit event reports both the funding address and the receiver
+    let events = e.events().all().filter_by_contract(&fee_vault);
+    assert_eq!(
+        events,
+        vec![
+            &e,
+            (
+                fee_vault.clone(),
+                (
+                    Symbol::new(&e, "vault_deposit"),
+                    pool.clone(),
+                    usdc.clone(),
+                    frodo.clone()
+                )
+                    .into_val(&e),
+                (gandalf.clone(), deposit_amount, shares, shares, 0i128).into_val(&e)
+            ),
+            (
+                fee_vault.clone(),
+                (Symbol::new(&e, "mint"), frodo.clone()).into_val(&e),
+                shares.into_val(&e)
+            ),
+        ]
+    );
+
+    // -> verify deposit_for
+    assert_eq!(usdc_client.balance(&gandalf), 0);
+    assert_eq!(fee_vault_client.get_shares(&frodo), shares);
+    assert_eq!(fee_vault_client.get_shares(&gandalf), 0);
+    let vault_positions = pool_client.get_positions(&fee_vault);
+    assert_eq!(vault_positions.supply.get(0).unwrap(), shares);
+
+    /*
+     * Withdraw to another address
+     * -> frodo exits part of their position to merry
+     */
+    let withdraw_amount = 40_0000000;
+    let expected_shares = fee_vault_client.preview_withdraw(&withdraw_amount);
+    let total_b_tokens = fee_vault_client.get_vault().total_b_tokens;
+    let burnt_shares = fee_vault_client.withdraw_to(&frodo, &merry, &withdraw_amount);
+    // -> verify withdraw_to auth is required from the position owner
+    assert_eq!(
+        e.auths(),
+        [(
+            frodo.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "withdraw_to"),
+                    vec![
+                        &e,
+                        frodo.to_val(),
+                        merry.to_val(),
+                        withdraw_amount.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+
+    // -> verify the withdraw event reports both the position owner and the receiver
+    let events = e.events().all().filter_by_contract(&fee_vault);
+    let b_tokens = total_b_tokens - fee_vault_client.get_vault().total_b_tokens;
+    assert_eq!(
+        events,
+        vec![
+            &e,
+            (
+                fee_vault.clone(),
+                (
+                    Symbol::new(&e, "vault_withdraw"),
+                    pool.clone(),
+                    usdc.clone(),
+                    frodo.clone()
+                )
+                    .into_val(&e),
+                (
+                    merry.clone(),
+                    withdraw_amount,
+                    burnt_shares,
+                    b_tokens,
+                    0i128
+                )
+                    .into_val(&e)
+            ),
+            (
+                fee_vault.clone(),
+                (Symbol::new(&e, "burn"), frodo.clone()).into_val(&e),
+                burnt_shares.into_val(&e)
+            ),
+        ]
+    );
+
+    // -> verify withdraw_to
+    assert_eq!(burnt_shares, expected_shares);
+    assert_eq!(usdc_client.balance(&merry), withdraw_amount);
+    assert_eq!(usdc_client.balance(&frodo), 0);
+    assert_eq!(fee_vault_client.get_shares(&frodo), shares - burnt_shares);
+    assert_eq!(fee_vault_client.get_shares(&merry), 0);
+}
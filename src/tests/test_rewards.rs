@@ -6,7 +6,7 @@ use blend_contract_sdk::pool::{Client as PoolClient, Request};
 use blend_contract_sdk::testutils::BlendFixture;
 use sep_41_token::testutils::MockTokenClient;
 use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
-use soroban_sdk::{vec, Address, Env, IntoVal, Symbol};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
 
 #[test]
 fn test_rewards() {
@@ -124,8 +124,8 @@ fn test_rewards() {
             }
         )
     );
-    let reward_token_result = fee_vault_client.get_reward_token().unwrap();
-    assert_eq!(reward_token_result, xlm);
+    let reward_tokens_result = fee_vault_client.get_reward_tokens();
+    assert_eq!(reward_tokens_result, vec![&e, xlm.clone()]);
     let reward_data = fee_vault_client.get_reward_data(&xlm).unwrap();
     assert_eq!(reward_data.index, 0);
     assert_eq!(reward_data.last_time, e.ledger().timestamp());
@@ -143,18 +143,19 @@ fn test_rewards() {
     // -> validate `get_vault_summary` and `get_reward_data` return updated values for the rewards
     let vault_summary = fee_vault_client.get_vault_summary();
     let updated_reward_data = fee_vault_client.get_reward_data(&xlm).unwrap();
-    assert_eq!(vault_summary.reward_token, Some(xlm.clone()));
-    assert_eq!(vault_summary.reward_data.eps, reward_data.eps);
-    assert!(vault_summary.reward_data.index > 0);
-    assert_eq!(vault_summary.reward_data.last_time, e.ledger().timestamp());
-    assert_eq!(vault_summary.reward_data.expiration, reward_data.expiration);
+    let summary_reward_data = vault_summary.reward_data.get(xlm.clone()).unwrap();
+    assert_eq!(vault_summary.reward_tokens, vec![&e, xlm.clone()]);
+    assert_eq!(summary_reward_data.eps, reward_data.eps);
+    assert!(summary_reward_data.index > 0);
+    assert_eq!(summary_reward_data.last_time, e.ledger().timestamp());
+    assert_eq!(summary_reward_data.expiration, reward_data.expiration);
 
     assert_eq!(updated_reward_data.eps, reward_data.eps);
     assert!(updated_reward_data.index > 0);
     assert_eq!(updated_reward_data.last_time, e.ledger().timestamp());
     assert_eq!(updated_reward_data.expiration, reward_data.expiration);
 
-    assert_eq!(updated_reward_data.index, vault_summary.reward_data.index);
+    assert_eq!(updated_reward_data.index, summary_reward_data.index);
 
     // -> samwise deposits ~200 USDC into the fee vault
     // -> use double frodo's balance to remove interest rate effects
@@ -212,8 +213,8 @@ fn test_rewards() {
         &blnd_rewards,
         &(e.ledger().timestamp() + blnd_reward_period),
     );
-    let reward_token_result = fee_vault_client.get_reward_token().unwrap();
-    assert_eq!(reward_token_result, blnd);
+    let reward_tokens_result = fee_vault_client.get_reward_tokens();
+    assert_eq!(reward_tokens_result, vec![&e, xlm.clone(), blnd.clone()]);
     let reward_data = fee_vault_client.get_reward_data(&blnd).unwrap();
     assert_eq!(reward_data.index, 0);
     assert_eq!(reward_data.last_time, e.ledger().timestamp());
@@ -287,8 +288,8 @@ fn test_rewards() {
         &xlm_rewards_0,
         &(e.ledger().timestamp() + xlm_reward_period),
     );
-    let reward_token_result = fee_vault_client.get_reward_token().unwrap();
-    assert_eq!(reward_token_result, xlm);
+    let reward_tokens_result = fee_vault_client.get_reward_tokens();
+    assert_eq!(reward_tokens_result, vec![&e, xlm.clone(), blnd.clone()]);
     let reward_data = fee_vault_client.get_reward_data(&xlm).unwrap();
     assert_eq!(reward_data.index, xlm_reward_data_post_claim.index);
     assert_eq!(reward_data.last_time, e.ledger().timestamp());
@@ -342,8 +343,8 @@ fn test_rewards() {
         &xlm_rewards_1,
         &(e.ledger().timestamp() + xlm_reward_period),
     );
-    let reward_token_result = fee_vault_client.get_reward_token().unwrap();
-    assert_eq!(reward_token_result, xlm);
+    let reward_tokens_result = fee_vault_client.get_reward_tokens();
+    assert_eq!(reward_tokens_result, vec![&e, xlm.clone(), blnd.clone()]);
     let reward_data = fee_vault_client.get_reward_data(&xlm).unwrap();
     assert_eq!(reward_data.index, pre_boost_xlm_rewards.index);
     assert_eq!(reward_data.last_time, e.ledger().timestamp());
@@ -394,3 +395,300 @@ fn test_rewards() {
         xlm_balance_vault_2 + xlm_rewards_1 - (frodo_claimed_4 + samwise_claimed_4)
     );
 }
+
+#[test]
+fn test_concurrent_rewards() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let blnd_client = MockTokenClient::new(&e, &blnd);
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    PoolClient::new(&e, &pool)
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // -> frodo deposits into the vault
+    let frodo_deposit = 100_0000000;
+    usdc_client.mint(&frodo, &frodo_deposit);
+    fee_vault_client.deposit(&frodo, &frodo_deposit);
+
+    // -> start XLM and BLND rewards over different periods
+    let xlm_rewards: i128 = 10_000_0000000;
+    let xlm_reward_period: u64 = 100_000;
+    xlm_client.mint(&gandalf, &xlm_rewards);
+    fee_vault_client.set_rewards(
+        &xlm,
+        &xlm_rewards,
+        &(e.ledger().timestamp() + xlm_reward_period),
+    );
+    let blnd_rewards: i128 = 1_000_0000000;
+    let blnd_reward_period: u64 = 50_000;
+    blnd_client.mint(&gandalf, &blnd_rewards);
+    fee_vault_client.set_rewards(
+        &blnd,
+        &blnd_rewards,
+        &(e.ledger().timestamp() + blnd_reward_period),
+    );
+    assert_eq!(
+        fee_vault_client.get_reward_tokens(),
+        vec![&e, xlm.clone(), blnd.clone()]
+    );
+    let vault_summary = fee_vault_client.get_vault_summary();
+    assert_eq!(
+        vault_summary.reward_tokens,
+        vec![&e, xlm.clone(), blnd.clone()]
+    );
+    assert_eq!(vault_summary.reward_data.len(), 2);
+    assert_eq!(
+        vault_summary.reward_data.get(xlm.clone()).unwrap().eps,
+        0_1000000
+    );
+    assert_eq!(
+        vault_summary.reward_data.get(blnd.clone()).unwrap().eps,
+        0_0200000
+    );
+
+    // -> samwise deposits the same amount as frodo halfway through the BLND period
+    e.jump_time(blnd_reward_period / 2);
+    let samwise_deposit = fee_vault_client.get_underlying_tokens(&frodo);
+    usdc_client.mint(&samwise, &samwise_deposit);
+    fee_vault_client.deposit(&samwise, &samwise_deposit);
+
+    // -> skip to the end of the XLM period
+    e.jump_time(xlm_reward_period - blnd_reward_period / 2);
+
+    // -> frodo earns all rewards until samwise deposits, and half of the rest of each reward
+    let frodo_claimable_xlm = fee_vault_client.get_claimable_rewards(&frodo, &xlm);
+    let frodo_claimable_blnd = fee_vault_client.get_claimable_rewards(&frodo, &blnd);
+    let frodo_xlm = fee_vault_client.claim_rewards(&frodo, &xlm, &frodo);
+    let frodo_blnd = fee_vault_client.claim_rewards(&frodo, &blnd, &frodo);
+    assert_eq!(frodo_claimable_xlm, frodo_xlm);
+    assert_eq!(frodo_claimable_blnd, frodo_blnd);
+    assert_eq!(fee_vault_client.get_claimable_rewards(&frodo, &xlm), 0);
+    let frodo_rewards = fee_vault_client.get_rewards(&frodo, &xlm).unwrap();
+    assert_eq!(frodo_rewards.accrued, 0);
+    assert_eq!(
+        frodo_rewards.index,
+        fee_vault_client.get_reward_data(&xlm).unwrap().index
+    );
+    let samwise_claimed = fee_vault_client.claim_all_rewards(&samwise, &samwise);
+    assert_eq!(
+        e.auths()[0],
+        (
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "claim_all_rewards"),
+                    vec![&e, samwise.to_val(), samwise.to_val()]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(samwise_claimed.len(), 2);
+    let samwise_xlm = samwise_claimed.get(0).unwrap();
+    let samwise_blnd = samwise_claimed.get(1).unwrap();
+    assert_approx_eq_abs(frodo_xlm, 6250_0000000, 0_0001000);
+    assert_approx_eq_abs(samwise_xlm, 3750_0000000, 0_0001000);
+    assert_approx_eq_abs(frodo_blnd, 750_0000000, 0_0001000);
+    assert_approx_eq_abs(samwise_blnd, 250_0000000, 0_0001000);
+    assert_eq!(xlm_client.balance(&frodo), frodo_xlm);
+    assert_eq!(xlm_client.balance(&samwise), samwise_xlm);
+    assert_eq!(blnd_client.balance(&samwise), samwise_blnd);
+
+    // -> nothing left to claim
+    let samwise_claimed = fee_vault_client.claim_all_rewards(&samwise, &samwise);
+    assert_eq!(samwise_claimed, vec![&e, 0, 0]);
+
+    /*
+     * Remove an expired reward token
+     */
+
+    // -> with no active rewards, the first reward token is the primary reward token
+    assert_eq!(fee_vault_client.get_reward_token(), Some(xlm.clone()));
+
+    // -> restart the BLND rewards, so it becomes the primary reward token
+    blnd_client.mint(&gandalf, &blnd_rewards);
+    fee_vault_client.set_rewards(
+        &blnd,
+        &blnd_rewards,
+        &(e.ledger().timestamp() + blnd_reward_period),
+    );
+    assert_eq!(fee_vault_client.get_reward_token(), Some(blnd.clone()));
+
+    // -> active reward tokens can't be removed
+    let result = fee_vault_client.try_remove_reward_token(&blnd);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(111))));
+
+    fee_vault_client.remove_reward_token(&xlm);
+    assert_eq!(
+        e.auths()[0],
+        (
+            gandalf.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "remove_reward_token"),
+                    vec![&e, xlm.to_val()]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(fee_vault_client.get_reward_tokens(), vec![&e, blnd.clone()]);
+    assert_eq!(
+        fee_vault_client.get_vault_summary().reward_tokens,
+        vec![&e, blnd.clone()]
+    );
+
+    // -> the removed token can't be removed again, but can still be claimed
+    let result = fee_vault_client.try_remove_reward_token(&xlm);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(110))));
+    assert_eq!(fee_vault_client.claim_rewards(&frodo, &xlm, &frodo), 0);
+    assert_eq!(
+        fee_vault_client.get_removed_reward_tokens(),
+        vec![&e, xlm.clone()]
+    );
+    assert_eq!(fee_vault_client.claim_all_rewards(&frodo, &frodo).len(), 2);
+
+    // -> the removed token can't be distributed again
+    xlm_client.mint(&gandalf, &xlm_rewards);
+    let result = fee_vault_client.try_set_rewards(
+        &xlm,
+        &xlm_rewards,
+        &(e.ledger().timestamp() + xlm_reward_period),
+    );
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(111))));
+
+    // -> the remaining balance of the removed token can be swept
+    let remaining_xlm = xlm_client.balance(&fee_vault);
+    let gandalf_xlm = xlm_client.balance(&gandalf);
+    let swept = fee_vault_client.sweep_reward_token(&xlm, &gandalf);
+    assert_eq!(
+        e.auths()[0],
+        (
+            gandalf.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "sweep_reward_token"),
+                    vec![&e, xlm.to_val(), gandalf.to_val()]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(swept, remaining_xlm);
+    assert_eq!(xlm_client.balance(&fee_vault), 0);
+    assert_eq!(xlm_client.balance(&gandalf), gandalf_xlm + remaining_xlm);
+
+    // -> the swept token can no longer be claimed
+    let result = fee_vault_client.try_claim_rewards(&frodo, &xlm, &frodo);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(110))));
+}
+
+#[test]
+fn test_claim_all_rewards_removed_token() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let blnd_client = MockTokenClient::new(&e, &blnd);
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // -> frodo deposits and earns the full XLM rewards without claiming them
+    usdc_client.mint(&frodo, &100_0000000);
+    fee_vault_client.deposit(&frodo, &100_0000000);
+    let xlm_rewards: i128 = 1_000_0000000;
+    let xlm_reward_period: u64 = 10_000;
+    xlm_client.mint(&gandalf, &xlm_rewards);
+    fee_vault_client.set_rewards(
+        &xlm,
+        &xlm_rewards,
+        &(e.ledger().timestamp() + xlm_reward_period),
+    );
+    e.jump_time(xlm_reward_period + 1);
+
+    // -> XLM is removed and replaced by BLND rewards
+    fee_vault_client.remove_reward_token(&xlm);
+    let blnd_rewards: i128 = 500_0000000;
+    let blnd_reward_period: u64 = 10_000;
+    blnd_client.mint(&gandalf, &blnd_rewards);
+    fee_vault_client.set_rewards(
+        &blnd,
+        &blnd_rewards,
+        &(e.ledger().timestamp() + blnd_reward_period),
+    );
+    e.jump_time(blnd_reward_period);
+
+    // -> claiming all rewards also claims the removed XLM rewards
+    let claimed = fee_vault_client.claim_all_rewards(&frodo, &frodo);
+    assert_eq!(claimed.len(), 2);
+    assert_approx_eq_abs(claimed.get_unchecked(0), blnd_rewards, 0_0001000);
+    assert_approx_eq_abs(claimed.get_unchecked(1), xlm_rewards, 0_0001000);
+    assert_eq!(blnd_client.balance(&frodo), claimed.get_unchecked(0));
+    assert_eq!(xlm_client.balance(&frodo), claimed.get_unchecked(1));
+
+    // -> nothing left to claim
+    let claimed = fee_vault_client.claim_all_rewards(&frodo, &frodo);
+    assert_eq!(claimed, vec![&e, 0, 0]);
+}
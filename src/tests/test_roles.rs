@@ -0,0 +1,147 @@
+#![cfg(test)]
+
+use crate::storage::Role;
+use crate::testutils::{create_blend_pool, register_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_roles() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+    let pippin = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // -> the owner is the admin, and no other roles are granted by default
+    assert_eq!(
+        fee_vault_client.get_role(&Role::Owner),
+        Some(gandalf.clone())
+    );
+    assert_eq!(fee_vault_client.get_role(&Role::FeeManager), None);
+    assert_eq!(fee_vault_client.get_role(&Role::RewardsManager), None);
+    assert_eq!(fee_vault_client.get_role(&Role::Treasury), None);
+    assert_eq!(fee_vault_client.get_role(&Role::Guardian), None);
+
+    /*
+     * Fee manager
+     */
+    fee_vault_client.grant_role(&Role::FeeManager, &samwise);
+    let authorized_function = AuthorizedInvocation {
+        function: AuthorizedFunction::Contract((
+            fee_vault.clone(),
+            Symbol::new(&e, "grant_role"),
+            vec![&e, Role::FeeManager.into_val(&e), samwise.into_val(&e)],
+        )),
+        sub_invocations: std::vec![],
+    };
+    // -> both the owner and the new role holder must sign
+    assert_eq!(
+        e.auths(),
+        std::vec![
+            (gandalf.clone(), authorized_function.clone()),
+            (samwise.clone(), authorized_function)
+        ]
+    );
+    assert_eq!(
+        fee_vault_client.get_role(&Role::FeeManager),
+        Some(samwise.clone())
+    );
+
+    fee_vault_client.set_fee(&0, &200_0000);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "set_fee"),
+                    vec![&e, 0_u32.into_val(&e), 200_0000_u32.into_val(&e)],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(fee_vault_client.get_fee().take_rate, Some(200_0000));
+
+    /*
+     * Rewards manager
+     */
+    fee_vault_client.grant_role(&Role::RewardsManager, &merry);
+    xlm_client.mint(&merry, &1_000_0000000);
+    fee_vault_client.set_rewards(&xlm, &1_000_0000000, &(e.ledger().timestamp() + 1000));
+    assert_eq!(e.auths()[0].0, merry);
+    // -> the rewards are funded by the rewards manager
+    assert_eq!(xlm_client.balance(&merry), 0);
+    assert_eq!(xlm_client.balance(&fee_vault), 1_000_0000000);
+
+    // -> expired reward tokens are removed by the rewards manager
+    e.jump(201);
+    fee_vault_client.remove_reward_token(&xlm);
+    assert_eq!(e.auths()[0].0, merry);
+    assert_eq!(
+        fee_vault_client.get_removed_reward_tokens(),
+        vec![&e, xlm.clone()]
+    );
+
+    /*
+     * Treasury
+     */
+    fee_vault_client.grant_role(&Role::Treasury, &pippin);
+    usdc_client.mint(&pippin, &100_0000000);
+    fee_vault_client.admin_deposit(&100_0000000);
+    assert_eq!(e.auths()[0].0, pippin);
+    assert_eq!(usdc_client.balance(&pippin), 0);
+
+    fee_vault_client.admin_withdraw(&50_0000000);
+    assert_eq!(e.auths()[0].0, pippin);
+    assert_eq!(usdc_client.balance(&pippin), 50_0000000);
+
+    fee_vault_client.claim_emissions(&vec![&e, 1], &pippin);
+    assert_eq!(e.auths()[0].0, pippin);
+
+    // -> revoked roles are held by the owner
+    fee_vault_client.revoke_role(&Role::Treasury);
+    assert_eq!(fee_vault_client.get_role(&Role::Treasury), None);
+    fee_vault_client.admin_withdraw(&10_0000000);
+    assert_eq!(e.auths()[0].0, gandalf);
+    assert_eq!(usdc_client.balance(&gandalf), 10_0000000);
+
+    /*
+     * The owner role can't be granted or revoked
+     */
+    let result = fee_vault_client.try_grant_role(&Role::Owner, &samwise);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(120))));
+    let result = fee_vault_client.try_revoke_role(&Role::Owner);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(120))));
+    assert_eq!(
+        fee_vault_client.get_role(&Role::Owner),
+        Some(gandalf.clone())
+    );
+}
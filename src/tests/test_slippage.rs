@@ -0,0 +1,156 @@
+#![cfg(test)]
+
+use crate::storage::ONE_DAY_LEDGERS;
+use crate::testutils::{create_blend_pool, register_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env, Error};
+
+#[test]
+fn test_slippage_protection() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = register_fee_vault(&e, &bombadil, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bombadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    let starting_balance = 100_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    usdc_client.mint(&samwise, &starting_balance);
+    fee_vault_client.deposit(&frodo, &starting_balance);
+
+    e.jump(ONE_DAY_LEDGERS);
+
+    /*
+     * Deposit with min shares
+     * -> verify a deposit past the deadline fails
+     * -> verify a deposit minting less than the minimum fails
+     * -> verify a deposit minting the previewed shares succeeds
+     */
+    let deposit_amount = 50_0000000;
+    let deadline = e.ledger().timestamp() + 60;
+    let quoted_shares = fee_vault_client.preview_deposit(&deposit_amount);
+
+    let result = fee_vault_client.try_deposit_with_min_shares(
+        &samwise,
+        &deposit_amount,
+        &quoted_shares,
+        &(e.ledger().timestamp() - 1),
+    );
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(114))));
+
+    let result = fee_vault_client.try_deposit_with_min_shares(
+        &samwise,
+        &deposit_amount,
+        &(quoted_shares + 1),
+        &deadline,
+    );
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(113))));
+
+    let shares = fee_vault_client.deposit_with_min_shares(
+        &samwise,
+        &deposit_amount,
+        &quoted_shares,
+        &deadline,
+    );
+    assert_eq!(shares, quoted_shares);
+    assert_eq!(fee_vault_client.get_shares(&samwise), quoted_shares);
+    assert_eq!(
+        usdc_client.balance(&samwise),
+        starting_balance - deposit_amount
+    );
+
+    // -> verify interest accrued after the quote mints less shares than quoted
+    let quoted_shares = fee_vault_client.preview_deposit(&deposit_amount);
+    e.jump(ONE_DAY_LEDGERS);
+    let result = fee_vault_client.try_deposit_with_min_shares(
+        &samwise,
+        &deposit_amount,
+        &quoted_shares,
+        &(e.ledger().timestamp() + 60),
+    );
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(113))));
+
+    /*
+     * Withdraw with max shares
+     * -> verify a withdraw past the deadline fails
+     * -> verify a withdraw burning more than the maximum fails
+     * -> verify a withdraw burning the previewed shares succeeds
+     */
+    let withdraw_amount = 25_0000000;
+    let deadline = e.ledger().timestamp() + 60;
+    let quoted_shares = fee_vault_client.preview_withdraw(&withdraw_amount);
+
+    let result = fee_vault_client.try_withdraw_with_max_shares(
+        &frodo,
+        &withdraw_amount,
+        &quoted_shares,
+        &(e.ledger().timestamp() - 1),
+    );
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(114))));
+
+    let result = fee_vault_client.try_withdraw_with_max_shares(
+        &frodo,
+        &withdraw_amount,
+        &(quoted_shares - 1),
+        &deadline,
+    );
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(113))));
+
+    let frodo_shares = fee_vault_client.get_shares(&frodo);
+    let burnt_shares = fee_vault_client.withdraw_with_max_shares(
+        &frodo,
+        &withdraw_amount,
+        &quoted_shares,
+        &deadline,
+    );
+    assert_eq!(burnt_shares, quoted_shares);
+    assert_eq!(
+        fee_vault_client.get_shares(&frodo),
+        frodo_shares - quoted_shares
+    );
+    assert_eq!(usdc_client.balance(&frodo), withdraw_amount);
+}
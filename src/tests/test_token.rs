@@ -0,0 +1,420 @@
+#![cfg(test)]
+
+use crate::testutils::{assert_approx_eq_abs, create_blend_pool, register_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{
+    Address as _, AuthorizedFunction, AuthorizedInvocation, Events, Ledger,
+};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, String, Symbol};
+use std::string::ToString;
+
+#[test]
+fn test_share_token() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // -> verify metadata is derived from the underlying asset
+    let mut expected_symbol = std::string::String::from("fv");
+    expected_symbol.push_str(&usdc_client.symbol().to_string());
+    let mut expected_name = std::string::String::from("Fee Vault ");
+    expected_name.push_str(&usdc_client.symbol().to_string());
+    assert_eq!(fee_vault_client.decimals(), 7);
+    assert_eq!(
+        fee_vault_client.symbol(),
+        String::from_str(&e, &expected_symbol)
+    );
+    assert_eq!(
+        fee_vault_client.name(),
+        String::from_str(&e, &expected_name)
+    );
+
+    /*
+     * Transfer shares with active rewards
+     */
+    let frodo_deposit = 100_0000000;
+    usdc_client.mint(&frodo, &frodo_deposit);
+    let frodo_shares = fee_vault_client.deposit(&frodo, &frodo_deposit);
+    assert_eq!(fee_vault_client.balance(&frodo), frodo_shares);
+
+    let xlm_rewards: i128 = 1_000_0000000;
+    let xlm_reward_period: u64 = 1000;
+    xlm_client.mint(&gandalf, &xlm_rewards);
+    fee_vault_client.set_rewards(
+        &xlm,
+        &xlm_rewards,
+        &(e.ledger().timestamp() + xlm_reward_period),
+    );
+
+    e.jump_time(xlm_reward_period / 2);
+
+    // -> frodo sends half of their shares to samwise
+    let transfer_amount = frodo_shares / 2;
+    fee_vault_client.transfer(&frodo, &samwise, &transfer_amount);
+    assert_eq!(
+        e.auths()[0],
+        (
+            frodo.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "transfer"),
+                    vec![
+                        &e,
+                        frodo.to_val(),
+                        samwise.to_val(),
+                        transfer_amount.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(
+        fee_vault_client.balance(&frodo),
+        frodo_shares - transfer_amount
+    );
+    assert_eq!(fee_vault_client.balance(&samwise), transfer_amount);
+    assert_eq!(fee_vault_client.get_vault().total_shares, frodo_shares);
+
+    e.jump_time(xlm_reward_period / 2);
+
+    // -> frodo earns all of the first half, samwise only earns after the transfer
+    let frodo_claimed = fee_vault_client.claim_rewards(&frodo, &xlm, &frodo);
+    let samwise_claimed = fee_vault_client.claim_rewards(&samwise, &xlm, &samwise);
+    assert_approx_eq_abs(frodo_claimed, 750_0000000, 0_0001000);
+    assert_approx_eq_abs(samwise_claimed, 250_0000000, 0_0001000);
+
+    // -> samwise can withdraw the transferred position
+    let samwise_underlying = fee_vault_client.get_underlying_tokens(&samwise);
+    assert_approx_eq_abs(samwise_underlying, frodo_deposit / 2, 0_0000010);
+
+    /*
+     * Allowances
+     */
+    let expiration_ledger = e.ledger().sequence() + 100;
+    fee_vault_client.approve(&samwise, &merry, &10_0000000, &expiration_ledger);
+    assert_eq!(fee_vault_client.allowance(&samwise, &merry), 10_0000000);
+
+    fee_vault_client.transfer_from(&merry, &samwise, &merry, &4_0000000);
+    assert_eq!(
+        e.auths()[0],
+        (
+            merry.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "transfer_from"),
+                    vec![
+                        &e,
+                        merry.to_val(),
+                        samwise.to_val(),
+                        merry.to_val(),
+                        4_0000000_i128.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(fee_vault_client.allowance(&samwise, &merry), 6_0000000);
+    assert_eq!(fee_vault_client.balance(&merry), 4_0000000);
+    assert_eq!(
+        fee_vault_client.balance(&samwise),
+        transfer_amount - 4_0000000
+    );
+
+    // -> spending more than the allowance fails
+    let result = fee_vault_client.try_transfer_from(&merry, &samwise, &merry, &6_0000001);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(9))));
+
+    // -> expired allowances can't be spent
+    e.ledger().set_sequence_number(expiration_ledger + 1);
+    assert_eq!(fee_vault_client.allowance(&samwise, &merry), 0);
+    let result = fee_vault_client.try_transfer_from(&merry, &samwise, &merry, &1_0000000);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(9))));
+
+    // -> approvals with a positive amount must not already be expired
+    let result =
+        fee_vault_client.try_approve(&samwise, &merry, &1_0000000, &(e.ledger().sequence() - 1));
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(9))));
+
+    /*
+     * Burn shares
+     */
+    let vault_pre_burn = fee_vault_client.get_vault();
+    let frodo_underlying_pre_burn = fee_vault_client.get_underlying_tokens(&frodo);
+    fee_vault_client.burn(&merry, &4_0000000);
+    let vault_post_burn = fee_vault_client.get_vault();
+    assert_eq!(fee_vault_client.balance(&merry), 0);
+    assert_eq!(
+        vault_post_burn.total_shares,
+        vault_pre_burn.total_shares - 4_0000000
+    );
+    assert_eq!(
+        vault_post_burn.total_b_tokens,
+        vault_pre_burn.total_b_tokens
+    );
+    // -> burnt shares are donated to the remaining holders
+    assert!(fee_vault_client.get_underlying_tokens(&frodo) > frodo_underlying_pre_burn);
+
+    /*
+     * Validation
+     */
+    let frodo_balance = fee_vault_client.balance(&frodo);
+    let result = fee_vault_client.try_transfer(&frodo, &samwise, &(frodo_balance + 1));
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(10))));
+
+    let result = fee_vault_client.try_transfer(&frodo, &samwise, &-1);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(8))));
+
+    let result = fee_vault_client.try_burn(&frodo, &(frodo_balance + 1));
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(10))));
+
+    // -> transferring to self is a no-op
+    fee_vault_client.transfer(&frodo, &frodo, &frodo_balance);
+    assert_eq!(fee_vault_client.balance(&frodo), frodo_balance);
+}
+
+#[test]
+fn test_share_token_transfer_requires_signer() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let signer = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(
+        &e,
+        &bombadil,
+        &pool,
+        &usdc,
+        0,
+        100_0000,
+        Some(signer.clone()),
+    );
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    usdc_client.mint(&frodo, &100_0000000);
+    let shares = fee_vault_client.deposit(&frodo, &100_0000000);
+
+    fee_vault_client.transfer(&frodo, &samwise, &shares);
+    let auths = e.auths();
+    assert_eq!(auths.len(), 2);
+    assert_eq!(auths[0].0, frodo);
+    assert_eq!(auths[1].0, signer);
+    assert_eq!(fee_vault_client.balance(&samwise), shares);
+}
+
+#[test]
+fn test_share_mint_and_burn_events() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    usdc_client.mint(&frodo, &200_0000000);
+
+    // -> deposit emits a mint event for the new shares
+    let total_b_tokens = fee_vault_client.get_vault().total_b_tokens;
+    let deposit_amount = 100_0000000;
+    let deposit_shares = fee_vault_client.deposit(&frodo, &deposit_amount);
+    let events = e.events().all().filter_by_contract(&fee_vault);
+    let b_tokens = fee_vault_client.get_vault().total_b_tokens - total_b_tokens;
+    assert_eq!(
+        events,
+        vec![
+            &e,
+            (
+                fee_vault.clone(),
+                (
+                    Symbol::new(&e, "vault_deposit"),
+                    pool.clone(),
+                    usdc.clone(),
+                    frodo.clone()
+                )
+                    .into_val(&e),
+                (
+                    frodo.clone(),
+                    deposit_amount,
+                    deposit_shares,
+                    b_tokens,
+                    0i128
+                )
+                    .into_val(&e)
+            ),
+            (
+                fee_vault.clone(),
+                (Symbol::new(&e, "mint"), frodo.clone()).into_val(&e),
+                deposit_shares.into_val(&e)
+            ),
+        ]
+    );
+
+    // -> mint emits a mint event for the requested shares
+    let total_b_tokens = fee_vault_client.get_vault().total_b_tokens;
+    let mint_shares = 50_0000000;
+    let mint_amount = fee_vault_client.mint(&frodo, &mint_shares);
+    let events = e.events().all().filter_by_contract(&fee_vault);
+    let b_tokens = fee_vault_client.get_vault().total_b_tokens - total_b_tokens;
+    assert_eq!(
+        events,
+        vec![
+            &e,
+            (
+                fee_vault.clone(),
+                (
+                    Symbol::new(&e, "vault_deposit"),
+                    pool.clone(),
+                    usdc.clone(),
+                    frodo.clone()
+                )
+                    .into_val(&e),
+                (frodo.clone(), mint_amount, mint_shares, b_tokens, 0i128).into_val(&e)
+            ),
+            (
+                fee_vault.clone(),
+                (Symbol::new(&e, "mint"), frodo.clone()).into_val(&e),
+                mint_shares.into_val(&e)
+            ),
+        ]
+    );
+    assert_eq!(
+        fee_vault_client.balance(&frodo),
+        deposit_shares + mint_shares
+    );
+
+    // -> withdraw emits a burn event for the burnt shares
+    let total_b_tokens = fee_vault_client.get_vault().total_b_tokens;
+    let withdraw_amount = 30_0000000;
+    let withdraw_shares = fee_vault_client.withdraw(&frodo, &withdraw_amount);
+    let events = e.events().all().filter_by_contract(&fee_vault);
+    let b_tokens = total_b_tokens - fee_vault_client.get_vault().total_b_tokens;
+    assert_eq!(
+        events,
+        vec![
+            &e,
+            (
+                fee_vault.clone(),
+                (
+                    Symbol::new(&e, "vault_withdraw"),
+                    pool.clone(),
+                    usdc.clone(),
+                    frodo.clone()
+                )
+                    .into_val(&e),
+                (
+                    frodo.clone(),
+                    withdraw_amount,
+                    withdraw_shares,
+                    b_tokens,
+                    0i128
+                )
+                    .into_val(&e)
+            ),
+            (
+                fee_vault.clone(),
+                (Symbol::new(&e, "burn"), frodo.clone()).into_val(&e),
+                withdraw_shares.into_val(&e)
+            ),
+        ]
+    );
+
+    // -> redeem emits a burn event for the redeemed shares
+    let total_b_tokens = fee_vault_client.get_vault().total_b_tokens;
+    let redeem_shares = 20_0000000;
+    let redeem_amount = fee_vault_client.redeem(&frodo, &redeem_shares);
+    let events = e.events().all().filter_by_contract(&fee_vault);
+    let b_tokens = total_b_tokens - fee_vault_client.get_vault().total_b_tokens;
+    assert_eq!(
+        events,
+        vec![
+            &e,
+            (
+                fee_vault.clone(),
+                (
+                    Symbol::new(&e, "vault_withdraw"),
+                    pool.clone(),
+                    usdc.clone(),
+                    frodo.clone()
+                )
+                    .into_val(&e),
+                (frodo.clone(), redeem_amount, redeem_shares, b_tokens, 0i128).into_val(&e)
+            ),
+            (
+                fee_vault.clone(),
+                (Symbol::new(&e, "burn"), frodo.clone()).into_val(&e),
+                redeem_shares.into_val(&e)
+            ),
+        ]
+    );
+    assert_eq!(
+        fee_vault_client.balance(&frodo),
+        deposit_shares + mint_shares - withdraw_shares - redeem_shares
+    );
+}
@@ -0,0 +1,269 @@
+#![cfg(test)]
+
+use crate::constants::STORAGE_VERSION;
+use crate::migration::{FeeV0, VaultDataV0};
+use crate::storage;
+use crate::testutils::{
+    assert_approx_eq_abs, create_blend_pool, register_fee_vault, EnvTestUtils, FEE_VAULT_WASM,
+};
+use crate::FeeVaultClient;
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{vec, Address, Env, Error, Executable, IntoVal, Symbol, Vec};
+
+#[test]
+fn test_upgrade() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // -> new vaults use the latest storage layout
+    assert_eq!(fee_vault_client.get_storage_version(), STORAGE_VERSION);
+    let result = fee_vault_client.try_migrate();
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(123))));
+
+    // -> setup live positions and rewards
+    usdc_client.mint(&frodo, &200_0000000);
+    usdc_client.mint(&samwise, &100_0000000);
+    let frodo_shares = fee_vault_client.deposit(&frodo, &100_0000000);
+    let samwise_shares = fee_vault_client.deposit(&samwise, &100_0000000);
+    xlm_client.mint(&gandalf, &1_000_0000000);
+    fee_vault_client.set_rewards(&xlm, &1_000_0000000, &(e.ledger().timestamp() + 1000));
+    e.jump_time(500);
+
+    // -> rewind the storage to the v0 layout used by deployed vaults
+    e.as_contract(&fee_vault, || {
+        let vault = storage::get_vault_data(&e);
+        e.storage().persistent().set(
+            &Symbol::new(&e, "Vault"),
+            &VaultDataV0 {
+                last_update_timestamp: vault.last_update_timestamp,
+                b_rate: vault.b_rate,
+                total_shares: vault.total_shares,
+                total_b_tokens: vault.total_b_tokens,
+                admin_balance: vault.admin_balance,
+            },
+        );
+        e.storage().instance().set(
+            &Symbol::new(&e, "Fee"),
+            &FeeV0 {
+                rate_type: 0,
+                rate: 100_0000,
+            },
+        );
+        e.storage().instance().remove(&Symbol::new(&e, "Version"));
+        e.storage().instance().remove(&Symbol::new(&e, "RwdTokens"));
+        e.storage()
+            .instance()
+            .set(&Symbol::new(&e, "RwdToken"), &xlm);
+    });
+
+    // -> upgrade to the vault's own WASM
+    let new_wasm_hash = e.deployer().upload_contract_wasm(FEE_VAULT_WASM);
+    fee_vault_client.upgrade(&new_wasm_hash);
+    assert_eq!(
+        e.auths()[0],
+        (
+            gandalf.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "upgrade"),
+                    vec![&e, new_wasm_hash.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(
+        fee_vault.executable(),
+        Some(Executable::Wasm(new_wasm_hash))
+    );
+    assert_eq!(fee_vault_client.get_storage_version(), 0);
+
+    fee_vault_client.migrate();
+    assert_eq!(fee_vault_client.get_storage_version(), STORAGE_VERSION);
+    assert_eq!(fee_vault_client.get_reward_tokens(), vec![&e, xlm.clone()]);
+
+    // -> positions are kept in the vault's storage across the upgrade
+    assert_eq!(fee_vault_client.get_shares(&frodo), frodo_shares);
+    assert_eq!(fee_vault_client.get_shares(&samwise), samwise_shares);
+    e.as_contract(&fee_vault, || {
+        assert_eq!(
+            storage::get_vault_data(&e).total_shares,
+            frodo_shares + samwise_shares
+        );
+        assert_eq!(storage::get_admin(&e), gandalf);
+    });
+
+    // -> the existing positions can deposit, withdraw and claim rewards with the upgraded code
+    let new_shares = fee_vault_client.deposit(&frodo, &100_0000000);
+    assert!(new_shares > 0);
+    assert_eq!(
+        fee_vault_client.get_shares(&frodo),
+        frodo_shares + new_shares
+    );
+    e.jump_time(500);
+
+    let frodo_claimed: Vec<i128> = fee_vault_client.claim_all_rewards(&frodo, &frodo);
+    let samwise_claimed: Vec<i128> = fee_vault_client.claim_all_rewards(&samwise, &samwise);
+    assert_approx_eq_abs(
+        frodo_claimed.get_unchecked(0) + samwise_claimed.get_unchecked(0),
+        1_000_0000000,
+        0_0001000,
+    );
+    assert!(frodo_claimed.get_unchecked(0) > samwise_claimed.get_unchecked(0));
+
+    fee_vault_client.withdraw(&samwise, &50_0000000);
+    assert_eq!(usdc_client.balance(&samwise), 50_0000000);
+    fee_vault_client.withdraw(&frodo, &50_0000000);
+    assert_eq!(usdc_client.balance(&frodo), 50_0000000);
+}
+
+#[test]
+fn test_migrate_legacy_vault() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let fee_vault = register_fee_vault(&e, &gandalf, &pool, &usdc, 0, 100_0000, None);
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // -> setup live positions and rewards
+    usdc_client.mint(&frodo, &100_0000000);
+    usdc_client.mint(&samwise, &100_0000000);
+    fee_vault_client.deposit(&frodo, &100_0000000);
+    fee_vault_client.deposit(&samwise, &100_0000000);
+    xlm_client.mint(&gandalf, &1_000_0000000);
+    fee_vault_client.set_rewards(&xlm, &1_000_0000000, &(e.ledger().timestamp() + 1000));
+    e.jump_time(500);
+
+    let frodo_underlying = fee_vault_client.get_underlying_tokens(&frodo);
+
+    // -> rewind the storage to the v0 layout, which stored a single reward token, a rate type
+    //    fee, no fee recipient balances and no high-water mark
+    e.as_contract(&fee_vault, || {
+        let vault = storage::get_vault_data(&e);
+        e.storage().persistent().set(
+            &Symbol::new(&e, "Vault"),
+            &VaultDataV0 {
+                last_update_timestamp: vault.last_update_timestamp,
+                b_rate: vault.b_rate,
+                total_shares: vault.total_shares,
+                total_b_tokens: vault.total_b_tokens,
+                admin_balance: vault.admin_balance,
+            },
+        );
+        e.storage().instance().set(
+            &Symbol::new(&e, "Fee"),
+            &FeeV0 {
+                rate_type: 0,
+                rate: 100_0000,
+            },
+        );
+        e.storage().instance().remove(&Symbol::new(&e, "Version"));
+        e.storage().instance().remove(&Symbol::new(&e, "RwdTokens"));
+        e.storage()
+            .instance()
+            .set(&Symbol::new(&e, "RwdToken"), &xlm);
+    });
+    assert_eq!(fee_vault_client.get_storage_version(), 0);
+
+    fee_vault_client.migrate();
+    assert_eq!(
+        e.auths()[0],
+        (
+            gandalf.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "migrate"),
+                    vec![&e]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(fee_vault_client.get_storage_version(), STORAGE_VERSION);
+    assert_eq!(fee_vault_client.get_reward_tokens(), vec![&e, xlm.clone()]);
+    let fee = fee_vault_client.get_fee();
+    assert_eq!(fee.take_rate, Some(100_0000));
+    assert_eq!(fee.take_rate_tiers.len(), 0);
+    assert_eq!(fee.take_rate_bands.len(), 0);
+    assert!(!fee.high_water_mark);
+    assert_eq!(fee.apr_cap, None);
+    assert_eq!(fee.apr_floor, None);
+    assert_eq!(fee.management_fee, None);
+    assert_eq!(fee.entry_fee, None);
+    assert_eq!(fee.exit_fee, None);
+    e.as_contract(&fee_vault, || {
+        let legacy_token: Option<Address> =
+            e.storage().instance().get(&Symbol::new(&e, "RwdToken"));
+        assert!(legacy_token.is_none());
+        let vault = storage::get_vault_data(&e);
+        assert_eq!(vault.fee_recipient_balance, 0);
+        assert_eq!(vault.unsplit_fees, 0);
+        assert!(vault.high_water_mark > 0);
+        assert_eq!(vault.high_water_mark, vault.share_price());
+        assert_eq!(vault.deficit, 0);
+    });
+
+    // -> positions and accrued rewards are unaffected by the migration
+    assert_eq!(
+        fee_vault_client.get_underlying_tokens(&frodo),
+        frodo_underlying
+    );
+    e.jump_time(500);
+    let claimed: Vec<i128> = fee_vault_client.claim_all_rewards(&frodo, &frodo);
+    assert_approx_eq_abs(claimed.get_unchecked(0), 500_0000000, 0_0001000);
+    fee_vault_client.withdraw(&samwise, &50_0000000);
+    assert_eq!(usdc_client.balance(&samwise), 50_0000000);
+
+    // -> the migration can only be run once
+    let result = fee_vault_client.try_migrate();
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(123))));
+}
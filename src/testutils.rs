@@ -1,4 +1,4 @@
-#![cfg(test)]
+#![cfg(any(test, feature = "testutils"))]
 
 use core::i64;
 
@@ -14,7 +14,7 @@ use soroban_sdk::{
 };
 
 /// Defaults to a mock pool with a b_rate of 1_100_000_000 and a take_rate of 0_1000000.
-pub(crate) fn register_fee_vault(
+pub fn register_fee_vault(
     e: &Env,
     admin: &Address,
     pool: &Address,
@@ -40,7 +40,7 @@ pub(crate) fn register_fee_vault(
 /// Uses a mock pool underneath so no deposits or withdrawls are functional.
 ///
 /// Returns (vault address, mock pool address, mock token address)
-pub(crate) fn create_test_fee_vault(
+pub fn create_test_fee_vault(
     e: &Env,
     admin: &Address,
     rate_type: u32,
@@ -56,7 +56,7 @@ pub(crate) fn create_test_fee_vault(
     (vault, pool, asset)
 }
 
-pub(crate) fn create_blend_pool(
+pub fn create_blend_pool(
     e: &Env,
     blend_fixture: &BlendFixture,
     admin: &Address,
@@ -148,6 +148,13 @@ pub(crate) fn create_blend_pool(
     return pool;
 }
 
+/// The fee vault WASM, used by tests that deploy or upgrade to the WASM. Run `make build` before
+/// testing so the WASM matches the current source.
+pub const FEE_VAULT_WASM: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/target/wasm32v1-none/release/fee_vault_v2.wasm"
+));
+
 pub trait EnvTestUtils {
     /// Jump the env by the given amount of ledgers. Assumes 5 seconds per ledger.
     fn jump(&self, ledgers: u32);
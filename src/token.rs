@@ -0,0 +1,88 @@
+use soroban_sdk::{panic_with_error, token::TokenClient, Address, Bytes, Env, String};
+
+use crate::{
+    errors::FeeVaultError,
+    storage::{self, Allowance},
+};
+
+/// Fetch the number of shares `spender` can spend on behalf of `from`. Expired allowances are
+/// treated as zero.
+///
+/// ### Arguments
+/// * `from` - The address of the user that granted the allowance
+/// * `spender` - The address of the spender
+pub fn get_allowance(e: &Env, from: &Address, spender: &Address) -> i128 {
+    let allowance = storage::get_allowance(e, from, spender);
+    if allowance.expiration_ledger < e.ledger().sequence() {
+        0
+    } else {
+        allowance.amount
+    }
+}
+
+/// Set the number of shares `spender` can spend on behalf of `from`
+///
+/// ### Arguments
+/// * `from` - The address of the user granting the allowance
+/// * `spender` - The address of the spender
+/// * `amount` - The number of shares the spender can spend
+/// * `expiration_ledger` - The ledger sequence number the allowance expires at
+///
+/// ### Panics
+/// * `AllowanceError` - If the amount is positive and the expiration ledger has already passed
+pub fn approve(e: &Env, from: &Address, spender: &Address, amount: i128, expiration_ledger: u32) {
+    if amount > 0 && expiration_ledger < e.ledger().sequence() {
+        panic_with_error!(e, FeeVaultError::AllowanceError);
+    }
+    storage::set_allowance(
+        e,
+        from,
+        spender,
+        &Allowance {
+            amount,
+            expiration_ledger,
+        },
+    );
+}
+
+/// Spend `amount` shares of the allowance `from` has granted to `spender`
+///
+/// ### Arguments
+/// * `from` - The address of the user that granted the allowance
+/// * `spender` - The address of the spender
+/// * `amount` - The number of shares to spend
+///
+/// ### Panics
+/// * `AllowanceError` - If the allowance is insufficient or expired
+pub fn spend_allowance(e: &Env, from: &Address, spender: &Address, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let allowance = storage::get_allowance(e, from, spender);
+    if allowance.amount < amount || allowance.expiration_ledger < e.ledger().sequence() {
+        panic_with_error!(e, FeeVaultError::AllowanceError);
+    }
+    storage::set_allowance(
+        e,
+        from,
+        spender,
+        &Allowance {
+            amount: allowance.amount - amount,
+            expiration_ledger: allowance.expiration_ledger,
+        },
+    );
+}
+
+/// Build the share token name from the underlying asset's symbol (e.g. "Fee Vault USDC")
+pub fn name(e: &Env, asset: &Address) -> String {
+    let mut name = Bytes::from_slice(e, b"Fee Vault ");
+    name.append(&TokenClient::new(e, asset).symbol().to_bytes());
+    String::from(name)
+}
+
+/// Build the share token symbol from the underlying asset's symbol (e.g. "fvUSDC")
+pub fn symbol(e: &Env, asset: &Address) -> String {
+    let mut symbol = Bytes::from_slice(e, b"fv");
+    symbol.append(&TokenClient::new(e, asset).symbol().to_bytes());
+    String::from(symbol)
+}
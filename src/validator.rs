@@ -3,9 +3,16 @@
  *
  * Functions in this module must panic if the valid conditions are not met.
  */
-use soroban_sdk::{panic_with_error, Env};
+use soroban_sdk::{panic_with_error, Address, Env, Vec};
 
-use crate::{errors::FeeVaultError, storage::Fee};
+use crate::{
+    constants::{
+        MAX_FEE_RECIPIENTS, MAX_TAKE_RATE_BANDS, MAX_TAKE_RATE_TIERS, MAX_TRANSACTION_FEE,
+        SCALAR_BPS,
+    },
+    errors::FeeVaultError,
+    storage::{self, Fee, FeeRecipient, Role},
+};
 
 /// Require that an incoming amount is positive
 ///
@@ -21,16 +28,182 @@ pub fn require_positive(e: &Env, amount: i128, err: FeeVaultError) {
     }
 }
 
-/// Require that a the rate_type and rate are a valid fee configuration
+/// Require that an incoming amount is not negative
 ///
 /// ### Arguments
-/// * `fee` - The fee configuration to check
+/// * `amount` - The amount to check
+///
+/// ### Panics
+/// If the number is negative
+pub fn require_nonnegative(e: &Env, amount: i128) {
+    if amount < 0 {
+        panic_with_error!(e, FeeVaultError::NegativeAmountError);
+    }
+}
+
+/// Require that the fee policy is a valid fee configuration
+///
+/// ### Arguments
+/// * `fee` - The fee policy to check
+///
+/// ### Panics
+/// * `InvalidFeeRate` - If any rate is not within 0 and 1_000_0000, or the entry or exit fee exceeds 10%
+/// * `InvalidFeePolicy` - If the APR floor exceeds the APR cap, a high-water mark or take rate tiers or bands
+///   are used without a take rate, the take rate tiers or bands are not ordered by increasing positive
+///   thresholds, or both take rate tiers and bands are used
 pub fn require_valid_fee(e: &Env, fee: &Fee) {
-    if fee.rate > 1_000_0000 {
-        panic_with_error!(&e, FeeVaultError::InvalidFeeRate);
+    let rates = [
+        fee.take_rate,
+        fee.apr_cap,
+        fee.apr_floor,
+        fee.management_fee,
+    ];
+    for rate in rates.iter().flatten() {
+        if *rate > 1_000_0000 {
+            panic_with_error!(&e, FeeVaultError::InvalidFeeRate);
+        }
+    }
+    for transaction_fee in [fee.entry_fee, fee.exit_fee].iter().flatten() {
+        if *transaction_fee > MAX_TRANSACTION_FEE {
+            panic_with_error!(&e, FeeVaultError::InvalidFeeRate);
+        }
+    }
+
+    if let (Some(apr_cap), Some(apr_floor)) = (fee.apr_cap, fee.apr_floor) {
+        if apr_floor > apr_cap {
+            panic_with_error!(&e, FeeVaultError::InvalidFeePolicy);
+        }
+    }
+
+    if fee.high_water_mark && fee.take_rate.is_none() {
+        panic_with_error!(&e, FeeVaultError::InvalidFeePolicy);
+    }
+
+    if !fee.take_rate_tiers.is_empty() {
+        if fee.take_rate.is_none() || fee.take_rate_tiers.len() > MAX_TAKE_RATE_TIERS {
+            panic_with_error!(&e, FeeVaultError::InvalidFeePolicy);
+        }
+        let mut last_threshold: i128 = 0;
+        for tier in fee.take_rate_tiers.iter() {
+            if tier.rate > 1_000_0000 {
+                panic_with_error!(&e, FeeVaultError::InvalidFeeRate);
+            }
+            if tier.threshold <= last_threshold {
+                panic_with_error!(&e, FeeVaultError::InvalidFeePolicy);
+            }
+            last_threshold = tier.threshold;
+        }
+    }
+
+    if !fee.take_rate_bands.is_empty() {
+        if fee.take_rate.is_none()
+            || !fee.take_rate_tiers.is_empty()
+            || fee.take_rate_bands.len() > MAX_TAKE_RATE_BANDS
+        {
+            panic_with_error!(&e, FeeVaultError::InvalidFeePolicy);
+        }
+        let mut last_apr: u32 = 0;
+        for band in fee.take_rate_bands.iter() {
+            if band.rate > 1_000_0000 {
+                panic_with_error!(&e, FeeVaultError::InvalidFeeRate);
+            }
+            if band.apr <= last_apr {
+                panic_with_error!(&e, FeeVaultError::InvalidFeePolicy);
+            }
+            last_apr = band.apr;
+        }
+    }
+}
+
+/// Require that the fee recipients are a valid fee split
+///
+/// ### Arguments
+/// * `recipients` - The fee recipients to check
+///
+/// ### Panics
+/// If there are too many recipients, a recipient is repeated or has no weight, or the weights sum
+/// to more than 100%
+pub fn require_valid_fee_recipients(e: &Env, recipients: &Vec<FeeRecipient>) {
+    if recipients.len() > MAX_FEE_RECIPIENTS {
+        panic_with_error!(e, FeeVaultError::InvalidFeeRecipients);
+    }
+
+    let mut total_weight: i128 = 0;
+    for (index, recipient) in recipients.iter().enumerate() {
+        if recipient.weight == 0 {
+            panic_with_error!(e, FeeVaultError::InvalidFeeRecipients);
+        }
+        if recipients
+            .iter()
+            .skip(index + 1)
+            .any(|other| other.address == recipient.address)
+        {
+            panic_with_error!(e, FeeVaultError::InvalidFeeRecipients);
+        }
+        total_weight += recipient.weight as i128;
     }
+    if total_weight > SCALAR_BPS {
+        panic_with_error!(e, FeeVaultError::InvalidFeeRecipients);
+    }
+}
+
+/// Require that the ledger timestamp has not passed the deadline
+///
+/// ### Arguments
+/// * `deadline` - The last timestamp the action can be executed at
+///
+/// ### Panics
+/// If the ledger timestamp is greater than the deadline
+pub fn require_before_deadline(e: &Env, deadline: u64) {
+    if e.ledger().timestamp() > deadline {
+        panic_with_error!(&e, FeeVaultError::DeadlineExpired);
+    }
+}
+
+/// Require that the caller is the admin or the guardian
+///
+/// ### Arguments
+/// * `caller` - The address to check
+///
+/// ### Panics
+/// If the caller is neither the admin nor the guardian
+pub fn require_admin_or_guardian(e: &Env, caller: &Address) {
+    if *caller != storage::get_admin(e)
+        && Some(caller.clone()) != storage::get_role(e, Role::Guardian)
+    {
+        panic_with_error!(e, FeeVaultError::UnauthorizedError);
+    }
+}
+
+/// Require that deposits are not paused
+///
+/// ### Panics
+/// If deposits are paused or the vault is in emergency mode
+pub fn require_deposits_allowed(e: &Env) {
+    let pause_state = storage::get_pause_state(e);
+    if pause_state.deposits || pause_state.emergency {
+        panic_with_error!(e, FeeVaultError::Paused);
+    }
+}
 
-    if fee.rate_type > 2 {
-        panic_with_error!(&e, FeeVaultError::InvalidFeeRateType);
+/// Require that withdrawals are not paused. Withdrawals are always allowed in emergency mode.
+///
+/// ### Panics
+/// If withdrawals are paused and the vault is not in emergency mode
+pub fn require_withdrawals_allowed(e: &Env) {
+    let pause_state = storage::get_pause_state(e);
+    if pause_state.withdrawals && !pause_state.emergency {
+        panic_with_error!(e, FeeVaultError::Paused);
+    }
+}
+
+/// Require that reward claims are not paused
+///
+/// ### Panics
+/// If reward claims are paused or the vault is in emergency mode
+pub fn require_claims_allowed(e: &Env) {
+    let pause_state = storage::get_pause_state(e);
+    if pause_state.claims || pause_state.emergency {
+        panic_with_error!(e, FeeVaultError::Paused);
     }
 }
@@ -1,6 +1,7 @@
 use crate::{
-    constants::{SCALAR_12, SCALAR_7, SECONDS_PER_YEAR},
+    constants::{SCALAR_12, SCALAR_7, SCALAR_BPS, SECONDS_PER_YEAR},
     errors::FeeVaultError,
+    events::FeeVaultEvents,
     pool,
     rewards::update_rewards,
     storage,
@@ -20,8 +21,20 @@ pub struct VaultData {
     pub total_shares: i128,
     /// The total bToken deposits owned by the reserve vault depositors. Excludes admin balance.
     pub total_b_tokens: i128,
-    /// The admin's bTokens. Excluded from the `total_b_tokens` value.
+    /// The admin's bTokens, including the fee recipients' bTokens. Excluded from the `total_b_tokens` value.
     pub admin_balance: i128,
+    /// The bTokens owed to the fee recipients that have been split into their balances. Included in the
+    /// `admin_balance` value.
+    pub fee_recipient_balance: i128,
+    /// The fees accrued since they were last split between the fee recipients. Included in the `admin_balance`
+    /// value.
+    pub unsplit_fees: i128,
+    /// The highest underlying value of a share reached by the vault, with 12 decimals. Performance
+    /// fees are only charged on gains above this value. Zero if no shares have been priced yet.
+    pub high_water_mark: i128,
+    /// The bTokens owed to the vault depositors for APR floor supplements the admin balance could not cover.
+    /// Repaid from the admin's own part of future fees and from admin deposits.
+    pub deficit: i128,
 }
 
 impl VaultData {
@@ -47,11 +60,24 @@ impl VaultData {
 
     /// Coverts a share amount to a b_token amount rounding down
     pub fn shares_to_b_tokens_down(&self, amount: i128) -> i128 {
+        if self.total_shares == 0 {
+            return amount;
+        }
         amount
             .fixed_div_floor(self.total_shares, self.total_b_tokens)
             .unwrap_optimized()
     }
 
+    /// Coverts a share amount to a b_token amount rounding up
+    pub fn shares_to_b_tokens_up(&self, amount: i128) -> i128 {
+        if self.total_shares == 0 || self.total_b_tokens == 0 {
+            return amount;
+        }
+        amount
+            .fixed_div_ceil(self.total_shares, self.total_b_tokens)
+            .unwrap_optimized()
+    }
+
     /// Coverts a b_token amount to an underlying token amount rounding down
     pub fn b_tokens_to_underlying_down(&self, amount: i128) -> i128 {
         amount
@@ -59,6 +85,13 @@ impl VaultData {
             .unwrap_optimized()
     }
 
+    /// Coverts a b_token amount to an underlying token amount rounding up
+    pub fn b_tokens_to_underlying_up(&self, amount: i128) -> i128 {
+        amount
+            .fixed_mul_ceil(self.b_rate, SCALAR_12)
+            .unwrap_optimized()
+    }
+
     /// Coverts an underlying amount to a b_token amount rounding down
     pub fn underlying_to_b_tokens_down(&self, amount: i128) -> i128 {
         amount
@@ -73,93 +106,386 @@ impl VaultData {
             .unwrap_optimized()
     }
 
-    /// Updates the reserve's bRate and accrues fees to the admin in accordance with the portion of interest they earned
+    /// Converts an underlying amount to shares rounding down
+    pub fn underlying_to_shares_down(&self, amount: i128) -> i128 {
+        self.b_tokens_to_shares_down(self.underlying_to_b_tokens_down(amount))
+    }
+
+    /// Converts an underlying amount to shares rounding up
+    pub fn underlying_to_shares_up(&self, amount: i128) -> i128 {
+        self.b_tokens_to_shares_up(self.underlying_to_b_tokens_up(amount))
+    }
+
+    /// Converts a share amount to an underlying amount rounding down
+    pub fn shares_to_underlying_down(&self, amount: i128) -> i128 {
+        self.b_tokens_to_underlying_down(self.shares_to_b_tokens_down(amount))
+    }
+
+    /// Converts a share amount to an underlying amount rounding up
+    pub fn shares_to_underlying_up(&self, amount: i128) -> i128 {
+        self.b_tokens_to_underlying_up(self.shares_to_b_tokens_up(amount))
+    }
+
+    /// Calculate the result of depositing `amount` underlying tokens. The entry fee is taken from the
+    /// minted bTokens before shares are issued.
+    ///
+    /// Returns (the bTokens minted to the vault, the shares minted, the bTokens taken as an entry fee)
+    pub fn preview_deposit(&self, entry_fee: Option<u32>, amount: i128) -> (i128, i128, i128) {
+        let b_tokens_amount = self.underlying_to_b_tokens_down(amount);
+        let fee_b_tokens = transaction_fee(b_tokens_amount, entry_fee);
+        let share_amount = self.b_tokens_to_shares_down(b_tokens_amount - fee_b_tokens);
+        (b_tokens_amount, share_amount, fee_b_tokens)
+    }
+
+    /// Calculate the result of minting `shares`. The underlying required includes the entry fee.
+    ///
+    /// Returns (the underlying to deposit, the bTokens minted to the vault, the bTokens taken as an entry fee)
+    pub fn preview_mint(&self, entry_fee: Option<u32>, shares: i128) -> (i128, i128, i128) {
+        let net_b_tokens = self.shares_to_b_tokens_up(shares);
+        let b_tokens_amount = gross_up_transaction_fee(net_b_tokens, entry_fee);
+        // the blend pool will round down the b_tokens minted based on the underlying supplied
+        let underlying_amount = self.b_tokens_to_underlying_up(b_tokens_amount);
+        (
+            underlying_amount,
+            b_tokens_amount,
+            b_tokens_amount - net_b_tokens,
+        )
+    }
+
+    /// Calculate the result of withdrawing `amount` underlying tokens. The exit fee is charged on top
+    /// of the bTokens burnt from the pool.
+    ///
+    /// Returns (the bTokens burnt from the pool, the shares burnt, the bTokens taken as an exit fee)
+    pub fn preview_withdraw(&self, exit_fee: Option<u32>, amount: i128) -> (i128, i128, i128) {
+        let b_tokens_amount = self.underlying_to_b_tokens_up(amount);
+        let gross_b_tokens = gross_up_transaction_fee(b_tokens_amount, exit_fee);
+        let share_amount = self.b_tokens_to_shares_up(gross_b_tokens);
+        (
+            b_tokens_amount,
+            share_amount,
+            gross_b_tokens - b_tokens_amount,
+        )
+    }
+
+    /// Calculate the result of redeeming `shares`. The exit fee is taken from the shares' bTokens
+    /// before they are withdrawn.
+    ///
+    /// Returns (the underlying withdrawn, the bTokens burnt from the pool, the bTokens taken as an exit fee)
+    pub fn preview_redeem(&self, exit_fee: Option<u32>, shares: i128) -> (i128, i128, i128) {
+        let gross_b_tokens = self.shares_to_b_tokens_down(shares);
+        let fee_b_tokens = transaction_fee(gross_b_tokens, exit_fee);
+        let underlying_amount = self.b_tokens_to_underlying_down(gross_b_tokens - fee_b_tokens);
+        // the blend pool will round up the b_tokens burnt based on the underlying amount withdrawn
+        let b_tokens_amount = self.underlying_to_b_tokens_up(underlying_amount);
+        (underlying_amount, b_tokens_amount, fee_b_tokens)
+    }
+
+    /// Fetch the underlying value of a single share, with 12 decimals
+    pub fn share_price(&self) -> i128 {
+        self.total_b_tokens
+            .fixed_mul_floor(self.b_rate, self.total_shares)
+            .unwrap_optimized()
+    }
+
+    /// Raises the high-water mark to the current share price, or resets it if the vault has no shares
+    fn update_high_water_mark(&mut self) {
+        if self.total_shares == 0 {
+            self.high_water_mark = 0;
+            return;
+        }
+        let share_price = self.share_price();
+        if share_price > self.high_water_mark {
+            self.high_water_mark = share_price;
+        }
+    }
+
+    /// Fetch the admin's bTokens that are not owed to any fee recipient
+    pub fn admin_fee_balance(&self, e: &Env) -> i128 {
+        let mut unsplit_recipient_fees = 0;
+        for recipient in storage::get_fee_recipients(e).iter() {
+            unsplit_recipient_fees += self.unsplit_fee_share(recipient.weight);
+        }
+        self.admin_balance - self.fee_recipient_balance - unsplit_recipient_fees
+    }
+
+    /// Fetch the bTokens owed to a fee recipient, including their share of the unsplit fees
+    pub fn recipient_fee_balance(&self, e: &Env, recipient: &Address) -> i128 {
+        let mut balance = storage::get_fee_balance(e, recipient);
+        for fee_recipient in storage::get_fee_recipients(e).iter() {
+            if fee_recipient.address == *recipient {
+                balance += self.unsplit_fee_share(fee_recipient.weight);
+            }
+        }
+        balance
+    }
+
+    /// Splits the unsplit fees between the fee recipients by weight, with any weight not assigned to a
+    /// recipient remaining with the admin. Must be invoked before the fee recipients change.
+    ///
+    /// Writes the recipients' balances to storage, so the vault data must be saved afterwards.
+    fn split_fees(&mut self, e: &Env) {
+        for recipient in storage::get_fee_recipients(e).iter() {
+            let recipient_b_tokens = self.unsplit_fee_share(recipient.weight);
+            if recipient_b_tokens > 0 {
+                let balance = storage::get_fee_balance(e, &recipient.address);
+                storage::set_fee_balance(e, &recipient.address, balance + recipient_b_tokens);
+                self.fee_recipient_balance += recipient_b_tokens;
+            }
+        }
+        self.unsplit_fees = 0;
+    }
+
+    /// Calculate a fee recipient's share of the unsplit fees, rounding down
+    fn unsplit_fee_share(&self, weight: u32) -> i128 {
+        self.unsplit_fees
+            .fixed_mul_floor(weight as i128, SCALAR_BPS)
+            .unwrap_optimized()
+    }
+
+    /// Calculate the fee recipients' combined share of `b_tokens` of fees, rounding down
+    fn recipients_fee_share(&self, e: &Env, b_tokens: i128) -> i128 {
+        let mut recipients_b_tokens = 0;
+        for recipient in storage::get_fee_recipients(e).iter() {
+            recipients_b_tokens += b_tokens
+                .fixed_mul_floor(recipient.weight as i128, SCALAR_BPS)
+                .unwrap_optimized();
+        }
+        recipients_b_tokens
+    }
+
+    /// Adds an APR floor supplement the admin balance could not cover to the vault's deficit
+    fn add_deficit(&mut self, e: &Env, pool: &Address, asset: &Address, b_tokens: i128) {
+        self.deficit += b_tokens;
+        FeeVaultEvents::vault_deficit_created(e, pool, asset, b_tokens, self.deficit);
+    }
+
+    /// Repays the vault's deficit with up to `b_tokens`. Returns the bTokens used to repay the deficit.
+    fn repay_deficit(&mut self, e: &Env, pool: &Address, asset: &Address, b_tokens: i128) -> i128 {
+        self.clear_deficit_if_empty(e, pool, asset);
+        let repaid_b_tokens = b_tokens.min(self.deficit);
+        if repaid_b_tokens <= 0 {
+            return 0;
+        }
+        self.deficit -= repaid_b_tokens;
+        FeeVaultEvents::vault_deficit_settled(e, pool, asset, repaid_b_tokens, self.deficit);
+        repaid_b_tokens
+    }
+
+    /// Clears the vault's deficit if the vault has no shares. The depositors the deficit was owed to have
+    /// left, so repaying it would only hand the bTokens to the next depositor.
+    fn clear_deficit_if_empty(&mut self, e: &Env, pool: &Address, asset: &Address) {
+        if self.total_shares == 0 && self.deficit > 0 {
+            let cleared_b_tokens = self.deficit;
+            self.deficit = 0;
+            FeeVaultEvents::vault_deficit_settled(e, pool, asset, cleared_b_tokens, 0);
+        }
+    }
+
+    /// Credits an entry or exit fee to the admin, to be split between the fee recipients
+    fn credit_transaction_fee(&mut self, fee_b_tokens: i128) {
+        if fee_b_tokens > 0 {
+            self.unsplit_fees += fee_b_tokens;
+            self.admin_balance += fee_b_tokens;
+        }
+    }
+
+    /// Calculate the difference in `b_tokens` the vault has vs the `b_tokens` it needs to earn the target APR
+    /// since the last update. This is done by finding the expected `b_rate` needed to achieve the target APR over
+    /// the update period, and then determining the `b_tokens` needed to make up the difference between the
+    /// current `b_rate` and the target `b_rate`.
+    ///
+    /// A positive value is the excess `b_tokens` above the target, and a negative value is the shortfall.
+    fn target_b_token_diff(&self, target_apr: i128, new_rate: i128, time_elapsed: u64) -> i128 {
+        // Target growth rate for target APR over the time elapsed scaled to 12 decimals
+        // -> target_apr is 7 decimals, so we multiply by 100_000 to get 12 decimals (seconds per year and
+        //    time elapsed have no decimals)
+        let target_growth_rate =
+            (100_000 * target_apr * (time_elapsed as i128)) / SECONDS_PER_YEAR + SCALAR_12;
+
+        let target_b_rate = self
+            .b_rate
+            .fixed_mul_ceil(target_growth_rate, SCALAR_12)
+            .unwrap_optimized();
+
+        // math lib treats floor as rounding away from 0 for negative numbers
+        self.total_b_tokens
+            .fixed_mul_floor(new_rate - target_b_rate, SCALAR_12)
+            .unwrap_optimized()
+            .fixed_div_floor(new_rate, SCALAR_12)
+            .unwrap_optimized()
+    }
+
+    /// Calculate the APR earned by the vault's bTokens since the last update, with 7 decimals. Returns zero if
+    /// no time has elapsed.
+    fn realized_apr(&self, new_rate: i128, time_elapsed: u64) -> i128 {
+        if time_elapsed == 0 {
+            return 0;
+        }
+        (new_rate - self.b_rate)
+            .fixed_mul_floor(
+                SCALAR_7 * SECONDS_PER_YEAR,
+                self.b_rate * time_elapsed as i128,
+            )
+            .unwrap_optimized()
+    }
+
+    /// Updates the reserve's bRate and accrues fees to the admin in accordance with the vault's fee policy.
+    /// The fee policy's components are applied in order: take rate, APR cap, APR floor, then management fee.
     fn update_rate(&mut self, e: &Env, pool: &Address, asset: &Address) {
         let now = e.ledger().timestamp();
         let new_rate = pool::reserve_b_rate(e, &pool, &asset);
-        // if the rate didn't increase, admin won't take any fees, so short circuit the math
-        // and just apply the b_rate update here
-        if new_rate <= self.b_rate {
+        let fee = storage::get_fee(e);
+        // if the rate didn't increase, admin won't take any interest based fees, so short circuit the math
+        // and just apply the b_rate update here. An APR floor still needs to be supplemented when the rate
+        // is flat or falling.
+        if new_rate <= self.b_rate && fee.management_fee.is_none() && fee.apr_floor.is_none() {
             self.last_update_timestamp = now;
             self.b_rate = new_rate;
+            self.update_high_water_mark();
             return;
         }
 
-        let fee = storage::get_fee(e);
+        let time_elapsed = now - self.last_update_timestamp;
         // this can round to zero if new_rate ~= target_b_rate
         // admin_b_tokens calc should round down, to prevent any rounding spam exploits
-        let admin_b_tokens: i128 = match fee.rate_type {
-            0 => {
+        let mut admin_b_tokens: i128 = 0;
+        let mut unpaid_b_tokens: i128 = 0;
+
+        // the take rate tier is based on the vault's TVL at the new bRate, before any fees are taken, and
+        // the take rate band is based on the APR realized since the last update
+        let tvl = self
+            .total_b_tokens
+            .fixed_mul_floor(new_rate, SCALAR_12)
+            .unwrap_optimized();
+        let apr = self.realized_apr(new_rate, time_elapsed);
+        if let Some(take_rate) = fee.current_take_rate(tvl, apr) {
+            let admin_take_rate = take_rate as i128;
+            if fee.high_water_mark {
+                // performance fee - admin earns a percentage of the share price gains above the high-water mark,
+                // so depositors are not charged again for recovering from a loss
+                if self.total_shares > 0 {
+                    // if the high-water mark was never set, only charge on the gains since the last update
+                    let high_water_mark = if self.high_water_mark == 0 {
+                        self.share_price()
+                    } else {
+                        self.high_water_mark
+                    };
+                    let new_share_price = self
+                        .total_b_tokens
+                        .fixed_mul_floor(new_rate, self.total_shares)
+                        .unwrap_optimized();
+                    if new_share_price > high_water_mark {
+                        admin_b_tokens = self
+                            .total_shares
+                            .fixed_mul_floor(new_share_price - high_water_mark, SCALAR_12)
+                            .unwrap_optimized()
+                            .fixed_mul_floor(admin_take_rate, SCALAR_7)
+                            .unwrap_optimized()
+                            .fixed_div_floor(new_rate, SCALAR_12)
+                            .unwrap_optimized();
+                    }
+                }
+            } else if new_rate > self.b_rate {
                 // take rate - admin earns a percentage of the interest accrued
-                let admin_take_rate = fee.rate as i128;
-                self.total_b_tokens
+                admin_b_tokens = self
+                    .total_b_tokens
                     .fixed_mul_floor(new_rate - self.b_rate, SCALAR_12)
                     .unwrap_optimized()
                     .fixed_mul_floor(admin_take_rate, SCALAR_7)
                     .unwrap_optimized()
                     .fixed_div_floor(new_rate, SCALAR_12)
-                    .unwrap_optimized()
-            }
-            1 | 2 => {
-                // 1 - capped rate - admin earns a percentage of the interest accrued
-                // 2 - fixed rate - admin either earns or supplements the vault to ensure the vault earns the target rate
-                //
-                // Both rate types calculate the difference in `b_tokens` the vault has vs the target rate. This is done by finding the
-                // expected `b_rate` needed to achieved the target rate over the update period, and then determining
-                // the `b_tokens` needed to make up the difference between the current `b_rate` and the target `b_rate`.
-                //
-                // However, capped rates do not get supplemented by the admin, so `admin_b_tokens` can't be negative, while
-                // fixed rates can be negative to force the admin to pay the difference into the vault.
-
-                let target_apr = fee.rate as i128;
-                let time_elapsed = now - self.last_update_timestamp;
-
-                // Target growth rate for target APR over the time elapsed scaled to 12 decimals
-                // -> target_apr is 7 decimals, so we multiply by 100_000 to get 12 decimals (seconds per year and
-                //    time elapsed have no decimals)
-                let target_growth_rate =
-                    (100_000 * target_apr * (time_elapsed as i128)) / SECONDS_PER_YEAR + SCALAR_12;
-
-                let target_b_rate = self
-                    .b_rate
-                    .fixed_mul_ceil(target_growth_rate, SCALAR_12)
                     .unwrap_optimized();
+            }
+        }
 
-                // math lib treats floor as rounding away from 0 for negative numbers
-                let b_token_diff = self
-                    .total_b_tokens
-                    .fixed_mul_floor(new_rate - target_b_rate, SCALAR_12)
-                    .unwrap_optimized()
-                    .fixed_div_floor(new_rate, SCALAR_12)
-                    .unwrap_optimized();
+        if let Some(apr_cap) = fee.apr_cap {
+            // capped rate - admin earns any interest remaining after the take rate that exceeds the APR cap.
+            // No fees are taken if the APR cap wasn't reached.
+            let excess_b_tokens =
+                self.target_b_token_diff(apr_cap as i128, new_rate, time_elapsed) - admin_b_tokens;
+            if excess_b_tokens > 0 {
+                admin_b_tokens += excess_b_tokens;
+            }
+        }
 
-                if b_token_diff <= 0 && fee.rate_type == 1 {
-                    // capped rate - no fees if the target rate wasn't reached
-                    0
-                } else if b_token_diff <= 0 && self.admin_balance + b_token_diff < 0 {
-                    // fixed rate - the negative b_token_diff exceeds the admin balance. To prevent any scenario
-                    // where users aren't able to withdraw their funds, the admin only pays what they have available.
-                    // The vault acts as a capped rate when the admin balance is zero, or negative.
-                    (-self.admin_balance).min(0i128) // if the admin balance is negative, return 0 to prevent fees
-                } else {
-                    b_token_diff
+        if let Some(apr_floor) = fee.apr_floor {
+            // floor rate - admin supplements the vault if the interest remaining after any fees is below the
+            // APR floor. To prevent any scenario where users aren't able to withdraw their funds, the admin only
+            // pays what they have available. The fees owed to the fee recipients are never used, and no supplement
+            // is paid if the admin's own balance is zero, or negative.
+            let shortfall_b_tokens =
+                self.target_b_token_diff(apr_floor as i128, new_rate, time_elapsed)
+                    - admin_b_tokens;
+            if shortfall_b_tokens < 0 {
+                let available_b_tokens = (self.admin_fee_balance(e) + admin_b_tokens).max(0);
+                if shortfall_b_tokens < -available_b_tokens {
+                    // the rest of the supplement is owed to the vault as a deficit
+                    unpaid_b_tokens = -available_b_tokens - shortfall_b_tokens;
                 }
+                admin_b_tokens += shortfall_b_tokens.max(-available_b_tokens);
             }
-            // If the fee rate type is malformed, don't accrue any fees for the admin to prevent
-            // funds from being locked in the contract. This should never happen.
-            _ => 0,
-        };
+        }
+
+        if let Some(management_fee) = fee.management_fee {
+            // management fee - admin earns an annualized percentage of the vault's bTokens over the time
+            // elapsed, regardless of the interest accrued
+            let remaining_b_tokens = self.total_b_tokens - admin_b_tokens;
+            admin_b_tokens += remaining_b_tokens
+                .fixed_mul_floor(
+                    management_fee as i128 * time_elapsed as i128,
+                    SCALAR_7 * SECONDS_PER_YEAR,
+                )
+                .unwrap_optimized()
+                .min(remaining_b_tokens);
+        }
 
         self.last_update_timestamp = now;
         self.b_rate = new_rate;
 
+        if unpaid_b_tokens > 0 {
+            self.add_deficit(e, pool, asset, unpaid_b_tokens);
+        }
+
         // if no interest was accrued we do not accrue fees
-        if admin_b_tokens == 0 {
-            return;
+        if admin_b_tokens != 0 {
+            if admin_b_tokens > 0 {
+                self.unsplit_fees += admin_b_tokens;
+            }
+            self.total_b_tokens = self.total_b_tokens - admin_b_tokens;
+            self.admin_balance = self.admin_balance + admin_b_tokens;
+        }
+
+        // the admin's own part of the fees repays any deficit owed to the vault. The fee recipients' part is
+        // split as usual.
+        if admin_b_tokens > 0 {
+            let admin_own_b_tokens = (admin_b_tokens
+                - self.recipients_fee_share(e, admin_b_tokens))
+            .min(self.admin_fee_balance(e));
+            let repaid_b_tokens = self.repay_deficit(e, pool, asset, admin_own_b_tokens);
+            self.total_b_tokens += repaid_b_tokens;
+            self.admin_balance -= repaid_b_tokens;
         }
+        self.update_high_water_mark();
+    }
+}
+
+/// Calculate the entry or exit fee charged on `b_tokens`, rounding up
+fn transaction_fee(b_tokens: i128, fee: Option<u32>) -> i128 {
+    match fee {
+        Some(fee) if fee > 0 => b_tokens
+            .fixed_mul_ceil(fee as i128, SCALAR_BPS)
+            .unwrap_optimized(),
+        _ => 0,
+    }
+}
 
-        self.total_b_tokens = self.total_b_tokens - admin_b_tokens;
-        self.admin_balance = self.admin_balance + admin_b_tokens;
+/// Calculate the bTokens required to be left with `b_tokens` after an entry or exit fee is charged, rounding up
+fn gross_up_transaction_fee(b_tokens: i128, fee: Option<u32>) -> i128 {
+    match fee {
+        Some(fee) if fee > 0 => b_tokens
+            .fixed_mul_ceil(SCALAR_BPS, SCALAR_BPS - fee as i128)
+            .unwrap_optimized(),
+        _ => b_tokens,
     }
 }
 
@@ -177,6 +503,41 @@ pub fn get_vault_updated(e: &Env, pool: &Address, asset: &Address) -> VaultData
     vault
 }
 
+/// Fetch the remaining underlying tokens a user can deposit before a deposit cap is reached
+///
+/// ### Arguments
+/// * `vault` - The vault data
+/// * `user_shares` - The number of shares the user has in the vault
+///
+/// ### Returns
+/// * `Option<i128>` - The remaining underlying tokens that can be deposited, or None if no caps are set
+pub fn remaining_deposit_cap(e: &Env, vault: &VaultData, user_shares: i128) -> Option<i128> {
+    let total_remaining = storage::get_deposit_cap(e)
+        .map(|cap| (cap - vault.b_tokens_to_underlying_down(vault.total_b_tokens)).max(0));
+    let user_remaining = storage::get_user_deposit_cap(e).map(|cap| {
+        let user_underlying = if user_shares > 0 {
+            vault.shares_to_underlying_down(user_shares)
+        } else {
+            0
+        };
+        (cap - user_underlying).max(0)
+    });
+    match (total_remaining, user_remaining) {
+        (Some(total), Some(user)) => Some(total.min(user)),
+        (Some(total), None) => Some(total),
+        (None, user) => user,
+    }
+}
+
+/// Require that depositing `amount` underlying tokens does not exceed a deposit cap
+fn require_within_deposit_caps(e: &Env, vault: &VaultData, user_shares: i128, amount: i128) {
+    if let Some(remaining) = remaining_deposit_cap(e, vault, user_shares) {
+        if amount > remaining {
+            panic_with_error!(e, FeeVaultError::DepositCapExceeded);
+        }
+    }
+}
+
 /// Deposit into the vault. Does not perform the call to the pool to deposit the tokens.
 ///
 /// ### Arguments
@@ -186,7 +547,11 @@ pub fn get_vault_updated(e: &Env, pool: &Address, asset: &Address) -> VaultData
 /// * `amount` - The amount of underlying deposited
 ///
 /// ### Returns
-/// * `(i128, i128)` - (The amount of b_tokens minted to the vault, the amount of shares minted to the user)
+/// * `(i128, i128, i128)` - (
+///         The amount of b_tokens minted to the vault,
+///         the amount of shares minted to the user,
+///         the amount of b_tokens credited to the admin as an entry fee
+///     )
 ///
 /// ### Panics
 /// * If the underlying amount is less than or equal to 0
@@ -196,23 +561,26 @@ pub fn deposit(
     asset: &Address,
     user: &Address,
     amount: i128,
-) -> (i128, i128) {
+) -> (i128, i128, i128) {
     let mut vault = get_vault_updated(e, pool, asset);
     let mut user_shares = storage::get_vault_shares(e, user);
 
     update_rewards(e, vault.total_shares, user, user_shares);
+    require_within_deposit_caps(e, &vault, user_shares, amount);
 
-    let b_tokens_amount = vault.underlying_to_b_tokens_down(amount);
+    let fee = storage::get_fee(e);
+    let (b_tokens_amount, share_amount, fee_b_tokens) =
+        vault.preview_deposit(fee.entry_fee, amount);
     require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensMinted);
-    let share_amount = vault.b_tokens_to_shares_down(b_tokens_amount);
     require_positive(e, share_amount, FeeVaultError::InvalidSharesMinted);
 
     vault.total_shares += share_amount;
-    vault.total_b_tokens += b_tokens_amount;
+    vault.total_b_tokens += b_tokens_amount - fee_b_tokens;
+    vault.credit_transaction_fee(fee_b_tokens);
     user_shares += share_amount;
     storage::set_vault_data(e, &vault);
     storage::set_vault_shares(e, user, user_shares);
-    (b_tokens_amount, share_amount)
+    (b_tokens_amount, share_amount, fee_b_tokens)
 }
 
 /// Withdraw from the vault. Does not perform the call to the pool to withdraw the tokens.
@@ -224,10 +592,11 @@ pub fn deposit(
 /// * `amount` - The amount of underlying amount withdrawn from the vault
 ///
 /// ### Returns
-/// * `(i128, i128, i128)` - (
+/// * `(i128, i128, i128, i128)` - (
 ///         The underlying to withdraw from the pool,
 ///         The amount of b_tokens burned from the vault,
-///         the amount of shares burned from the user
+///         the amount of shares burned from the user,
+///         the amount of b_tokens credited to the admin as an exit fee
 ///     )
 ///
 /// ### Panics
@@ -239,15 +608,16 @@ pub fn withdraw(
     asset: &Address,
     user: &Address,
     amount: i128,
-) -> (i128, i128, i128) {
+) -> (i128, i128, i128, i128) {
     let mut vault = get_vault_updated(e, pool, asset);
     let mut user_shares = storage::get_vault_shares(e, user);
 
     update_rewards(e, vault.total_shares, user, user_shares);
 
-    let mut b_tokens_amount = vault.underlying_to_b_tokens_up(amount);
+    let fee = storage::get_fee(e);
+    let (mut b_tokens_amount, mut share_amount, mut fee_b_tokens) =
+        vault.preview_withdraw(fee.exit_fee, amount);
     require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensBurnt);
-    let mut share_amount = vault.b_tokens_to_shares_up(b_tokens_amount);
     require_positive(e, share_amount, FeeVaultError::InvalidSharesBurnt);
     let mut underlying_amount = amount;
 
@@ -256,44 +626,245 @@ pub fn withdraw(
         // round b_token and underlying down to prevent excess withdrawal amounts
         require_positive(e, user_shares, FeeVaultError::BalanceError);
         share_amount = user_shares;
-        underlying_amount =
-            vault.b_tokens_to_underlying_down(vault.shares_to_b_tokens_down(share_amount));
-        // the blend pool will round up the b_tokens burnt based on the underlying amount withdrawn
-        b_tokens_amount = vault.underlying_to_b_tokens_up(underlying_amount);
+        (underlying_amount, b_tokens_amount, fee_b_tokens) =
+            vault.preview_redeem(fee.exit_fee, share_amount);
     }
 
-    if vault.total_shares < share_amount || vault.total_b_tokens < b_tokens_amount {
+    if vault.total_shares < share_amount || vault.total_b_tokens < b_tokens_amount + fee_b_tokens {
         panic_with_error!(e, FeeVaultError::InsufficientReserves);
     }
 
     vault.total_shares -= share_amount;
-    vault.total_b_tokens -= b_tokens_amount;
+    vault.total_b_tokens -= b_tokens_amount + fee_b_tokens;
+    vault.credit_transaction_fee(fee_b_tokens);
+    vault.clear_deficit_if_empty(e, pool, asset);
 
     user_shares -= share_amount;
     storage::set_vault_data(e, &vault);
     storage::set_vault_shares(e, user, user_shares);
-    (underlying_amount, b_tokens_amount, share_amount)
+    (
+        underlying_amount,
+        b_tokens_amount,
+        share_amount,
+        fee_b_tokens,
+    )
+}
+
+/// Mint an exact amount of shares from the vault. Does not perform the call to the pool to deposit the tokens.
+///
+/// The underlying amount required is rounded up, such that the vault is never owed tokens.
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `asset` - The asset address
+/// * `user` - The user receiving the shares
+/// * `shares` - The amount of shares to mint
+///
+/// ### Returns
+/// * `(i128, i128, i128)` - (
+///         The underlying to deposit into the pool,
+///         the amount of b_tokens minted to the vault,
+///         the amount of b_tokens credited to the admin as an entry fee
+///     )
+///
+/// ### Panics
+/// * If the amount of b_tokens or underlying required is less than or equal to 0
+pub fn mint(
+    e: &Env,
+    pool: &Address,
+    asset: &Address,
+    user: &Address,
+    shares: i128,
+) -> (i128, i128, i128) {
+    let mut vault = get_vault_updated(e, pool, asset);
+    let mut user_shares = storage::get_vault_shares(e, user);
+
+    update_rewards(e, vault.total_shares, user, user_shares);
+
+    let fee = storage::get_fee(e);
+    let (underlying_amount, b_tokens_amount, fee_b_tokens) =
+        vault.preview_mint(fee.entry_fee, shares);
+    require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensMinted);
+    require_positive(e, underlying_amount, FeeVaultError::InvalidAmount);
+    require_within_deposit_caps(e, &vault, user_shares, underlying_amount);
+
+    vault.total_shares += shares;
+    vault.total_b_tokens += b_tokens_amount - fee_b_tokens;
+    vault.credit_transaction_fee(fee_b_tokens);
+    user_shares += shares;
+    storage::set_vault_data(e, &vault);
+    storage::set_vault_shares(e, user, user_shares);
+    (underlying_amount, b_tokens_amount, fee_b_tokens)
+}
+
+/// Redeem an exact amount of shares from the vault. Does not perform the call to the pool to withdraw the tokens.
+///
+/// The underlying amount returned is rounded down, such that the vault never pays out more than the shares are worth.
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `asset` - The asset address
+/// * `user` - The user redeeming shares
+/// * `shares` - The amount of shares to burn
+///
+/// ### Returns
+/// * `(i128, i128, i128)` - (
+///         The underlying to withdraw from the pool,
+///         the amount of b_tokens burned from the vault,
+///         the amount of b_tokens credited to the admin as an exit fee
+///     )
+///
+/// ### Panics
+/// * If the user does not have enough shares
+/// * If the shares are worth less than 1 unit of underlying
+/// * If the vault does not have enough shares or bTokens to redeem
+pub fn redeem(
+    e: &Env,
+    pool: &Address,
+    asset: &Address,
+    user: &Address,
+    shares: i128,
+) -> (i128, i128, i128) {
+    let mut vault = get_vault_updated(e, pool, asset);
+    let mut user_shares = storage::get_vault_shares(e, user);
+
+    update_rewards(e, vault.total_shares, user, user_shares);
+
+    if shares > user_shares {
+        panic_with_error!(e, FeeVaultError::BalanceError);
+    }
+
+    let fee = storage::get_fee(e);
+    let (underlying_amount, b_tokens_amount, fee_b_tokens) =
+        vault.preview_redeem(fee.exit_fee, shares);
+    require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensBurnt);
+
+    if vault.total_shares < shares || vault.total_b_tokens < b_tokens_amount + fee_b_tokens {
+        panic_with_error!(e, FeeVaultError::InsufficientReserves);
+    }
+
+    vault.total_shares -= shares;
+    vault.total_b_tokens -= b_tokens_amount + fee_b_tokens;
+    vault.credit_transaction_fee(fee_b_tokens);
+    vault.clear_deficit_if_empty(e, pool, asset);
+
+    user_shares -= shares;
+    storage::set_vault_data(e, &vault);
+    storage::set_vault_shares(e, user, user_shares);
+    (underlying_amount, b_tokens_amount, fee_b_tokens)
+}
+
+/// Burn shares from a user. The bTokens backing the burnt shares remain in the vault, and are distributed to
+/// the remaining share holders.
+///
+/// Fees are accrued before the shares are burnt, and the high-water mark is raised afterwards, so the value
+/// donated by the burn is never charged as a performance gain.
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `asset` - The asset address
+/// * `user` - The user burning shares
+/// * `shares` - The number of shares to burn
+///
+/// ### Panics
+/// * If the user does not have enough shares
+/// * If the burn would leave bTokens in the vault with no shares to claim them
+pub fn burn(e: &Env, pool: &Address, asset: &Address, user: &Address, shares: i128) {
+    let mut vault = get_vault_updated(e, pool, asset);
+    let user_shares = storage::get_vault_shares(e, user);
+    if shares > user_shares {
+        panic_with_error!(e, FeeVaultError::BalanceError);
+    }
+
+    update_rewards(e, vault.total_shares, user, user_shares);
+
+    vault.total_shares -= shares;
+    if vault.total_shares == 0 && vault.total_b_tokens > 0 {
+        // the next depositor would mint shares 1:1 and claim the remaining bTokens
+        panic_with_error!(e, FeeVaultError::InvalidSharesBurnt);
+    }
+    vault.update_high_water_mark();
+    vault.clear_deficit_if_empty(e, pool, asset);
+
+    storage::set_vault_data(e, &vault);
+    storage::set_vault_shares(e, user, user_shares - shares);
+}
+
+/// Transfer shares from one user to another. Fees are accrued and rewards are updated for both users
+/// before any shares are moved.
+///
+/// Receiving shares counts as a deposit for the receiver, so the per-user deposit cap is enforced against
+/// the receiver's balance after the transfer.
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `asset` - The asset address
+/// * `from` - The user sending shares
+/// * `to` - The user receiving shares
+/// * `shares` - The number of shares to transfer
+///
+/// ### Panics
+/// * If `from` does not have enough shares
+/// * If the receiver would hold more underlying than the per-user deposit cap
+pub fn transfer(
+    e: &Env,
+    pool: &Address,
+    asset: &Address,
+    from: &Address,
+    to: &Address,
+    shares: i128,
+) {
+    let vault = get_vault_updated(e, pool, asset);
+    let from_shares = storage::get_vault_shares(e, from);
+    if shares > from_shares {
+        panic_with_error!(e, FeeVaultError::BalanceError);
+    }
+    storage::set_vault_data(e, &vault);
+    update_rewards(e, vault.total_shares, from, from_shares);
+    if from == to || shares == 0 {
+        return;
+    }
+
+    let to_shares = storage::get_vault_shares(e, to);
+    update_rewards(e, vault.total_shares, to, to_shares);
+    if let Some(user_cap) = storage::get_user_deposit_cap(e) {
+        if vault.shares_to_underlying_down(to_shares + shares) > user_cap {
+            panic_with_error!(e, FeeVaultError::DepositCapExceeded);
+        }
+    }
+
+    storage::set_vault_shares(e, from, from_shares - shares);
+    storage::set_vault_shares(e, to, to_shares + shares);
 }
 
 /// Admin deposits tokens into the vault. Does not perform the call to the pool to deposit the tokens.
 ///
+/// If the vault has a deficit, the deposit repays it before being added to the admin balance.
+///
 /// ### Arguments
 /// * `pool` - The pool address
 /// * `asset` - The asset address
 /// * `amount` - The amount of tokens to deposit into the vault
 ///
 /// ### Returns
-/// * The amount of bTokens added to the admin balance
-pub fn admin_deposit(e: &Env, pool: &Address, asset: &Address, amount: i128) -> i128 {
+/// * (The amount of bTokens added to the admin balance, the amount of bTokens used to repay the deficit)
+pub fn admin_deposit(e: &Env, pool: &Address, asset: &Address, amount: i128) -> (i128, i128) {
     let mut vault = get_vault_updated(e, pool, asset);
 
     let b_tokens_amount = vault.underlying_to_b_tokens_down(amount);
     require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensMinted);
 
-    vault.admin_balance += b_tokens_amount;
+    // repay any deficit owed to the vault before adding to the admin balance
+    let repaid_b_tokens = vault.repay_deficit(e, pool, asset, b_tokens_amount);
+    if repaid_b_tokens > 0 {
+        vault.total_b_tokens += repaid_b_tokens;
+        vault.update_high_water_mark();
+    }
+    let credited_b_tokens = b_tokens_amount - repaid_b_tokens;
+    vault.admin_balance += credited_b_tokens;
 
     storage::set_vault_data(e, &vault);
-    b_tokens_amount
+    (credited_b_tokens, repaid_b_tokens)
 }
 
 /// Admin withdraws tokens from the vault. Does not perform the call to the pool to withdraw the tokens.
@@ -307,14 +878,14 @@ pub fn admin_deposit(e: &Env, pool: &Address, asset: &Address, amount: i128) ->
 /// * The amount of bTokens burnt from the admin balance
 ///
 /// ### Panics
-/// * If the admin balance does not have enough bTokens to withdraw
+/// * If the admin balance, excluding the fee recipients' balances, does not have enough bTokens to withdraw
 pub fn admin_withdraw(e: &Env, pool: &Address, asset: &Address, amount: i128) -> i128 {
     let mut vault = get_vault_updated(e, pool, asset);
 
     let b_tokens_burnt = vault.underlying_to_b_tokens_up(amount);
     require_positive(e, b_tokens_burnt, FeeVaultError::InvalidBTokensBurnt);
 
-    if b_tokens_burnt > vault.admin_balance {
+    if b_tokens_burnt > vault.admin_fee_balance(e) {
         panic_with_error!(e, FeeVaultError::BalanceError);
     }
     vault.admin_balance -= b_tokens_burnt;
@@ -323,23 +894,80 @@ pub fn admin_withdraw(e: &Env, pool: &Address, asset: &Address, amount: i128) ->
     b_tokens_burnt
 }
 
-#[cfg(test)]
-mod generic_tests {
-    use super::*;
-    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
-    use soroban_sdk::{testutils::Address as _, Address};
+/// Fee recipient withdraws tokens from their share of the fees. Does not perform the call to the pool to
+/// withdraw the tokens.
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `asset` - The asset address
+/// * `recipient` - The fee recipient withdrawing the tokens
+/// * `amount` - The amount of tokens to withdraw from the vault
+///
+/// ### Returns
+/// * The amount of bTokens burnt from the recipient's balance
+///
+/// ### Panics
+/// * If the recipient's balance does not have enough bTokens to withdraw
+pub fn withdraw_fees(
+    e: &Env,
+    pool: &Address,
+    asset: &Address,
+    recipient: &Address,
+    amount: i128,
+) -> i128 {
+    let mut vault = get_vault_updated(e, pool, asset);
 
-    #[test]
-    fn test_b_tokens_to_shares_down() {
-        let mut vault = VaultData {
-            b_rate: 1_000_000_000_000,
-            last_update_timestamp: 0,
-            total_shares: 0,
-            total_b_tokens: 0,
-            admin_balance: 0,
-        };
+    let b_tokens_burnt = vault.underlying_to_b_tokens_up(amount);
+    require_positive(e, b_tokens_burnt, FeeVaultError::InvalidBTokensBurnt);
 
-        // rounds down
+    vault.split_fees(e);
+    let balance = storage::get_fee_balance(e, recipient);
+    if b_tokens_burnt > balance {
+        panic_with_error!(e, FeeVaultError::BalanceError);
+    }
+    storage::set_fee_balance(e, recipient, balance - b_tokens_burnt);
+    vault.fee_recipient_balance -= b_tokens_burnt;
+    vault.admin_balance -= b_tokens_burnt;
+
+    storage::set_vault_data(e, &vault);
+    b_tokens_burnt
+}
+
+/// Split the fees accrued to the admin between the current fee recipients. Must be invoked before the fee
+/// recipients change, so the unsplit fees are split by the weights they were accrued under.
+///
+/// ### Arguments
+/// * `pool` - The pool address
+/// * `asset` - The asset address
+pub fn split_fees(e: &Env, pool: &Address, asset: &Address) {
+    let mut vault = get_vault_updated(e, pool, asset);
+    vault.split_fees(e);
+    storage::set_vault_data(e, &vault);
+}
+
+#[cfg(test)]
+mod generic_tests {
+    use super::*;
+    use crate::storage::FeeRecipient;
+    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
+    use soroban_sdk::{testutils::Address as _, vec, Address};
+
+    #[test]
+    fn test_b_tokens_to_shares_down() {
+        let e = Env::default();
+        let mut vault = VaultData {
+            b_rate: 1_000_000_000_000,
+            last_update_timestamp: 0,
+            total_shares: 0,
+            total_b_tokens: 0,
+            admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
+        };
+
+        // rounds down
         vault.total_shares = 200_0000001;
         vault.total_b_tokens = 100_0000000;
         let b_tokens = vault.b_tokens_to_shares_down(1_0000000);
@@ -360,12 +988,17 @@ mod generic_tests {
 
     #[test]
     fn test_b_tokens_to_shares_up() {
+        let e = Env::default();
         let mut vault = VaultData {
             b_rate: 1_000_000_000_000,
             last_update_timestamp: 0,
             total_shares: 0,
             total_b_tokens: 0,
             admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
         };
 
         // rounds up
@@ -389,12 +1022,17 @@ mod generic_tests {
 
     #[test]
     fn test_shares_to_b_tokens_down() {
+        let e = Env::default();
         let mut vault = VaultData {
             b_rate: 1_000_000_000_000,
             last_update_timestamp: 0,
             total_shares: 0,
             total_b_tokens: 0,
             admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
         };
 
         // rounds down
@@ -408,6 +1046,64 @@ mod generic_tests {
         vault.total_b_tokens = 0;
         let b_tokens = vault.shares_to_b_tokens_down(2_0000000);
         assert_eq!(b_tokens, 0);
+        // returns amount if total_shares is 0
+        vault.total_shares = 0;
+        vault.total_b_tokens = 0;
+        let b_tokens = vault.shares_to_b_tokens_down(2_0000000);
+        assert_eq!(b_tokens, 2_0000000);
+    }
+
+    #[test]
+    fn test_shares_to_b_tokens_up() {
+        let e = Env::default();
+        let mut vault = VaultData {
+            b_rate: 1_000_000_000_000,
+            last_update_timestamp: 0,
+            total_shares: 0,
+            total_b_tokens: 0,
+            admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
+        };
+
+        // rounds up
+        vault.total_shares = 200_0000001;
+        vault.total_b_tokens = 100_0000000;
+        let b_tokens = vault.shares_to_b_tokens_up(2_0000000);
+        assert_eq!(b_tokens, 1_0000000);
+
+        // returns amount if total_shares is 0
+        vault.total_shares = 0;
+        vault.total_b_tokens = 100_0000000;
+        let b_tokens = vault.shares_to_b_tokens_up(2_0000000);
+        assert_eq!(b_tokens, 2_0000000);
+
+        // returns amount if total_b_tokens is 0
+        vault.total_shares = 200_0000000;
+        vault.total_b_tokens = 0;
+        let b_tokens = vault.shares_to_b_tokens_up(2_0000000);
+        assert_eq!(b_tokens, 2_0000000);
+    }
+
+    #[test]
+    fn test_b_tokens_to_underlying_up() {
+        let e = Env::default();
+        let vault = VaultData {
+            b_rate: 1_100_000_000_000,
+            last_update_timestamp: 0,
+            total_shares: 0,
+            total_b_tokens: 0,
+            admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
+        };
+
+        assert_eq!(vault.b_tokens_to_underlying_up(1_0000001), 1_1000002);
+        assert_eq!(vault.b_tokens_to_underlying_down(1_0000001), 1_1000001);
     }
 
     #[test]
@@ -428,6 +1124,10 @@ mod generic_tests {
                 b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -442,7 +1142,7 @@ mod generic_tests {
                 .unwrap_optimized();
             let expected_b_token_fees = 0_9009009;
             let expected_share_amount = 100_0901673;
-            let (b_tokens_minted, shares_minted) = deposit(&e, &pool, &asset, &samwise, amount);
+            let (b_tokens_minted, shares_minted, _) = deposit(&e, &pool, &asset, &samwise, amount);
             assert_eq!(b_tokens_minted, b_tokens);
             assert_eq!(shares_minted, expected_share_amount);
 
@@ -479,6 +1179,10 @@ mod generic_tests {
                 b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -490,7 +1194,7 @@ mod generic_tests {
             let expected_b_tokens = amount
                 .fixed_div_floor(new_b_rate, SCALAR_12)
                 .unwrap_optimized();
-            let (b_tokens_minted, shares_minted) = deposit(&e, &pool, &asset, &samwise, amount);
+            let (b_tokens_minted, shares_minted, _) = deposit(&e, &pool, &asset, &samwise, amount);
 
             // Load the updated vault to verify the changes
             let expected_share_amount = expected_b_tokens;
@@ -524,6 +1228,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -548,6 +1256,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -573,6 +1285,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -580,6 +1296,156 @@ mod generic_tests {
         });
     }
 
+    #[test]
+    fn test_deposit_within_caps() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let b_rate = 1_100_000_000_000;
+        let mock_client = MockPoolClient::new(&e, &pool);
+        mock_client.set_b_rate(&b_rate);
+        e.as_contract(&vault_address, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            // samwise owns 110 underlying tokens
+            storage::set_vault_shares(&e, &samwise, 120_0000000);
+            storage::set_deposit_cap(&e, 1150_0000000);
+            storage::set_user_deposit_cap(&e, 200_0000000);
+
+            let vault = storage::get_vault_data(&e);
+            assert_eq!(
+                remaining_deposit_cap(&e, &vault, 120_0000000),
+                Some(50_0000000)
+            );
+            assert_eq!(remaining_deposit_cap(&e, &vault, 0), Some(50_0000000));
+
+            // deposit exactly up to the total cap
+            deposit(&e, &pool, &asset, &samwise, 50_0000000);
+            // -> the b_tokens minted are rounded down, leaving dust under the cap
+            let vault = storage::get_vault_data(&e);
+            assert_eq!(remaining_deposit_cap(&e, &vault, 0), Some(1));
+
+            // removing the caps allows further deposits
+            storage::del_deposit_cap(&e);
+            storage::del_user_deposit_cap(&e);
+            assert_eq!(remaining_deposit_cap(&e, &vault, 0), None);
+            deposit(&e, &pool, &asset, &samwise, 50_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #117)")]
+    fn test_deposit_exceeds_total_cap() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let b_rate = 1_100_000_000_000;
+        let mock_client = MockPoolClient::new(&e, &pool);
+        mock_client.set_b_rate(&b_rate);
+        e.as_contract(&vault_address, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            storage::set_deposit_cap(&e, 1150_0000000);
+
+            deposit(&e, &pool, &asset, &samwise, 50_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #117)")]
+    fn test_deposit_exceeds_user_cap() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let b_rate = 1_100_000_000_000;
+        let mock_client = MockPoolClient::new(&e, &pool);
+        mock_client.set_b_rate(&b_rate);
+        e.as_contract(&vault_address, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            // samwise owns 110 underlying tokens
+            storage::set_vault_shares(&e, &samwise, 120_0000000);
+            storage::set_user_deposit_cap(&e, 150_0000000);
+
+            deposit(&e, &pool, &asset, &samwise, 40_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #117)")]
+    fn test_mint_exceeds_total_cap() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let b_rate = 1_100_000_000_000;
+        let mock_client = MockPoolClient::new(&e, &pool);
+        mock_client.set_b_rate(&b_rate);
+        e.as_contract(&vault_address, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            storage::set_deposit_cap(&e, 1150_0000000);
+
+            // 60 shares are worth 55 underlying tokens
+            mint(&e, &pool, &asset, &samwise, 60_0000000);
+        });
+    }
+
     #[test]
     fn test_withdraw() {
         let e = Env::default();
@@ -598,6 +1464,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -615,7 +1485,7 @@ mod generic_tests {
             vault_data.b_rate = new_b_rate;
             let withdraw_amount = vault_data.b_tokens_to_underlying_down(b_tokens_to_withdraw);
 
-            let (underlying_withdrawn, b_tokens_burnt, shares_burnt) =
+            let (underlying_withdrawn, b_tokens_burnt, shares_burnt, _) =
                 withdraw(&e, &pool, &asset, &samwise, withdraw_amount);
             assert_eq!(underlying_withdrawn, withdraw_amount);
 
@@ -656,13 +1526,17 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
             storage::set_vault_shares(&e, &samwise, vault_data.total_shares);
             let withdraw_amount = vault_data.b_tokens_to_underlying_down(1000_0000000);
 
-            let (underlying_withdrawn, b_tokens_burnt, shares_burnt) =
+            let (underlying_withdrawn, b_tokens_burnt, shares_burnt, _) =
                 withdraw(&e, &pool, &asset, &samwise, withdraw_amount);
             assert_eq!(underlying_withdrawn, withdraw_amount);
             assert_eq!(b_tokens_burnt, 1000_0000000);
@@ -689,6 +1563,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -713,6 +1591,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -739,6 +1621,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -749,7 +1635,7 @@ mod generic_tests {
             let sam_underlying_balance = vault_data.b_tokens_to_underlying_down(sam_b_tokens);
 
             // Withdraw whole underlying balance as read by the contract
-            let (underlying_withdrawn, b_tokens_burnt, shares_burnt) =
+            let (underlying_withdrawn, b_tokens_burnt, shares_burnt, _) =
                 withdraw(&e, &pool, &asset, &samwise, sam_underlying_balance);
             assert_eq!(underlying_withdrawn, sam_underlying_balance);
             assert_eq!(b_tokens_burnt, sam_b_tokens);
@@ -774,6 +1660,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -784,7 +1674,7 @@ mod generic_tests {
             let sam_underlying_balance = vault_data.b_tokens_to_underlying_down(sam_b_tokens);
 
             // Try to withdraw 1 more than `sam_underlying_balance`
-            let (underlying_withdrawn, b_tokens_burnt, shares_burnt) =
+            let (underlying_withdrawn, b_tokens_burnt, shares_burnt, _) =
                 withdraw(&e, &pool, &asset, &samwise, sam_underlying_balance + 1);
             // Pulls back down to `sam_underlying_balance`
             assert_eq!(underlying_withdrawn, sam_underlying_balance);
@@ -810,6 +1700,10 @@ mod generic_tests {
                 b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
@@ -819,7 +1713,7 @@ mod generic_tests {
                 vault_data.shares_to_b_tokens_down(storage::get_vault_shares(&e, &samwise));
             let sam_underlying_balance = vault_data.b_tokens_to_underlying_down(sam_b_tokens);
 
-            let (underlying_withdrawn, b_tokens_burnt, shares_burnt) =
+            let (underlying_withdrawn, b_tokens_burnt, shares_burnt, _) =
                 withdraw(&e, &pool, &asset, &samwise, i64::MAX as i128);
             // Pulls back down to `sam_underlying_balance`
             assert_eq!(underlying_withdrawn, sam_underlying_balance);
@@ -834,403 +1728,402 @@ mod generic_tests {
     }
 
     #[test]
-    fn test_admin_deposit() {
+    fn test_mint() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
+        let init_b_rate = 1_100_000_000_000;
         let mock_client = MockPoolClient::new(&e, &pool);
-
         e.as_contract(&vault_address, || {
-            let starting_admin_balance = 5_0000000;
             let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
-                admin_balance: starting_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
-            // Perform a deposit for the admin
+            // Perform a mint for samwise
             let new_b_rate = 1_110_000_000_000;
             mock_client.set_b_rate(&new_b_rate);
             e.jump(5);
-            let expected_b_token_fees = 0_9009009;
-            let b_token_deposit = 10_0000000;
-            let amount = b_token_deposit
-                .fixed_mul_floor(new_b_rate, SCALAR_12)
-                .unwrap_optimized();
-
-            let deposit_result = admin_deposit(&e, &pool, &asset, amount);
 
-            assert_eq!(deposit_result, b_token_deposit);
+            let share_amount = 100_0000000;
+            let expected_b_token_fees = 0_9009009;
+            // b_tokens and underlying both round up
+            let expected_b_tokens = 83_2582583;
+            let expected_amount = 92_4166668;
+            let (amount, b_tokens_minted, _) = mint(&e, &pool, &asset, &samwise, share_amount);
+            assert_eq!(amount, expected_amount);
+            assert_eq!(b_tokens_minted, expected_b_tokens);
 
-            // Load the updated reserve to verify the changes
+            // the pool mints the exact amount of b_tokens accounted for by the vault
             let new_vault = storage::get_vault_data(&e);
-            assert_eq!(new_vault.total_shares, 1200_0000000);
             assert_eq!(
-                new_vault.total_b_tokens,
-                1000_0000000 - expected_b_token_fees
+                new_vault.underlying_to_b_tokens_down(amount),
+                b_tokens_minted
             );
-            assert_eq!(new_vault.b_rate, new_b_rate);
+
+            // Load the updated vault to verify the changes
+            assert_eq!(new_vault.total_shares, 1200_0000000 + share_amount);
             assert_eq!(
-                new_vault.admin_balance,
-                starting_admin_balance + expected_b_token_fees + b_token_deposit
+                new_vault.total_b_tokens,
+                1000_0000000 + expected_b_tokens - expected_b_token_fees
             );
-            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(new_vault.b_rate, new_b_rate);
+            assert_eq!(new_vault.admin_balance, expected_b_token_fees);
+
+            let new_balance = storage::get_vault_shares(&e, &samwise);
+            assert_eq!(new_balance, share_amount);
+        });
+    }
+
+    #[test]
+    fn test_initial_mint() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
+
+        let init_b_rate = 1_000_000_000_000;
+        let mock_client = MockPoolClient::new(&e, &pool);
+        e.as_contract(&vault_address, || {
+            let vault_data = VaultData {
+                total_b_tokens: 0,
+                total_shares: 0,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // Perform a mint for samwise
+            let new_b_rate = 1_100_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            let share_amount = 100_0000000;
+            let (amount, b_tokens_minted, _) = mint(&e, &pool, &asset, &samwise, share_amount);
+
+            // shares are minted 1:1 with b_tokens for an empty vault
+            assert_eq!(b_tokens_minted, share_amount);
+            assert_eq!(amount, 110_0000000);
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.total_shares, share_amount);
+            assert_eq!(new_vault.total_b_tokens, b_tokens_minted);
+            assert_eq!(new_vault.b_rate, new_b_rate);
+            // no fees should accrue against 0 deposits
+            assert_eq!(new_vault.admin_balance, 0);
+
+            let new_balance = storage::get_vault_shares(&e, &samwise);
+            assert_eq!(new_balance, share_amount);
         });
     }
 
     #[test]
     #[should_panic(expected = "Error(Contract, #106)")]
-    fn test_admin_deposit_zero_mint() {
+    fn test_mint_zero_shares() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         e.as_contract(&vault_address, || {
-            let starting_admin_balance = 5_0000000;
             let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
-                b_rate: init_b_rate,
+                b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
-                admin_balance: starting_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
-            admin_deposit(&e, &pool, &asset, 1);
+            mint(&e, &pool, &asset, &samwise, 0);
         });
     }
 
     #[test]
-    fn test_admin_withdraw() {
+    fn test_redeem() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
-            let starting_admin_balance = 5_0000000;
             let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
-                b_rate: init_b_rate,
+                b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
-                admin_balance: starting_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
-            // Perform a deposit for the admin
+            // Perform a redeem for samwise
             let new_b_rate = 1_110_000_000_000;
             mock_client.set_b_rate(&new_b_rate);
             e.jump(5);
-            let expected_b_token_fees = 0_9009009;
-            let b_token_withdraw = 3_0000000;
-            let amount = b_token_withdraw
-                .fixed_mul_floor(new_b_rate, SCALAR_12)
-                .unwrap_optimized();
 
-            let withdraw_result = admin_withdraw(&e, &pool, &asset, amount);
+            let sam_shares = 150_0000000;
+            let share_amount = 100_0000000;
+            let expected_b_token_fees = 0_9009009;
+            // b_tokens and underlying both round down
+            let expected_b_tokens = 83_2582582;
+            let expected_amount = 92_4166666;
+            storage::set_vault_shares(&e, &samwise, sam_shares);
 
-            assert_eq!(withdraw_result, b_token_withdraw);
+            let (underlying_withdrawn, b_tokens_burnt, _) =
+                redeem(&e, &pool, &asset, &samwise, share_amount);
+            assert_eq!(underlying_withdrawn, expected_amount);
+            assert_eq!(b_tokens_burnt, expected_b_tokens);
 
             // Load the updated reserve to verify the changes
             let new_vault = storage::get_vault_data(&e);
-            assert_eq!(new_vault.total_shares, 1200_0000000);
+            assert_eq!(new_vault.total_shares, 1200_0000000 - share_amount);
             assert_eq!(
                 new_vault.total_b_tokens,
-                1000_0000000 - expected_b_token_fees
+                1000_0000000 - expected_b_tokens - expected_b_token_fees
             );
             assert_eq!(new_vault.b_rate, new_b_rate);
-            assert_eq!(
-                new_vault.admin_balance,
-                starting_admin_balance + expected_b_token_fees - b_token_withdraw
-            );
-            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(new_vault.admin_balance, expected_b_token_fees);
+
+            let new_balance = storage::get_vault_shares(&e, &samwise);
+            assert_eq!(new_balance, sam_shares - share_amount);
         });
     }
 
     #[test]
-    fn test_admin_withdraw_all() {
+    fn test_redeem_full_vault() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
-
-        let mock_client = MockPoolClient::new(&e, &pool);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         e.as_contract(&vault_address, || {
-            let starting_admin_balance = 5_0000000;
             let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
-                b_rate: init_b_rate,
+                b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
-                admin_balance: starting_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
 
-            // Perform a withdraw for the admin
-            let new_b_rate = 1_110_000_000_000;
-            mock_client.set_b_rate(&new_b_rate);
-            e.jump(5);
-            let expected_b_token_fees = 0_9009009;
-            let b_token_withdraw = starting_admin_balance + expected_b_token_fees;
-            let amount = b_token_withdraw
-                .fixed_mul_floor(new_b_rate, SCALAR_12)
-                .unwrap_optimized();
-
-            let withdraw_result = admin_withdraw(&e, &pool, &asset, amount);
-
-            assert_eq!(withdraw_result, b_token_withdraw);
+            storage::set_vault_shares(&e, &samwise, vault_data.total_shares);
 
-            // Load the updated reserve to verify the changes
-            let new_vault = storage::get_vault_data(&e);
-            assert_eq!(new_vault.total_shares, 1200_0000000);
-            assert_eq!(
-                new_vault.total_b_tokens,
-                1000_0000000 - expected_b_token_fees
-            );
-            assert_eq!(new_vault.b_rate, new_b_rate);
-            assert_eq!(new_vault.admin_balance, 0);
-            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+            let (underlying_withdrawn, b_tokens_burnt, _) =
+                redeem(&e, &pool, &asset, &samwise, vault_data.total_shares);
+            assert_eq!(underlying_withdrawn, 1100_0000000);
+            assert_eq!(b_tokens_burnt, 1000_0000000);
+            assert_eq!(storage::get_vault_shares(&e, &samwise), 0);
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_b_tokens, 0);
+            assert_eq!(vault_data.total_shares, 0);
+            assert_eq!(vault_data.admin_balance, 0);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #10)")]
-    fn test_admin_withdraw_balance_error() {
+    fn test_redeem_full_vault_clears_deficit() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
-
-        let mock_client = MockPoolClient::new(&e, &pool);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 2, 0_0500000, None);
 
         e.as_contract(&vault_address, || {
-            let starting_admin_balance = 5_0000000;
             let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
-                b_rate: init_b_rate,
+                b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
-                admin_balance: starting_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 5_0000000,
             };
             storage::set_vault_data(&e, &vault_data);
 
-            // Perform a withdraw for the admin
-            let new_b_rate = 1_110_000_000_000;
-            mock_client.set_b_rate(&new_b_rate);
-            e.jump(5);
-            let expected_b_token_fees = 0_9009009;
-            let b_token_withdraw = starting_admin_balance + expected_b_token_fees + 1;
-            let amount = b_token_withdraw
-                .fixed_mul_floor(new_b_rate, SCALAR_12)
-                .unwrap_optimized();
+            storage::set_vault_shares(&e, &samwise, vault_data.total_shares);
 
-            admin_withdraw(&e, &pool, &asset, amount);
+            redeem(&e, &pool, &asset, &samwise, vault_data.total_shares);
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_shares, 0);
+            assert_eq!(vault_data.deficit, 0);
+
+            // -> admin deposits are no longer used to repay the deficit
+            let (b_tokens, repaid_b_tokens) = admin_deposit(&e, &pool, &asset, 11_0000000);
+            assert_eq!(b_tokens, 10_0000000);
+            assert_eq!(repaid_b_tokens, 0);
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_b_tokens, 0);
+            assert_eq!(vault_data.admin_balance, 10_0000000);
         });
     }
 
     #[test]
-    fn test_admin_withdraw_zero_shares() {
+    fn test_burn() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
-
-        let mock_client = MockPoolClient::new(&e, &pool);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         e.as_contract(&vault_address, || {
-            let starting_admin_balance = 5_0000000;
             let vault_data = VaultData {
-                total_b_tokens: 0,
-                total_shares: 0,
-                b_rate: init_b_rate,
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
-                admin_balance: starting_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
             storage::set_vault_data(&e, &vault_data);
+            storage::set_vault_shares(&e, &samwise, 100_0000000);
 
-            // Perform a withdraw for the admin
-            // Even if b_rate doubles, since there are no b_tokens deposited, no more fees should've been accrued
-            let new_b_rate = 2_200_000_000_000;
-            mock_client.set_b_rate(&new_b_rate);
-            e.jump(5);
-            let amount = starting_admin_balance
-                .fixed_mul_floor(new_b_rate, SCALAR_12)
-                .unwrap_optimized();
-
-            let withdraw_result = admin_withdraw(&e, &pool, &asset, amount);
-
-            assert_eq!(withdraw_result, starting_admin_balance);
-
-            // Load the updated reserve to verify the changes
-            let new_vault = storage::get_vault_data(&e);
-            assert_eq!(new_vault.total_shares, 0);
-            assert_eq!(new_vault.total_b_tokens, 0);
-            assert_eq!(new_vault.b_rate, new_b_rate);
-            assert_eq!(new_vault.admin_balance, 0);
-            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+            burn(&e, &pool, &asset, &samwise, 100_0000000);
+            assert_eq!(storage::get_vault_shares(&e, &samwise), 0);
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_shares, 1100_0000000);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000);
+            // -> the donated value raises the high-water mark instead of being charged as a gain
+            assert_eq!(vault_data.high_water_mark, 1_000_000_000_000);
         });
     }
-}
-
-#[cfg(test)]
-mod take_rate_tests {
-    use super::*;
-    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
-    use soroban_sdk::{testutils::Address as _, Address};
 
     #[test]
-    fn test_update_rate() {
+    #[should_panic(expected = "Error(Contract, #112)")]
+    fn test_burn_last_shares() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_2000000, Some(init_b_rate));
-
-        let mock_client = MockPoolClient::new(&e, &pool);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         e.as_contract(&vault_address, || {
-            let mut vault_data = VaultData {
+            let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
-                last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
-                b_rate: init_b_rate,
+                b_rate: 1_100_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
+            storage::set_vault_data(&e, &vault_data);
+            storage::set_vault_shares(&e, &samwise, vault_data.total_shares);
 
-            // update b_rate to 1.2
-            let expected_accrued_fee = 16_6666666;
-            mock_client.set_b_rate(&120_000_0000_000);
-            e.jump(5);
-            vault_data.update_rate(&e, &pool, &asset);
-
-            assert_eq!(vault_data.admin_balance, expected_accrued_fee);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 16_6666666);
-
-            // update b_rate to 1.5
-            let expected_accrued_fee_2 = 39_333_3333;
-            mock_client.set_b_rate(&150_000_0000_000);
-            e.jump(5);
-
-            vault_data.update_rate(&e, &pool, &asset);
-
-            assert_eq!(
-                vault_data.admin_balance,
-                expected_accrued_fee + expected_accrued_fee_2
-            );
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(
-                vault_data.total_b_tokens,
-                1000_0000000 - 16_6666666 - 39_333_3333
-            );
+            burn(&e, &pool, &asset, &samwise, vault_data.total_shares);
         });
     }
 
     #[test]
-    fn test_update_rate_2() {
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_redeem_over_balance() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_000_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_2000000, Some(init_b_rate));
-
-        let mock_client = MockPoolClient::new(&e, &pool);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         e.as_contract(&vault_address, || {
-            let mut vault_data = VaultData {
-                total_b_tokens: 500_000_0000000,
-                total_shares: 500_000_0000000,
-                b_rate: init_b_rate,
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
+            storage::set_vault_data(&e, &vault_data);
 
-            let expected_accrued_fee = 1050_1384599;
-
-            let new_b_rate = 1_000_0000000 * SCALAR_12 / 989_4986154;
-            mock_client.set_b_rate(&new_b_rate);
-            e.jump(5);
-            vault_data.update_rate(&e, &pool, &asset);
-            let deposit_b_tokens = 989_4986154;
-            let shares = vault_data.b_tokens_to_shares_down(deposit_b_tokens);
-            vault_data.total_b_tokens += deposit_b_tokens;
-            vault_data.total_shares += shares;
-            assert_eq!(
-                vault_data.total_b_tokens,
-                500_000_0000000 + 989_4986154 - expected_accrued_fee
-            );
-            assert_eq!(vault_data.total_shares, 500_000_0000000 + 991_5812105);
-
-            assert_eq!(vault_data.b_rate, 1_010_612_834_052);
+            let sam_shares = 1000_0000000;
+            storage::set_vault_shares(&e, &samwise, sam_shares);
 
-            assert_eq!(vault_data.admin_balance, expected_accrued_fee);
+            redeem(&e, &pool, &asset, &samwise, sam_shares + 1);
         });
     }
 
     #[test]
-    fn test_update_rate_no_change() {
+    #[should_panic(expected = "Error(Contract, #107)")]
+    fn test_redeem_zero_b_tokens() {
         let e = Env::default();
         e.mock_all_auths();
 
-        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
-        let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) = create_test_fee_vault(&e, &bombadil, 0, 0_1000000, None);
 
         e.as_contract(&vault_address, || {
-            let now = e.ledger().timestamp();
-            let mut vault_data = VaultData {
+            let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
-                b_rate: init_b_rate,
-                last_update_timestamp: now,
-                admin_balance: 12_0000000,
+                b_rate: 1_100_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
+            storage::set_vault_data(&e, &vault_data);
 
-            vault_data.update_rate(&e, &pool, &asset);
-            // assert nothing changes
-            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
-            assert_eq!(vault_data.total_shares, vault_data.total_shares);
-            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
-            assert_eq!(vault_data.b_rate, vault_data.b_rate);
-            assert_eq!(vault_data.last_update_timestamp, now);
+            storage::set_vault_shares(&e, &samwise, 1000_0000000);
+
+            // 1 share is worth less than 1 b_token
+            redeem(&e, &pool, &asset, &samwise, 1);
         });
     }
 
     #[test]
-    fn test_update_rate_different_timestamp_same_brate() {
+    fn test_admin_deposit() {
         let e = Env::default();
         e.mock_all_auths();
 
@@ -1239,137 +2132,2436 @@ mod take_rate_tests {
         let (vault_address, pool, asset) =
             create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
 
+        let mock_client = MockPoolClient::new(&e, &pool);
+
         e.as_contract(&vault_address, || {
-            let now = e.ledger().timestamp();
-            let mut vault_data = VaultData {
+            let starting_admin_balance = 5_0000000;
+            let vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                last_update_timestamp: now,
-                admin_balance: 12_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: starting_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
+            storage::set_vault_data(&e, &vault_data);
 
-            e.jump_time(100);
+            // Perform a deposit for the admin
+            let new_b_rate = 1_110_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            let expected_b_token_fees = 0_9009009;
+            let b_token_deposit = 10_0000000;
+            let amount = b_token_deposit
+                .fixed_mul_floor(new_b_rate, SCALAR_12)
+                .unwrap_optimized();
+
+            let deposit_result = admin_deposit(&e, &pool, &asset, amount);
+
+            assert_eq!(deposit_result, (b_token_deposit, 0));
+
+            // Load the updated reserve to verify the changes
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.total_shares, 1200_0000000);
+            assert_eq!(
+                new_vault.total_b_tokens,
+                1000_0000000 - expected_b_token_fees
+            );
+            assert_eq!(new_vault.b_rate, new_b_rate);
+            assert_eq!(
+                new_vault.admin_balance,
+                starting_admin_balance + expected_b_token_fees + b_token_deposit
+            );
+            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #106)")]
+    fn test_admin_deposit_zero_mint() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let starting_admin_balance = 5_0000000;
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: starting_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            admin_deposit(&e, &pool, &asset, 1);
+        });
+    }
+
+    #[test]
+    fn test_admin_deposit_repays_deficit() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 15_0000000,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // the deposit only covers part of the deficit
+            let deposit_result = admin_deposit(&e, &pool, &asset, 11_0000000);
+            assert_eq!(deposit_result, (0, 10_0000000));
+
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.deficit, 5_0000000);
+            assert_eq!(new_vault.admin_balance, 0);
+            assert_eq!(new_vault.total_b_tokens, 1010_0000000);
+            assert_eq!(new_vault.total_shares, 1200_0000000);
+
+            // the rest of the deposit is added to the admin balance once the deficit is repaid
+            let deposit_result = admin_deposit(&e, &pool, &asset, 11_0000000);
+            assert_eq!(deposit_result, (5_0000000, 5_0000000));
+
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.deficit, 0);
+            assert_eq!(new_vault.admin_balance, 5_0000000);
+            assert_eq!(new_vault.total_b_tokens, 1015_0000000);
+            assert_eq!(new_vault.total_shares, 1200_0000000);
+        });
+    }
+
+    #[test]
+    fn test_admin_withdraw() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let starting_admin_balance = 5_0000000;
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: starting_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // Perform a deposit for the admin
+            let new_b_rate = 1_110_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            let expected_b_token_fees = 0_9009009;
+            let b_token_withdraw = 3_0000000;
+            let amount = b_token_withdraw
+                .fixed_mul_floor(new_b_rate, SCALAR_12)
+                .unwrap_optimized();
+
+            let withdraw_result = admin_withdraw(&e, &pool, &asset, amount);
+
+            assert_eq!(withdraw_result, b_token_withdraw);
+
+            // Load the updated reserve to verify the changes
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.total_shares, 1200_0000000);
+            assert_eq!(
+                new_vault.total_b_tokens,
+                1000_0000000 - expected_b_token_fees
+            );
+            assert_eq!(new_vault.b_rate, new_b_rate);
+            assert_eq!(
+                new_vault.admin_balance,
+                starting_admin_balance + expected_b_token_fees - b_token_withdraw
+            );
+            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    fn test_admin_withdraw_all() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let starting_admin_balance = 5_0000000;
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: starting_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // Perform a withdraw for the admin
+            let new_b_rate = 1_110_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            let expected_b_token_fees = 0_9009009;
+            let b_token_withdraw = starting_admin_balance + expected_b_token_fees;
+            let amount = b_token_withdraw
+                .fixed_mul_floor(new_b_rate, SCALAR_12)
+                .unwrap_optimized();
+
+            let withdraw_result = admin_withdraw(&e, &pool, &asset, amount);
+
+            assert_eq!(withdraw_result, b_token_withdraw);
+
+            // Load the updated reserve to verify the changes
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.total_shares, 1200_0000000);
+            assert_eq!(
+                new_vault.total_b_tokens,
+                1000_0000000 - expected_b_token_fees
+            );
+            assert_eq!(new_vault.b_rate, new_b_rate);
+            assert_eq!(new_vault.admin_balance, 0);
+            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_admin_withdraw_balance_error() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let starting_admin_balance = 5_0000000;
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: starting_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // Perform a withdraw for the admin
+            let new_b_rate = 1_110_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            let expected_b_token_fees = 0_9009009;
+            let b_token_withdraw = starting_admin_balance + expected_b_token_fees + 1;
+            let amount = b_token_withdraw
+                .fixed_mul_floor(new_b_rate, SCALAR_12)
+                .unwrap_optimized();
+
+            admin_withdraw(&e, &pool, &asset, amount);
+        });
+    }
+
+    #[test]
+    fn test_admin_withdraw_zero_shares() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let starting_admin_balance = 5_0000000;
+            let vault_data = VaultData {
+                total_b_tokens: 0,
+                total_shares: 0,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: starting_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // Perform a withdraw for the admin
+            // Even if b_rate doubles, since there are no b_tokens deposited, no more fees should've been accrued
+            let new_b_rate = 2_200_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            let amount = starting_admin_balance
+                .fixed_mul_floor(new_b_rate, SCALAR_12)
+                .unwrap_optimized();
+
+            let withdraw_result = admin_withdraw(&e, &pool, &asset, amount);
+
+            assert_eq!(withdraw_result, starting_admin_balance);
+
+            // Load the updated reserve to verify the changes
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.total_shares, 0);
+            assert_eq!(new_vault.total_b_tokens, 0);
+            assert_eq!(new_vault.b_rate, new_b_rate);
+            assert_eq!(new_vault.admin_balance, 0);
+            assert_eq!(new_vault.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_admin_withdraw_excludes_fee_balances() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_balance(&e, &frodo, 2_0000000);
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 5_0000000,
+                fee_recipient_balance: 2_0000000,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // the admin can only withdraw the 3 bTokens not owed to frodo
+            let amount = 3_0000001
+                .fixed_mul_floor(init_b_rate, SCALAR_12)
+                .unwrap_optimized();
+
+            admin_withdraw(&e, &pool, &asset, amount);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_admin_withdraw_excludes_unsplit_fee_balances() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_recipients(
+                &e,
+                &vec![
+                    &e,
+                    FeeRecipient {
+                        address: frodo.clone(),
+                        weight: 4000,
+                    },
+                ],
+            );
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 5_0000000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 5_0000000,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            assert_eq!(vault_data.admin_fee_balance(&e), 3_0000000);
+
+            // the admin can only withdraw the 3 bTokens not owed to frodo
+            let amount = 3_0000001
+                .fixed_mul_floor(init_b_rate, SCALAR_12)
+                .unwrap_optimized();
+
+            admin_withdraw(&e, &pool, &asset, amount);
+        });
+    }
+
+    #[test]
+    fn test_withdraw_fees() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_balance(&e, &frodo, 2_0000000);
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 5_0000000,
+                fee_recipient_balance: 2_0000000,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            let amount = 1_0000000
+                .fixed_mul_floor(init_b_rate, SCALAR_12)
+                .unwrap_optimized();
+            let withdraw_result = withdraw_fees(&e, &pool, &asset, &frodo, amount);
+            assert_eq!(withdraw_result, 1_0000000);
+
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.total_b_tokens, 1000_0000000);
+            assert_eq!(new_vault.admin_balance, 4_0000000);
+            assert_eq!(new_vault.fee_recipient_balance, 1_0000000);
+            assert_eq!(storage::get_fee_balance(&e, &frodo), 1_0000000);
+            assert_eq!(new_vault.admin_fee_balance(&e), 3_0000000);
+
+            let withdraw_result = withdraw_fees(&e, &pool, &asset, &frodo, amount);
+            assert_eq!(withdraw_result, 1_0000000);
+
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.admin_balance, 3_0000000);
+            assert_eq!(new_vault.fee_recipient_balance, 0);
+            assert_eq!(storage::get_fee_balance(&e, &frodo), 0);
+        });
+    }
+
+    #[test]
+    fn test_withdraw_fees_splits_unsplit_fees() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_recipients(
+                &e,
+                &vec![
+                    &e,
+                    FeeRecipient {
+                        address: frodo.clone(),
+                        weight: 2500,
+                    },
+                    FeeRecipient {
+                        address: samwise.clone(),
+                        weight: 5000,
+                    },
+                ],
+            );
+            storage::set_fee_balance(&e, &frodo, 1_0000000);
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 5_0000001,
+                fee_recipient_balance: 1_0000000,
+                unsplit_fees: 4_0000001,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+            assert_eq!(vault_data.recipient_fee_balance(&e, &frodo), 2_0000000);
+            assert_eq!(vault_data.recipient_fee_balance(&e, &samwise), 2_0000000);
+
+            let amount = 2_0000000
+                .fixed_mul_floor(init_b_rate, SCALAR_12)
+                .unwrap_optimized();
+            let withdraw_result = withdraw_fees(&e, &pool, &asset, &frodo, amount);
+            assert_eq!(withdraw_result, 2_0000000);
+
+            let new_vault = storage::get_vault_data(&e);
+            assert_eq!(new_vault.unsplit_fees, 0);
+            assert_eq!(new_vault.admin_balance, 3_0000001);
+            assert_eq!(new_vault.fee_recipient_balance, 2_0000000);
+            assert_eq!(storage::get_fee_balance(&e, &frodo), 0);
+            assert_eq!(storage::get_fee_balance(&e, &samwise), 2_0000000);
+            // the admin keeps the unassigned weight and any rounding
+            assert_eq!(new_vault.admin_fee_balance(&e), 1_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_withdraw_fees_balance_error() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_balance(&e, &frodo, 2_0000000);
+            let vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 5_0000000,
+                fee_recipient_balance: 2_0000000,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+            storage::set_vault_data(&e, &vault_data);
+
+            // samwise is not owed any fees
+            let amount = 1_0000000
+                .fixed_mul_floor(init_b_rate, SCALAR_12)
+                .unwrap_optimized();
+            withdraw_fees(&e, &pool, &asset, &samwise, amount);
+        });
+    }
+}
+
+#[cfg(test)]
+mod take_rate_tests {
+    use super::*;
+    use crate::storage::{Fee, FeeRecipient, TakeRateBand, TakeRateTier};
+    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
+    use soroban_sdk::{testutils::Address as _, vec, Address};
+
+    #[test]
+    fn test_update_rate() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_2000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // update b_rate to 1.2
+            let expected_accrued_fee = 16_6666666;
+            mock_client.set_b_rate(&120_000_0000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, expected_accrued_fee);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 16_6666666);
+
+            // update b_rate to 1.5
+            let expected_accrued_fee_2 = 39_333_3333;
+            mock_client.set_b_rate(&150_000_0000_000);
+            e.jump(5);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(
+                vault_data.admin_balance,
+                expected_accrued_fee + expected_accrued_fee_2
+            );
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(
+                vault_data.total_b_tokens,
+                1000_0000000 - 16_6666666 - 39_333_3333
+            );
+        });
+    }
+
+    #[test]
+    fn test_update_rate_splits_fees() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_2000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_recipients(
+                &e,
+                &vec![
+                    &e,
+                    FeeRecipient {
+                        address: frodo.clone(),
+                        weight: 2500,
+                    },
+                    FeeRecipient {
+                        address: samwise.clone(),
+                        weight: 5000,
+                    },
+                ],
+            );
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // update b_rate to 1.2
+            let expected_accrued_fee = 16_6666666;
+            mock_client.set_b_rate(&120_000_0000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, expected_accrued_fee);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 16_6666666);
+            assert_eq!(vault_data.unsplit_fees, expected_accrued_fee);
+            assert_eq!(vault_data.recipient_fee_balance(&e, &frodo), 4_1666666);
+            assert_eq!(vault_data.recipient_fee_balance(&e, &samwise), 8_3333333);
+            // the admin keeps the unassigned weight and any rounding
+            assert_eq!(vault_data.admin_fee_balance(&e), 4_1666667);
+
+            // splitting the fees moves them into the recipients' balances
+            vault_data.split_fees(&e);
+            assert_eq!(vault_data.unsplit_fees, 0);
+            assert_eq!(vault_data.fee_recipient_balance, 12_4999999);
+            assert_eq!(storage::get_fee_balance(&e, &frodo), 4_1666666);
+            assert_eq!(storage::get_fee_balance(&e, &samwise), 8_3333333);
+            assert_eq!(vault_data.recipient_fee_balance(&e, &frodo), 4_1666666);
+            assert_eq!(vault_data.admin_fee_balance(&e), 4_1666667);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_2() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_2000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 500_000_0000000,
+                total_shares: 500_000_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let expected_accrued_fee = 1050_1384599;
+
+            let new_b_rate = 1_000_0000000 * SCALAR_12 / 989_4986154;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+            let deposit_b_tokens = 989_4986154;
+            let shares = vault_data.b_tokens_to_shares_down(deposit_b_tokens);
+            vault_data.total_b_tokens += deposit_b_tokens;
+            vault_data.total_shares += shares;
+            assert_eq!(
+                vault_data.total_b_tokens,
+                500_000_0000000 + 989_4986154 - expected_accrued_fee
+            );
+            assert_eq!(vault_data.total_shares, 500_000_0000000 + 991_5812105);
+
+            assert_eq!(vault_data.b_rate, 1_010_612_834_052);
+
+            assert_eq!(vault_data.admin_balance, expected_accrued_fee);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_no_change() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let now = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: now,
+                admin_balance: 12_0000000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            vault_data.update_rate(&e, &pool, &asset);
+            // assert nothing changes
+            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
+            assert_eq!(vault_data.total_shares, vault_data.total_shares);
+            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
+            assert_eq!(vault_data.b_rate, vault_data.b_rate);
+            assert_eq!(vault_data.last_update_timestamp, now);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_different_timestamp_same_brate() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let now = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: now,
+                admin_balance: 12_0000000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            e.jump_time(100);
+
+            vault_data.update_rate(&e, &pool, &asset);
+            // assert nothing changes
+            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
+            assert_eq!(vault_data.total_shares, vault_data.total_shares);
+            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
+            assert_eq!(vault_data.b_rate, vault_data.b_rate);
+            // Assert the timestamp still gets updated
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    fn test_update_rate_negative_value() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_100_000_000_000;
+
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 100_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 100_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // negative rate
+            let new_b_rate: i128 = 1_050_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Assert b_rate change is reflected
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+
+            // negative rate - time change - does not deduct already accrued fees
+            let new_b_rate: i128 = 1_010_000_000_000;
+            vault_data.admin_balance = 100_0000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Assert nothing changes apart
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 100_0000);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    fn test_update_rate_rounds_down() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let init_timestamp = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 100_0000000,
+                last_update_timestamp: init_timestamp,
+                total_shares: 100_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // 2% rate over 5s - too small for vault to capture any interest
+            // for for the admin with a 1% take rate
+            let new_b_rate: i128 = 1_000_000_003_171;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump_time(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Assert b_rate change is reflected but rounds fees to zero if less than a stroop amount
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+
+            // Assert with enough b_tokens to capture interest still rounds down
+            // reset vault
+            vault_data.total_b_tokens = 1000_0000000;
+            vault_data.b_rate = init_b_rate;
+            vault_data.admin_balance = 0;
+            vault_data.last_update_timestamp = init_timestamp;
+
+            // new_b_rate already applied to pool
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 2); // actual is 0_0000002_9
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_tiered_take_rate() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_2000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_2000000);
+            fee.take_rate_tiers = vec![
+                &e,
+                TakeRateTier {
+                    threshold: 1000_0000000,
+                    rate: 0_1000000,
+                },
+                TakeRateTier {
+                    threshold: 2000_0000000,
+                    rate: 0_0500000,
+                },
+            ];
+            storage::set_fee(&e, fee);
+
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // update b_rate to 1.2, the vault's TVL is 1200 tokens so the 10% tier applies
+            mock_client.set_b_rate(&1_200_000_000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, 8_3333333);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 8_3333333);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_tiered_take_rate_below_tiers() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_2000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_2000000);
+            fee.take_rate_tiers = vec![
+                &e,
+                TakeRateTier {
+                    threshold: 2000_0000000,
+                    rate: 0_1000000,
+                },
+            ];
+            storage::set_fee(&e, fee);
+
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // update b_rate to 1.2, the vault's TVL is below the first tier so the base take rate applies
+            mock_client.set_b_rate(&1_200_000_000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, 16_6666666);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 16_6666666);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_banded_take_rate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0);
+            fee.take_rate_bands = vec![
+                &e,
+                TakeRateBand {
+                    apr: 0_0500000,
+                    rate: 0_1000000,
+                },
+                TakeRateBand {
+                    apr: 0_1500000,
+                    rate: 0_3000000,
+                },
+            ];
+            storage::set_fee(&e, fee);
+
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1000_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // 2% APR is below the first band, so no fees are taken
+            mock_client.set_b_rate(&1_020_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000);
+
+            // 10% APR reaches the first band, so the admin takes 10% of the interest
+            mock_client.set_b_rate(&1_122_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, 9_0909090);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 9_0909090);
+
+            // 20% APR reaches the second band, so the admin takes 30% of the interest
+            mock_client.set_b_rate(&1_346_400_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, 9_0909090 + 49_5454545);
+        });
+    }
+}
+
+#[cfg(test)]
+mod apr_capped_tests {
+    use super::*;
+    use crate::{
+        storage::Fee,
+        testutils::{
+            assert_approx_eq_abs, assert_approx_eq_rel, create_test_fee_vault,
+            mockpool::MockPoolClient, EnvTestUtils,
+        },
+    };
+    use soroban_sdk::{testutils::Address as _, Address};
+
+    fn update_b_rate_and_time(
+        e: &Env,
+        mock_pool_client: &MockPoolClient,
+        new_b_rate: i128,
+        jump_seconds: u64,
+    ) {
+        mock_pool_client.set_b_rate(&new_b_rate);
+        e.jump_time(jump_seconds);
+    }
+
+    #[test]
+    fn test_update_rate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let new_b_rate = 1_050_000_000_000;
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // Update b_rate to 1.05 over 3 months
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, (SECONDS_PER_YEAR as u64) / 4);
+
+            vault_data.update_rate(&e, &pool, &asset);
+            let expected_fees = 357142857;
+
+            // We'd expect user's underlying value to have increased by approx. (5/4)%, as the cap is reached
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            assert_approx_eq_rel(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 125 / 10_000,
+                0_000001,
+            );
+
+            // The 1.25% growth was returned to the users, so we'd expect the admin's accrued fees to be the rest 3.75% of the initial value
+            let admin_balance_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
+            assert_approx_eq_rel(
+                admin_balance_value,
+                underlying_value_before * 375 / 10_000,
+                0_0000001,
+            );
+
+            assert_eq!(vault_data.admin_balance, expected_fees);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - expected_fees);
+
+            // Update b_rate to 1.06 over 6 months
+            let final_b_rate = 1_060_000_000_000;
+            update_b_rate_and_time(
+                &e,
+                &mock_client,
+                final_b_rate,
+                (SECONDS_PER_YEAR as u64) / 2,
+            );
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // The target APR wasn't reached, so we expect that the whole interest is distributed to the users, with no fee acrual
+            assert_eq!(vault_data.admin_balance, expected_fees);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, final_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - expected_fees);
+
+            // The user's should still get some value
+            let final_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // Approx 0.009% increase. All the value should end up to the users
+            let increase_pct = final_b_rate * SCALAR_12 / new_b_rate;
+            assert_eq!(
+                final_underlying_value,
+                underlying_value_after * increase_pct / SCALAR_12
+            );
+
+            // Exactly 5% increase over the next year
+            let b_rate_after_1_year = 1_113_000_000_000;
+            update_b_rate_and_time(
+                &e,
+                &mock_client,
+                b_rate_after_1_year,
+                SECONDS_PER_YEAR as u64,
+            );
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Still no fees accrued for the admin
+            assert_eq!(vault_data.admin_balance, expected_fees);
+            // The user's value should've increased by 5% exactly. Accounting for rounding errors
+            assert_approx_eq_rel(
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens),
+                final_underlying_value * 105 / 100,
+                0_0000001,
+            );
+
+            assert_eq!(vault_data.b_rate, b_rate_after_1_year);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - expected_fees);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_2() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_0600000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // Assume no interest accrual for 1 month
+            update_b_rate_and_time(
+                &e,
+                &mock_client,
+                init_b_rate,
+                (SECONDS_PER_YEAR as u64) / 12,
+            );
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Assert nothing changes apart from the timestamp
+            assert_eq!(vault_data.b_rate, init_b_rate);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+
+            // 1% yield over the next 2 months - exactly 6% yearly
+            let new_b_rate = 1_010_000_000_000;
+
+            let pre_update_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, (SECONDS_PER_YEAR as u64) / 6);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let post_update_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // we expect that post_update_underlying_value = 1.01 * pre_update_underlying_value
+            assert_eq!(
+                post_update_underlying_value,
+                101 * pre_update_underlying_value / 100
+            );
+            // The admin still shouldn't have accrued any fees
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+
+            // 3% yield over the next 3 months, 12% yearly, so the admin should accrue some fees
+            let final_b_rate = 1_040_300_000_000;
+            update_b_rate_and_time(
+                &e,
+                &mock_client,
+                final_b_rate,
+                (SECONDS_PER_YEAR as u64) / 4,
+            );
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let final_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // We expect that the underlying value now is (6/4)%=1.5% higher than the previous value
+            assert_eq!(
+                final_underlying_value,
+                post_update_underlying_value * 1015 / 1000
+            );
+            assert_eq!(vault_data.b_rate, final_b_rate);
+            assert_eq!(vault_data.total_shares, 1200_0000000);
+            assert_ne!(vault_data.admin_balance, 0);
+            assert_eq!(
+                vault_data.total_b_tokens,
+                1000_0000000 - vault_data.admin_balance
+            );
+
+            // Since the growth was 3%, 1.5% should be the user's yield and the rest 1.5% the accrued fees
+            let expected_admin_balance = final_underlying_value - post_update_underlying_value;
+            let admin_balance_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
+            // there may be a small rounding error
+            assert_approx_eq_rel(admin_balance_value, expected_admin_balance, 0_0000001);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_capped_rounds_down() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_0100000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let init_timestamp = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 10_0000000,
+                last_update_timestamp: init_timestamp,
+                total_shares: 10_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // 2% rate over 5s - too small for vault to capture any interest
+            // for the admin on the 1% capped rate with 10 b_tokens
+            let new_b_rate: i128 = 1_000_000_003_171;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Assert b_rate change is reflected but rounds fees to zero if less than a stroop amount
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.total_b_tokens, 10_0000000);
+            assert_eq!(vault_data.total_shares, 10_0000000);
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+
+            // Assert with enough b_tokens to capture interest still rounds down
+            // reset vault
+            vault_data.total_b_tokens = 150_0000000;
+            vault_data.b_rate = init_b_rate;
+            vault_data.admin_balance = 0;
+            vault_data.last_update_timestamp = init_timestamp;
+
+            // new_b_rate already applied to pool
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 1);
+            assert_eq!(vault_data.total_b_tokens, 150_0000000 - 1);
+            assert_eq!(vault_data.total_shares, 10_0000000);
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+        });
+    }
+
+    #[test]
+    fn update_apr_cap() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_1000000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // Assume 5% APR over 6 months
+            let b_rate = 1_050_000_000_000;
+            let pre_update_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            update_b_rate_and_time(&e, &mock_client, b_rate, (SECONDS_PER_YEAR as u64) / 2);
+            vault_data.update_rate(&e, &pool, &asset);
+            let post_update_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // no admin_balance, as the APR is equal to the cap
+            assert_eq!(
+                post_update_underlying_value,
+                105 * pre_update_underlying_value / 100
+            );
+            assert_eq!(vault_data.b_rate, b_rate);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+
+            // The admin decides to update the apr_cap to 5%, as 10% didn't yield any interest to the admin
+            storage::set_fee(&e, Fee::from_rate_type(&e, 1, 0_0500000));
+
+            // Assume 4% APR increase over the the next 6 months, 8% yearly
+            let new_b_rate = 1_092_000_000_000;
+
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, (SECONDS_PER_YEAR as u64) / 2);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let final_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // The target APR is reached, so the users should get an increase of 2.5%
+            assert_eq!(
+                final_underlying_value,
+                post_update_underlying_value * 1025 / 1000
+            );
+            // The rest 1.5% should be the admin's accrued fees
+            let expected_fees = post_update_underlying_value * 15 / 1000;
+            let admin_balance_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
+            assert_approx_eq_rel(admin_balance_value, expected_fees, 0_0000001);
+
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.total_shares, 1200_0000000);
+            assert_ne!(vault_data.admin_balance, 0);
+            assert_eq!(
+                vault_data.total_b_tokens,
+                1000_0000000 - vault_data.admin_balance
+            );
+        });
+    }
+
+    #[test]
+    fn change_fee_mode() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_0800000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // Assume 10% APR over 12 months
+            let b_rate = 1_100_000_000_000;
+            let pre_update_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            update_b_rate_and_time(&e, &mock_client, b_rate, SECONDS_PER_YEAR as u64);
+            vault_data.update_rate(&e, &pool, &asset);
+            let post_update_underlying_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            let admin_balance_value =
+                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
+            // no admin_balance, as the APR is equal to the cap
+            assert_eq!(
+                post_update_underlying_value,
+                108 * pre_update_underlying_value / 100
+            );
+            // The rest 2% is the fees(there could be a small rounding error)
+            assert_approx_eq_rel(
+                admin_balance_value,
+                2 * pre_update_underlying_value / 100,
+                0_0000001,
+            );
+            assert_eq!(vault_data.b_rate, b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+
+            // Update the fee mode to take_rate with 20% take rate
+            storage::set_fee(&e, Fee::from_rate_type(&e, 0, 200_0000));
+
+            let new_b_rate = 1_200_000_000_000;
+
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, SECONDS_PER_YEAR as u64);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // 163636363 accrued fees from this accrual + the pre-existing fees
+            let expected_accrued_fee = 34_5454544;
+
+            assert_eq!(vault_data.admin_balance, expected_accrued_fee);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(
+                vault_data.total_b_tokens,
+                1000_0000000 - expected_accrued_fee
+            );
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    fn test_update_rate_no_change() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let now = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: now,
+                admin_balance: 12_0000000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            vault_data.update_rate(&e, &pool, &asset);
+            // assert nothing changes
+            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
+            assert_eq!(vault_data.total_shares, vault_data.total_shares);
+            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
+            assert_eq!(vault_data.b_rate, vault_data.b_rate);
+            assert_eq!(vault_data.last_update_timestamp, now);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_different_timestamp_same_brate() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let now = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                last_update_timestamp: now,
+                admin_balance: 12_0000000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            e.jump_time(100);
+
+            vault_data.update_rate(&e, &pool, &asset);
+            // assert nothing changes
+            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
+            assert_eq!(vault_data.total_shares, vault_data.total_shares);
+            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
+            assert_eq!(vault_data.b_rate, vault_data.b_rate);
+            // assert the timestamp still gets updated
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    fn test_update_rate_below_target() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_admin_balance = 10_0000000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // approx 3.65% APR over 1 day
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // 0.000_100_000 = 0.0365 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 100_000 / 1_000_000_000,
+                10,
+            );
+
+            // no b_token fees applied when below target
+            assert_eq!(vault_data.admin_balance, init_admin_balance);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(vault_data.total_b_tokens, init_b_supply);
+        });
+    }
+}
+
+#[cfg(test)]
+mod fixed_rate_tests {
+    use super::*;
+    use crate::storage::FeeRecipient;
+    use crate::testutils::{
+        assert_approx_eq_abs, create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils,
+    };
+    use soroban_sdk::{testutils::Address as _, vec, Address};
+
+    fn update_b_rate_and_time(
+        e: &Env,
+        mock_pool_client: &MockPoolClient,
+        new_b_rate: i128,
+        jump_seconds: u64,
+    ) {
+        mock_pool_client.set_b_rate(&new_b_rate);
+        e.jump_time(jump_seconds);
+    }
+
+    #[test]
+    fn test_update_rate_over_target() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_admin_balance = 10_0000000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // approx 10.95% APR over 1 day
+            let new_b_rate = 1_000_300_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // 0.000_136_986 = 0.05 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000,
+                10,
+            );
+
+            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
+            let underlying_admin_delta =
+                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
+            // 0.000_163_013 = 0.0595 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_admin_delta,
+                underlying_value_before * 163_013 / 1_000_000_000,
+                10,
+            );
+
+            assert_eq!(
+                vault_data.admin_balance,
+                init_admin_balance + admin_balance_delta
+            );
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(
+                vault_data.total_b_tokens,
+                init_b_supply - admin_balance_delta
+            );
+        });
+    }
+
+    #[test]
+    fn test_update_rate_below_target() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_admin_balance = 10_0000000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // approx 3.65% APR over 1 day
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // 0.000_136_986 = 0.05 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000,
+                10,
+            );
+
+            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
+            let underlying_admin_delta =
+                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
+            // 0.000_036_986 = 0.0135 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_admin_delta,
+                -1 * underlying_value_before * 36_986 / 1_000_000_000,
+                10,
+            );
+
+            assert_eq!(
+                vault_data.admin_balance,
+                init_admin_balance + admin_balance_delta
+            );
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(
+                vault_data.total_b_tokens,
+                init_b_supply - admin_balance_delta
+            );
+        });
+    }
+
+    #[test]
+    fn test_update_rate_below_target_excludes_fee_recipients() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_balance(&e, &frodo, 4_0000000);
+            storage::set_fee_balance(&e, &samwise, 2_0000000);
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 6_0200000,
+                fee_recipient_balance: 6_0000000,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // approx 3.65% APR over 1 day
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // the supplement is only paid from the admin's own balance, and the rest is owed as a deficit
+            assert_eq!(vault_data.admin_balance, 6_0000000);
+            assert_eq!(vault_data.admin_fee_balance(&e), 0);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply + 0_0200000);
+            assert_eq!(vault_data.deficit, 0_0369828 - 0_0200000);
+
+            // the fee recipients' balances are untouched
+            assert_eq!(vault_data.fee_recipient_balance, 6_0000000);
+            assert_eq!(storage::get_fee_balance(&e, &frodo), 4_0000000);
+            assert_eq!(storage::get_fee_balance(&e, &samwise), 2_0000000);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_over_target_rounds_down() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0100000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let init_timestamp = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 10_0000000,
+                last_update_timestamp: init_timestamp,
+                total_shares: 10_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // 2% rate over 5s - too small for vault to capture any interest
+            // for the admin on the 1% capped rate with 10 b_tokens
+            let new_b_rate: i128 = 1_000_000_003_171;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Assert b_rate change is reflected but rounds fees to zero if less than a stroop amount
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.total_b_tokens, 10_0000000);
+            assert_eq!(vault_data.total_shares, 10_0000000);
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+
+            // Assert with enough b_tokens to capture interest still rounds down
+            // reset vault
+            vault_data.total_b_tokens = 150_0000000;
+            vault_data.b_rate = init_b_rate;
+            vault_data.admin_balance = 0;
+            vault_data.last_update_timestamp = init_timestamp;
+
+            // new_b_rate already applied to pool
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 1);
+            assert_eq!(vault_data.total_b_tokens, 150_0000000 - 1);
+            assert_eq!(vault_data.total_shares, 10_0000000);
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_below_target_rounds_down() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0300000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let init_timestamp = e.ledger().timestamp();
+            let mut vault_data = VaultData {
+                total_b_tokens: 10_0000000,
+                last_update_timestamp: init_timestamp,
+                total_shares: 10_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 100,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // 2% rate over 5s - required supplemental b_tokens below 1 stroop
+            let new_b_rate: i128 = 1_000_000_003_171;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // Asset admin balance is rounding away from zero
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 99);
+            assert_eq!(vault_data.total_b_tokens, 10_0000000 + 1);
+            assert_eq!(vault_data.total_shares, 10_0000000);
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+
+            // Assert with more than 1 stroop of supplemental b_tokens still rounds down
+            // reset vault
+            vault_data.total_b_tokens = 100_0000000;
+            vault_data.b_rate = init_b_rate;
+            vault_data.admin_balance = 100;
+            vault_data.last_update_timestamp = init_timestamp;
 
+            // new_b_rate already applied to pool
             vault_data.update_rate(&e, &pool, &asset);
-            // assert nothing changes
-            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
-            assert_eq!(vault_data.total_shares, vault_data.total_shares);
-            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
-            assert_eq!(vault_data.b_rate, vault_data.b_rate);
-            // Assert the timestamp still gets updated
+
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 98);
+            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_below_target_over_admin_balance() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        // 100000 short
+        let init_admin_balance = 269_828;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // approx 3.65% APR over 1 day
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            // 369828 taken from the `test_update_rate_below_target` test
+            let underlying_admin_short_amount =
+                vault_data.b_tokens_to_underlying_down(369828 - init_admin_balance);
+
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // 0.000_136_986 = 0.05 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000
+                    - underlying_admin_short_amount,
+                10,
+            );
+
+            // validate that the admin was supposed to be charged the full amount
+            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
+            let underlying_admin_delta =
+                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
+            // 0.000_036_986 = 0.0135 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_admin_delta - underlying_admin_short_amount,
+                -1 * underlying_value_before * 36_986 / 1_000_000_000,
+                10,
+            );
+
+            assert_eq!(vault_data.admin_balance, 0);
+            // the rest of the supplement is owed to the vault
+            assert_eq!(vault_data.deficit, 369828 - init_admin_balance);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(
+                vault_data.total_b_tokens,
+                init_b_supply - admin_balance_delta
+            );
+        });
+    }
+
+    #[test]
+    fn test_update_rate_below_target_zero_admin_balance() {
+        // expected behavior with no admin balance is that the vault acts like a capped rate vault, with the
+        // missed supplement recorded as a deficit
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_admin_balance = 0;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // approx 3.65% APR over 1 day
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // 0.000_100_000 = 0.0365 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 100_000 / 1_000_000_000,
+                10,
+            );
+
+            // no b_token fees applied when below target
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.deficit, 369828);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(vault_data.total_b_tokens, init_b_supply);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_below_target_negative_admin_balance() {
+        // this test state should never be reached, but should be handled gracefully (like a capped rate vault)
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_admin_balance = -269_828;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // approx 3.65% APR over 1 day
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // 0.000_100_000 = 0.0365 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 100_000 / 1_000_000_000,
+                10,
+            );
+
+            // no b_token fees applied when below target
+            assert_eq!(vault_data.admin_balance, init_admin_balance);
+            assert_eq!(vault_data.deficit, 369828);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(vault_data.total_b_tokens, init_b_supply);
+        });
+    }
+
+    #[test]
+    fn test_update_rate_over_target_negative_admin_balance() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_admin_balance = -10_0000000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            let underlying_value_before =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+
+            // approx 10.95% APR over 1 day
+            let new_b_rate = 1_000_300_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
+            vault_data.update_rate(&e, &pool, &asset);
+
+            let underlying_value_after =
+                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // 0.000_136_986 = 0.05 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_value_after,
+                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000,
+                10,
+            );
+
+            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
+            let underlying_admin_delta =
+                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
+            // 0.000_163_013 = 0.0595 * 1 / 365
+            assert_approx_eq_abs(
+                underlying_admin_delta,
+                underlying_value_before * 163_013 / 1_000_000_000,
+                10,
+            );
+
+            assert_eq!(
+                vault_data.admin_balance,
+                init_admin_balance + admin_balance_delta
+            );
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
             assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(
+                vault_data.total_b_tokens,
+                init_b_supply - admin_balance_delta
+            );
         });
     }
 
     #[test]
-    fn test_update_rate_negative_value() {
+    fn test_update_rate_over_target_repays_deficit() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_b_rate = 1_100_000_000_000;
-
+        let init_deficit = 100000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
             let mut vault_data = VaultData {
-                total_b_tokens: 100_0000000,
+                total_b_tokens: init_b_supply,
                 last_update_timestamp: e.ledger().timestamp(),
-                total_shares: 100_0000000,
+                total_shares: 1200_0000000,
                 b_rate: init_b_rate,
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: init_deficit,
             };
 
-            // negative rate
-            let new_b_rate: i128 = 1_050_000_000_000;
-            mock_client.set_b_rate(&new_b_rate);
+            // approx 10.95% APR over 1 day
+            let new_b_rate = 1_000_300_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
             vault_data.update_rate(&e, &pool, &asset);
 
-            // Assert b_rate change is reflected
+            // 1629647 bTokens of excess interest, of which the deficit is repaid first and left in the vault
+            assert_eq!(vault_data.deficit, 0);
+            assert_eq!(vault_data.admin_balance, 1629647 - init_deficit);
+            assert_eq!(
+                vault_data.total_b_tokens,
+                init_b_supply - (1629647 - init_deficit)
+            );
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
             assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 0);
             assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+
+    #[test]
+    fn test_update_rate_over_target_repays_deficit_from_admin_share() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_deficit = 1_0000000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            storage::set_fee_recipients(
+                &e,
+                &vec![
+                    &e,
+                    FeeRecipient {
+                        address: frodo.clone(),
+                        weight: 4000,
+                    },
+                ],
+            );
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: init_deficit,
+            };
+
+            // approx 10.95% APR over 1 day
+            let new_b_rate = 1_000_300_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
 
-            // negative rate - time change - does not deduct already accrued fees
-            let new_b_rate: i128 = 1_010_000_000_000;
-            vault_data.admin_balance = 100_0000;
-            mock_client.set_b_rate(&new_b_rate);
-            e.jump(5);
             vault_data.update_rate(&e, &pool, &asset);
 
-            // Assert nothing changes apart
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 100_0000);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            // 1629647 bTokens of excess interest. The recipient is credited 40% of it, and only the admin's
+            // 60% repays the deficit.
+            let recipient_b_tokens = 651858;
+            let admin_b_tokens = 1629647 - recipient_b_tokens;
+            assert_eq!(vault_data.unsplit_fees, 1629647);
+            assert_eq!(
+                vault_data.recipient_fee_balance(&e, &frodo),
+                recipient_b_tokens
+            );
+            assert_eq!(vault_data.admin_fee_balance(&e), 0);
+            assert_eq!(vault_data.deficit, init_deficit - admin_b_tokens);
+            assert_eq!(vault_data.admin_balance, recipient_b_tokens);
+            assert_eq!(
+                vault_data.total_b_tokens,
+                init_b_supply - recipient_b_tokens
+            );
+
+            // the recipient's credited balance is kept when the fees are split
+            vault_data.split_fees(&e);
+            assert_eq!(storage::get_fee_balance(&e, &frodo), recipient_b_tokens);
+            assert_eq!(vault_data.fee_recipient_balance, recipient_b_tokens);
+            assert_eq!(vault_data.admin_fee_balance(&e), 0);
         });
     }
 
     #[test]
-    fn test_update_rate_rounds_down() {
+    fn test_update_rate_flat_b_rate() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
+        let init_admin_balance = 10_0000000;
         let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
-            let init_timestamp = e.ledger().timestamp();
             let mut vault_data = VaultData {
-                total_b_tokens: 100_0000000,
-                last_update_timestamp: init_timestamp,
-                total_shares: 100_0000000,
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: 0,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            // 2% rate over 5s - too small for vault to capture any interest
-            // for for the admin with a 1% take rate
-            let new_b_rate: i128 = 1_000_000_003_171;
-            mock_client.set_b_rate(&new_b_rate);
-            e.jump_time(5);
+            // no interest earned over 1 day
+            let new_b_rate = init_b_rate;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
             vault_data.update_rate(&e, &pool, &asset);
 
-            // Assert b_rate change is reflected but rounds fees to zero if less than a stroop amount
+            // the admin supplements the vault up to the 5% floor, even though no interest was earned
+            assert_eq!(vault_data.admin_balance, init_admin_balance - 1369864);
+            assert_eq!(vault_data.deficit, 0);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply + 1369864);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
             assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 0);
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
 
-            // Assert with enough b_tokens to capture interest still rounds down
-            // reset vault
-            vault_data.total_b_tokens = 1000_0000000;
-            vault_data.b_rate = init_b_rate;
-            vault_data.admin_balance = 0;
-            vault_data.last_update_timestamp = init_timestamp;
+    #[test]
+    fn test_update_rate_falling_b_rate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_admin_balance = 10_0000000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
+
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // approx -3.65% APR over 1 day
+            let new_b_rate = 999_900_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
 
-            // new_b_rate already applied to pool
             vault_data.update_rate(&e, &pool, &asset);
 
+            // the admin supplements the vault up to the 5% floor, even though no interest was earned
+            assert_eq!(vault_data.admin_balance, init_admin_balance - 2370102);
+            assert_eq!(vault_data.deficit, 0);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply + 2370102);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
             assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 2); // actual is 0_0000002_9
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
         });
     }
 }
 
 #[cfg(test)]
-mod apr_capped_tests {
+mod floor_rate_tests {
     use super::*;
-    use crate::{
-        storage::Fee,
-        testutils::{
-            assert_approx_eq_abs, assert_approx_eq_rel, create_test_fee_vault,
-            mockpool::MockPoolClient, EnvTestUtils,
-        },
-    };
+    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
     use soroban_sdk::{testutils::Address as _, Address};
 
     fn update_b_rate_and_time(
@@ -1383,354 +4575,265 @@ mod apr_capped_tests {
     }
 
     #[test]
-    fn test_update_rate() {
+    fn test_update_rate_over_target() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
+        let init_admin_balance = 10_0000000;
         let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 5, 0_0500000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
             let mut vault_data = VaultData {
-                total_b_tokens: 1000_0000000,
+                total_b_tokens: init_b_supply,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: 0,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            let new_b_rate = 1_050_000_000_000;
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // Update b_rate to 1.05 over 3 months
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, (SECONDS_PER_YEAR as u64) / 4);
-
+            // approx 10.95% APR over 1 day, users keep all of the interest
+            let new_b_rate = 1_000_300_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
             vault_data.update_rate(&e, &pool, &asset);
-            let expected_fees = 357142857;
-
-            // We'd expect user's underlying value to have increased by approx. (5/4)%, as the cap is reached
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-
-            assert_approx_eq_rel(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 125 / 10_000,
-                0_000001,
-            );
-
-            // The 1.25% growth was returned to the users, so we'd expect the admin's accrued fees to be the rest 3.75% of the initial value
-            let admin_balance_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
-            assert_approx_eq_rel(
-                admin_balance_value,
-                underlying_value_before * 375 / 10_000,
-                0_0000001,
-            );
 
-            assert_eq!(vault_data.admin_balance, expected_fees);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.admin_balance, init_admin_balance);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply);
             assert_eq!(vault_data.b_rate, new_b_rate);
             assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - expected_fees);
-
-            // Update b_rate to 1.06 over 6 months
-            let final_b_rate = 1_060_000_000_000;
-            update_b_rate_and_time(
-                &e,
-                &mock_client,
-                final_b_rate,
-                (SECONDS_PER_YEAR as u64) / 2,
-            );
-            vault_data.update_rate(&e, &pool, &asset);
+        });
+    }
 
-            // The target APR wasn't reached, so we expect that the whole interest is distributed to the users, with no fee acrual
-            assert_eq!(vault_data.admin_balance, expected_fees);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, final_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - expected_fees);
+    #[test]
+    fn test_update_rate_below_target() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
 
-            // The user's should still get some value
-            let final_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+        let init_admin_balance = 10_0000000;
+        let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 5, 0_0500000, Some(init_b_rate));
 
-            // Approx 0.009% increase. All the value should end up to the users
-            let increase_pct = final_b_rate * SCALAR_12 / new_b_rate;
-            assert_eq!(
-                final_underlying_value,
-                underlying_value_after * increase_pct / SCALAR_12
-            );
+        let mock_client = MockPoolClient::new(&e, &pool);
 
-            // Exactly 5% increase over the next year
-            let b_rate_after_1_year = 1_113_000_000_000;
-            update_b_rate_and_time(
-                &e,
-                &mock_client,
-                b_rate_after_1_year,
-                SECONDS_PER_YEAR as u64,
-            );
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
 
+            // approx 3.65% APR over 1 day, the admin supplements the vault up to the 5% floor
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
             vault_data.update_rate(&e, &pool, &asset);
-
-            // Still no fees accrued for the admin
-            assert_eq!(vault_data.admin_balance, expected_fees);
-            // The user's value should've increased by 5% exactly. Accounting for rounding errors
-            assert_approx_eq_rel(
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens),
-                final_underlying_value * 105 / 100,
-                0_0000001,
-            );
-
-            assert_eq!(vault_data.b_rate, b_rate_after_1_year);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - expected_fees);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
+
+            // matches the fixed rate supplement for the same period
+            assert_eq!(vault_data.admin_balance, init_admin_balance - 369828);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply + 369828);
+            assert_eq!(vault_data.b_rate, new_b_rate);
         });
     }
 
     #[test]
-    fn test_update_rate_2() {
+    fn test_update_rate_below_target_zero_admin_balance() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
         let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_0600000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 5, 0_0500000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
             let mut vault_data = VaultData {
-                total_b_tokens: 1000_0000000,
+                total_b_tokens: init_b_supply,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            // Assume no interest accrual for 1 month
-            update_b_rate_and_time(
-                &e,
-                &mock_client,
-                init_b_rate,
-                (SECONDS_PER_YEAR as u64) / 12,
-            );
-            vault_data.update_rate(&e, &pool, &asset);
-
-            // Assert nothing changes apart from the timestamp
-            assert_eq!(vault_data.b_rate, init_b_rate);
-            assert_eq!(vault_data.admin_balance, 0);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-
-            // 1% yield over the next 2 months - exactly 6% yearly
-            let new_b_rate = 1_010_000_000_000;
-
-            let pre_update_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, (SECONDS_PER_YEAR as u64) / 6);
-
+            // approx 3.65% APR over 1 day, but the admin has nothing to supplement the vault with
+            let new_b_rate = 1_000_100_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
             vault_data.update_rate(&e, &pool, &asset);
 
-            let post_update_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // we expect that post_update_underlying_value = 1.01 * pre_update_underlying_value
-            assert_eq!(
-                post_update_underlying_value,
-                101 * pre_update_underlying_value / 100
-            );
-            // The admin still shouldn't have accrued any fees
             assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.deficit, 369828);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply);
             assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-
-            // 3% yield over the next 3 months, 12% yearly, so the admin should accrue some fees
-            let final_b_rate = 1_040_300_000_000;
-            update_b_rate_and_time(
-                &e,
-                &mock_client,
-                final_b_rate,
-                (SECONDS_PER_YEAR as u64) / 4,
-            );
-            vault_data.update_rate(&e, &pool, &asset);
-
-            let final_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-
-            // We expect that the underlying value now is (6/4)%=1.5% higher than the previous value
-            assert_eq!(
-                final_underlying_value,
-                post_update_underlying_value * 1015 / 1000
-            );
-            assert_eq!(vault_data.b_rate, final_b_rate);
-            assert_eq!(vault_data.total_shares, 1200_0000000);
-            assert_ne!(vault_data.admin_balance, 0);
-            assert_eq!(
-                vault_data.total_b_tokens,
-                1000_0000000 - vault_data.admin_balance
-            );
-
-            // Since the growth was 3%, 1.5% should be the user's yield and the rest 1.5% the accrued fees
-            let expected_admin_balance = final_underlying_value - post_update_underlying_value;
-            let admin_balance_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
-            // there may be a small rounding error
-            assert_approx_eq_rel(admin_balance_value, expected_admin_balance, 0_0000001);
         });
     }
 
     #[test]
-    fn test_update_rate_capped_rounds_down() {
+    fn test_update_rate_flat_b_rate() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
+        let init_admin_balance = 10_0000000;
         let init_b_rate = 1_000_000_000_000;
-
+        let init_b_supply = 1000_0000000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_0100000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 5, 0_0500000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
-            let init_timestamp = e.ledger().timestamp();
             let mut vault_data = VaultData {
-                total_b_tokens: 10_0000000,
-                last_update_timestamp: init_timestamp,
-                total_shares: 10_0000000,
+                total_b_tokens: init_b_supply,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: 0,
+                admin_balance: init_admin_balance,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            // 2% rate over 5s - too small for vault to capture any interest
-            // for the admin on the 1% capped rate with 10 b_tokens
-            let new_b_rate: i128 = 1_000_000_003_171;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 5);
-            vault_data.update_rate(&e, &pool, &asset);
-
-            // Assert b_rate change is reflected but rounds fees to zero if less than a stroop amount
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 0);
-            assert_eq!(vault_data.total_b_tokens, 10_0000000);
-            assert_eq!(vault_data.total_shares, 10_0000000);
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
-
-            // Assert with enough b_tokens to capture interest still rounds down
-            // reset vault
-            vault_data.total_b_tokens = 150_0000000;
-            vault_data.b_rate = init_b_rate;
-            vault_data.admin_balance = 0;
-            vault_data.last_update_timestamp = init_timestamp;
+            // no interest earned over 1 day
+            let new_b_rate = init_b_rate;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
 
-            // new_b_rate already applied to pool
             vault_data.update_rate(&e, &pool, &asset);
 
+            // the admin supplements the vault up to the 5% floor, even though no interest was earned
+            assert_eq!(vault_data.admin_balance, init_admin_balance - 1369864);
+            assert_eq!(vault_data.deficit, 0);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply + 1369864);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
             assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 1);
-            assert_eq!(vault_data.total_b_tokens, 150_0000000 - 1);
-            assert_eq!(vault_data.total_shares, 10_0000000);
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
         });
     }
 
     #[test]
-    fn update_apr_cap() {
+    fn test_update_rate_falling_b_rate_zero_admin_balance() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
         let init_b_rate = 1_000_000_000_000;
+        let init_b_supply = 1000_0000000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_1000000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 5, 0_0500000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
             let mut vault_data = VaultData {
-                total_b_tokens: 1000_0000000,
+                total_b_tokens: init_b_supply,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            // Assume 5% APR over 6 months
-            let b_rate = 1_050_000_000_000;
-            let pre_update_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            update_b_rate_and_time(&e, &mock_client, b_rate, (SECONDS_PER_YEAR as u64) / 2);
+            // approx -3.65% APR over 1 day
+            let new_b_rate = 999_900_000_000;
+            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+
             vault_data.update_rate(&e, &pool, &asset);
-            let post_update_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
 
-            // no admin_balance, as the APR is equal to the cap
-            assert_eq!(
-                post_update_underlying_value,
-                105 * pre_update_underlying_value / 100
-            );
-            assert_eq!(vault_data.b_rate, b_rate);
+            // the admin has nothing to supplement the vault with, so the supplement up to the 5% floor is
+            // owed to the vault as a deficit
             assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.deficit, 2370102);
+            assert_eq!(vault_data.total_b_tokens, init_b_supply);
+            assert_eq!(vault_data.total_shares, 1200_000_0000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
             assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+        });
+    }
+}
 
-            // The admin decides to update the apr_cap to 5%, as 10% didn't yield any interest to the admin
-            storage::set_fee(
-                &e,
-                Fee {
-                    rate_type: 1,
-                    rate: 0_0500000,
-                },
-            );
+#[cfg(test)]
+mod management_fee_tests {
+    use super::*;
+    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
+    use soroban_sdk::{testutils::Address as _, Address};
 
-            // Assume 4% APR increase over the the next 6 months, 8% yearly
-            let new_b_rate = 1_092_000_000_000;
+    #[test]
+    fn test_update_rate_no_interest() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
 
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, (SECONDS_PER_YEAR as u64) / 2);
-            vault_data.update_rate(&e, &pool, &asset);
+        let init_b_rate = 1_100_000_000_000;
+        let bombadil = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 3, 0_0200000, Some(init_b_rate));
 
-            let final_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+        e.as_contract(&vault_address, || {
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
 
-            // The target APR is reached, so the users should get an increase of 2.5%
-            assert_eq!(
-                final_underlying_value,
-                post_update_underlying_value * 1025 / 1000
-            );
-            // The rest 1.5% should be the admin's accrued fees
-            let expected_fees = post_update_underlying_value * 15 / 1000;
-            let admin_balance_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
-            assert_approx_eq_rel(admin_balance_value, expected_fees, 0_0000001);
+            // the fee is charged even though the b_rate didn't change
+            e.jump_time(SECONDS_PER_YEAR as u64);
+            vault_data.update_rate(&e, &pool, &asset);
 
-            assert_eq!(vault_data.b_rate, new_b_rate);
+            assert_eq!(vault_data.admin_balance, 20_0000000);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 20_0000000);
             assert_eq!(vault_data.total_shares, 1200_0000000);
-            assert_ne!(vault_data.admin_balance, 0);
-            assert_eq!(
-                vault_data.total_b_tokens,
-                1000_0000000 - vault_data.admin_balance
-            );
+            assert_eq!(vault_data.b_rate, init_b_rate);
+            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
         });
     }
 
     #[test]
-    fn change_fee_mode() {
+    fn test_update_rate_with_interest() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_b_rate = 1_000_000_000_000;
-
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_0800000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 3, 0_0200000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
@@ -1741,670 +4844,717 @@ mod apr_capped_tests {
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            // Assume 10% APR over 12 months
-            let b_rate = 1_100_000_000_000;
-            let pre_update_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            update_b_rate_and_time(&e, &mock_client, b_rate, SECONDS_PER_YEAR as u64);
-            vault_data.update_rate(&e, &pool, &asset);
-            let post_update_underlying_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            let admin_balance_value =
-                vault_data.b_tokens_to_underlying_down(vault_data.admin_balance);
-            // no admin_balance, as the APR is equal to the cap
-            assert_eq!(
-                post_update_underlying_value,
-                108 * pre_update_underlying_value / 100
-            );
-            // The rest 2% is the fees(there could be a small rounding error)
-            assert_approx_eq_rel(
-                admin_balance_value,
-                2 * pre_update_underlying_value / 100,
-                0_0000001,
-            );
-            assert_eq!(vault_data.b_rate, b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-
-            // Update the fee mode to take_rate with 20% take rate
-            storage::set_fee(
-                &e,
-                Fee {
-                    rate_type: 0,
-                    rate: 200_0000,
-                },
-            );
-
-            let new_b_rate = 1_200_000_000_000;
-
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, SECONDS_PER_YEAR as u64);
+            // the fee does not depend on the interest earned
+            let new_b_rate = 1_210_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
+            e.jump_time(SECONDS_PER_YEAR as u64 / 2);
             vault_data.update_rate(&e, &pool, &asset);
 
-            // 163636363 accrued fees from this accrual + the pre-existing fees
-            let expected_accrued_fee = 34_5454544;
-
-            assert_eq!(vault_data.admin_balance, expected_accrued_fee);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(
-                vault_data.total_b_tokens,
-                1000_0000000 - expected_accrued_fee
-            );
+            assert_eq!(vault_data.admin_balance, 10_0000000);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 10_0000000);
             assert_eq!(vault_data.b_rate, new_b_rate);
             assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
         });
     }
 
     #[test]
-    fn test_update_rate_no_change() {
+    fn test_update_rate_no_time_elapsed() {
         let e = Env::default();
         e.mock_all_auths();
+        e.set_default_info();
 
         let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 3, 0_0200000, Some(init_b_rate));
+
+        let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
-            let now = e.ledger().timestamp();
             let mut vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                last_update_timestamp: now,
-                admin_balance: 12_0000000,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
+            let new_b_rate = 1_210_000_000_000;
+            mock_client.set_b_rate(&new_b_rate);
             vault_data.update_rate(&e, &pool, &asset);
-            // assert nothing changes
-            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
-            assert_eq!(vault_data.total_shares, vault_data.total_shares);
-            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
-            assert_eq!(vault_data.b_rate, vault_data.b_rate);
-            assert_eq!(vault_data.last_update_timestamp, now);
+
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000);
+            assert_eq!(vault_data.b_rate, new_b_rate);
         });
     }
 
     #[test]
-    fn test_update_rate_different_timestamp_same_brate() {
+    fn test_update_rate_rounds_down() {
         let e = Env::default();
         e.mock_all_auths();
+        e.set_default_info();
 
         let init_b_rate = 1_100_000_000_000;
-
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 3, 0_0200000, Some(init_b_rate));
 
         e.as_contract(&vault_address, || {
-            let now = e.ledger().timestamp();
             let mut vault_data = VaultData {
                 total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                last_update_timestamp: now,
-                admin_balance: 12_0000000,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            e.jump_time(100);
-
+            // 1000 * 0.02 * 5 / 31536000 = 0.0000031709...
+            e.jump_time(5);
             vault_data.update_rate(&e, &pool, &asset);
-            // assert nothing changes
-            assert_eq!(vault_data.admin_balance, vault_data.admin_balance);
-            assert_eq!(vault_data.total_shares, vault_data.total_shares);
-            assert_eq!(vault_data.total_b_tokens, vault_data.total_b_tokens);
-            assert_eq!(vault_data.b_rate, vault_data.b_rate);
-            // assert the timestamp still gets updated
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+
+            assert_eq!(vault_data.admin_balance, 0_0000031);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 0_0000031);
         });
     }
 
     #[test]
-    fn test_update_rate_below_target() {
+    fn test_update_rate_max_fee() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_admin_balance = 10_0000000;
-        let init_b_rate = 1_000_000_000_000;
-        let init_b_supply = 1000_0000000;
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 1, 0_0500000, Some(init_b_rate));
-
-        let mock_client = MockPoolClient::new(&e, &pool);
+            create_test_fee_vault(&e, &bombadil, 3, 1_0000000, Some(init_b_rate));
 
         e.as_contract(&vault_address, || {
             let mut vault_data = VaultData {
-                total_b_tokens: init_b_supply,
+                total_b_tokens: 1000_0000000,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: init_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-
-            // approx 3.65% APR over 1 day
-            let new_b_rate = 1_000_100_000_000;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
-
+            // a 100% fee can't take more than the vault's bTokens
+            e.jump_time(2 * SECONDS_PER_YEAR as u64);
             vault_data.update_rate(&e, &pool, &asset);
 
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // 0.000_100_000 = 0.0365 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 100_000 / 1_000_000_000,
-                10,
-            );
-
-            // no b_token fees applied when below target
-            assert_eq!(vault_data.admin_balance, init_admin_balance);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(vault_data.total_b_tokens, init_b_supply);
+            assert_eq!(vault_data.admin_balance, 1000_0000000);
+            assert_eq!(vault_data.total_b_tokens, 0);
         });
     }
 }
 
 #[cfg(test)]
-mod fixed_rate_tests {
+mod performance_fee_tests {
     use super::*;
-    use crate::testutils::{
-        assert_approx_eq_abs, create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils,
-    };
+    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
     use soroban_sdk::{testutils::Address as _, Address};
 
-    fn update_b_rate_and_time(
-        e: &Env,
-        mock_pool_client: &MockPoolClient,
-        new_b_rate: i128,
-        jump_seconds: u64,
-    ) {
-        mock_pool_client.set_b_rate(&new_b_rate);
-        e.jump_time(jump_seconds);
-    }
-
     #[test]
-    fn test_update_rate_over_target() {
+    fn test_update_rate_no_high_water_mark() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_admin_balance = 10_0000000;
-        let init_b_rate = 1_000_000_000_000;
-        let init_b_supply = 1000_0000000;
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 4, 0_1000000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
             let mut vault_data = VaultData {
-                total_b_tokens: init_b_supply,
+                total_b_tokens: 1000_0000000,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: init_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-
-            // approx 10.95% APR over 1 day
-            let new_b_rate = 1_000_300_000_000;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
-
+            // without a high-water mark, fees are charged on the gains since the last update
+            mock_client.set_b_rate(&1_210_000_000_000);
+            e.jump(5);
             vault_data.update_rate(&e, &pool, &asset);
 
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // 0.000_136_986 = 0.05 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000,
-                10,
-            );
-
-            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
-            let underlying_admin_delta =
-                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
-            // 0.000_163_013 = 0.0595 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_admin_delta,
-                underlying_value_before * 163_013 / 1_000_000_000,
-                10,
-            );
-
-            assert_eq!(
-                vault_data.admin_balance,
-                init_admin_balance + admin_balance_delta
-            );
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(
-                vault_data.total_b_tokens,
-                init_b_supply - admin_balance_delta
-            );
+            assert_eq!(vault_data.admin_balance, 9_0909090);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 9_0909090);
+            assert_eq!(vault_data.total_shares, 1200_0000000);
+            assert_eq!(vault_data.high_water_mark, 999_166_666_758);
         });
     }
 
     #[test]
-    fn test_update_rate_below_target() {
+    fn test_update_rate_recovery_below_high_water_mark() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_admin_balance = 10_0000000;
-        let init_b_rate = 1_000_000_000_000;
-        let init_b_supply = 1000_0000000;
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 4, 0_1000000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
             let mut vault_data = VaultData {
-                total_b_tokens: init_b_supply,
+                total_b_tokens: 1000_0000000,
                 last_update_timestamp: e.ledger().timestamp(),
-                total_shares: 1200_0000000,
+                total_shares: 1000_0000000,
                 b_rate: init_b_rate,
-                admin_balance: init_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 1_100_000_000_000,
+                deficit: 0,
             };
 
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // b_rate drops from a default
+            mock_client.set_b_rate(&1_000_000_000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
 
-            // approx 3.65% APR over 1 day
-            let new_b_rate = 1_000_100_000_000;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.b_rate, 1_000_000_000_000);
+            assert_eq!(vault_data.high_water_mark, 1_100_000_000_000);
 
+            // b_rate recovers, but not past the high-water mark
+            mock_client.set_b_rate(&1_050_000_000_000);
+            e.jump(5);
             vault_data.update_rate(&e, &pool, &asset);
 
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // 0.000_136_986 = 0.05 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000,
-                10,
-            );
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.b_rate, 1_050_000_000_000);
+            assert_eq!(vault_data.high_water_mark, 1_100_000_000_000);
 
-            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
-            let underlying_admin_delta =
-                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
-            // 0.000_036_986 = 0.0135 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_admin_delta,
-                -1 * underlying_value_before * 36_986 / 1_000_000_000,
-                10,
-            );
+            // b_rate recovers to the high-water mark
+            mock_client.set_b_rate(&1_100_000_000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
 
-            assert_eq!(
-                vault_data.admin_balance,
-                init_admin_balance + admin_balance_delta
-            );
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000);
+            assert_eq!(vault_data.high_water_mark, 1_100_000_000_000);
+
+            // fees are only charged on the gains above the high-water mark
+            mock_client.set_b_rate(&1_210_000_000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, 9_0909090);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 9_0909090);
+            assert_eq!(vault_data.high_water_mark, 1_199_000_000_110);
+
+            // the high-water mark is raised after fees, so gains are not charged twice
+            mock_client.set_b_rate(&1_300_000_000_000);
+            e.jump(5);
+            vault_data.update_rate(&e, &pool, &asset);
+
+            assert_eq!(vault_data.admin_balance, 9_0909090 + 6_8601398);
             assert_eq!(
                 vault_data.total_b_tokens,
-                init_b_supply - admin_balance_delta
+                1000_0000000 - 9_0909090 - 6_8601398
             );
         });
     }
 
     #[test]
-    fn test_update_rate_over_target_rounds_down() {
+    fn test_update_rate_resets_high_water_mark_without_shares() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_b_rate = 1_000_000_000_000;
-
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0100000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 4, 0_1000000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
-            let init_timestamp = e.ledger().timestamp();
             let mut vault_data = VaultData {
-                total_b_tokens: 10_0000000,
-                last_update_timestamp: init_timestamp,
-                total_shares: 10_0000000,
+                total_b_tokens: 0,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 0,
                 b_rate: init_b_rate,
                 admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 1_200_000_000_000,
+                deficit: 0,
             };
 
-            // 2% rate over 5s - too small for vault to capture any interest
-            // for the admin on the 1% capped rate with 10 b_tokens
-            let new_b_rate: i128 = 1_000_000_003_171;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 5);
+            mock_client.set_b_rate(&1_210_000_000_000);
+            e.jump(5);
             vault_data.update_rate(&e, &pool, &asset);
 
-            // Assert b_rate change is reflected but rounds fees to zero if less than a stroop amount
-            assert_eq!(vault_data.b_rate, new_b_rate);
             assert_eq!(vault_data.admin_balance, 0);
-            assert_eq!(vault_data.total_b_tokens, 10_0000000);
-            assert_eq!(vault_data.total_shares, 10_0000000);
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
-
-            // Assert with enough b_tokens to capture interest still rounds down
-            // reset vault
-            vault_data.total_b_tokens = 150_0000000;
-            vault_data.b_rate = init_b_rate;
-            vault_data.admin_balance = 0;
-            vault_data.last_update_timestamp = init_timestamp;
-
-            // new_b_rate already applied to pool
-            vault_data.update_rate(&e, &pool, &asset);
-
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 1);
-            assert_eq!(vault_data.total_b_tokens, 150_0000000 - 1);
-            assert_eq!(vault_data.total_shares, 10_0000000);
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+            assert_eq!(vault_data.b_rate, 1_210_000_000_000);
+            assert_eq!(vault_data.high_water_mark, 0);
         });
     }
+}
+
+#[cfg(test)]
+mod fee_policy_tests {
+    use super::*;
+    use crate::storage::Fee;
+    use crate::testutils::{create_test_fee_vault, mockpool::MockPoolClient, EnvTestUtils};
+    use soroban_sdk::{testutils::Address as _, Address};
 
     #[test]
-    fn test_update_rate_below_target_rounds_down() {
+    fn test_update_rate_take_rate_and_capped_rate() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_b_rate = 1_000_000_000_000;
-
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0300000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
-            let init_timestamp = e.ledger().timestamp();
-            let mut vault_data = VaultData {
-                total_b_tokens: 10_0000000,
-                last_update_timestamp: init_timestamp,
-                total_shares: 10_0000000,
-                b_rate: init_b_rate,
-                admin_balance: 100,
-            };
-
-            // 2% rate over 5s - required supplemental b_tokens below 1 stroop
-            let new_b_rate: i128 = 1_000_000_003_171;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 5);
-            vault_data.update_rate(&e, &pool, &asset);
-
-            // Asset admin balance is rounding away from zero
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 99);
-            assert_eq!(vault_data.total_b_tokens, 10_0000000 + 1);
-            assert_eq!(vault_data.total_shares, 10_0000000);
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
-
-            // Assert with more than 1 stroop of supplemental b_tokens still rounds down
-            // reset vault
-            vault_data.total_b_tokens = 100_0000000;
-            vault_data.b_rate = init_b_rate;
-            vault_data.admin_balance = 100;
-            vault_data.last_update_timestamp = init_timestamp;
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.apr_cap = Some(0_0500000);
+            storage::set_fee(&e, fee);
 
-            // new_b_rate already applied to pool
+            let mut vault_data = VaultData {
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
+            };
+
+            // 10% APR, the take rate leaves users above the cap, so the admin takes everything past 5% APR
+            mock_client.set_b_rate(&1_210_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
             vault_data.update_rate(&e, &pool, &asset);
 
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.admin_balance, 98);
-            assert_eq!(vault_data.last_update_timestamp, init_timestamp + 5);
+            assert_eq!(vault_data.admin_balance, 45_4545454);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 45_4545454);
+            assert_eq!(vault_data.b_rate, 1_210_000_000_000);
         });
     }
 
     #[test]
-    fn test_update_rate_below_target_over_admin_balance() {
+    fn test_update_rate_take_rate_below_cap() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        // 100000 short
-        let init_admin_balance = 269_828;
-        let init_b_rate = 1_000_000_000_000;
-        let init_b_supply = 1000_0000000;
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.apr_cap = Some(0_0950000);
+            storage::set_fee(&e, fee);
+
             let mut vault_data = VaultData {
-                total_b_tokens: init_b_supply,
+                total_b_tokens: 1000_0000000,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: init_admin_balance,
+                admin_balance: 0,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-
-            // approx 3.65% APR over 1 day
-            let new_b_rate = 1_000_100_000_000;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
-
+            // 10% APR, the take rate already brings users below the 9.5% cap, so only the take rate is charged
+            mock_client.set_b_rate(&1_210_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
             vault_data.update_rate(&e, &pool, &asset);
 
-            // 369828 taken from the `test_update_rate_below_target` test
-            let underlying_admin_short_amount =
-                vault_data.b_tokens_to_underlying_down(369828 - init_admin_balance);
-
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // 0.000_136_986 = 0.05 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000
-                    - underlying_admin_short_amount,
-                10,
-            );
-
-            // validate that the admin was supposed to be charged the full amount
-            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
-            let underlying_admin_delta =
-                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
-            // 0.000_036_986 = 0.0135 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_admin_delta - underlying_admin_short_amount,
-                -1 * underlying_value_before * 36_986 / 1_000_000_000,
-                10,
-            );
-
-            assert_eq!(vault_data.admin_balance, 0);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(
-                vault_data.total_b_tokens,
-                init_b_supply - admin_balance_delta
-            );
+            assert_eq!(vault_data.admin_balance, 9_0909090);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 - 9_0909090);
         });
     }
 
     #[test]
-    fn test_update_rate_below_target_zero_admin_balance() {
-        // expected behavior with no admin balance is that the vault acts like a capped rate vault
+    fn test_update_rate_take_rate_and_floor_rate() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_admin_balance = 0;
-        let init_b_rate = 1_000_000_000_000;
-        let init_b_supply = 1000_0000000;
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.apr_floor = Some(0_0500000);
+            storage::set_fee(&e, fee);
+
             let mut vault_data = VaultData {
-                total_b_tokens: init_b_supply,
+                total_b_tokens: 1000_0000000,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: init_admin_balance,
+                admin_balance: 100_0000000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-
-            // approx 3.65% APR over 1 day
-            let new_b_rate = 1_000_100_000_000;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
-
+            // ~1.8% APR, the admin supplements the vault up to the 5% floor, net of the take rate
+            mock_client.set_b_rate(&1_120_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
             vault_data.update_rate(&e, &pool, &asset);
 
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // 0.000_100_000 = 0.0365 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 100_000 / 1_000_000_000,
-                10,
-            );
-
-            // no b_token fees applied when below target
-            assert_eq!(vault_data.admin_balance, 0);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(vault_data.total_b_tokens, init_b_supply);
+            assert_eq!(vault_data.admin_balance, 100_0000000 - 31_2500000);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 + 31_2500000);
         });
     }
 
     #[test]
-    fn test_update_rate_below_target_negative_admin_balance() {
-        // this test state should never be reached, but should be handled gracefully (like a capped rate vault)
+    fn test_update_rate_floor_rate_limited_by_admin_balance() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_admin_balance = -269_828;
-        let init_b_rate = 1_000_000_000_000;
-        let init_b_supply = 1000_0000000;
+        let init_b_rate = 1_100_000_000_000;
         let bombadil = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
 
         let mock_client = MockPoolClient::new(&e, &pool);
 
         e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.apr_floor = Some(0_0500000);
+            storage::set_fee(&e, fee);
+
             let mut vault_data = VaultData {
-                total_b_tokens: init_b_supply,
+                total_b_tokens: 1000_0000000,
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
-                admin_balance: init_admin_balance,
+                admin_balance: 10_0000000,
+                fee_recipient_balance: 0,
+                unsplit_fees: 0,
+                high_water_mark: 0,
+                deficit: 0,
             };
 
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            // the admin can pay at most their balance, including the take rate fees from this accrual
+            mock_client.set_b_rate(&1_120_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
+            vault_data.update_rate(&e, &pool, &asset);
 
-            // approx 3.65% APR over 1 day
-            let new_b_rate = 1_000_100_000_000;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+            assert_eq!(vault_data.admin_balance, 0);
+            assert_eq!(vault_data.total_b_tokens, 1000_0000000 + 10_0000000);
+        });
+    }
+}
 
-            vault_data.update_rate(&e, &pool, &asset);
+#[cfg(test)]
+mod transaction_fee_tests {
+    use super::*;
+    use crate::storage::{Fee, FeeRecipient};
+    use crate::testutils::{create_test_fee_vault, EnvTestUtils};
+    use soroban_sdk::{testutils::Address as _, vec, Address};
 
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // 0.000_100_000 = 0.0365 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 100_000 / 1_000_000_000,
-                10,
+    #[test]
+    fn test_preview_transaction_fees() {
+        let e = Env::default();
+        let vault = VaultData {
+            b_rate: 1_000_000_000_000,
+            last_update_timestamp: 0,
+            total_shares: 1000_0000000,
+            total_b_tokens: 1000_0000000,
+            admin_balance: 0,
+            fee_recipient_balance: 0,
+            unsplit_fees: 0,
+            high_water_mark: 0,
+            deficit: 0,
+        };
+
+        // 1% entry fee
+        assert_eq!(
+            vault.preview_deposit(Some(100), 100_0000000),
+            (100_0000000, 99_0000000, 1_0000000)
+        );
+        assert_eq!(
+            vault.preview_mint(Some(100), 99_0000000),
+            (100_0000000, 100_0000000, 1_0000000)
+        );
+
+        // 0.5% exit fee
+        assert_eq!(
+            vault.preview_withdraw(Some(50), 99_5000000),
+            (99_5000000, 100_0000000, 0_5000000)
+        );
+        assert_eq!(
+            vault.preview_redeem(Some(50), 100_0000000),
+            (99_5000000, 99_5000000, 0_5000000)
+        );
+
+        // fees round up
+        assert_eq!(vault.preview_deposit(Some(100), 1_0000001).2, 0_0100001);
+        assert_eq!(vault.preview_redeem(Some(50), 1_0000001).2, 0_0050001);
+
+        // no fee
+        assert_eq!(
+            vault.preview_deposit(None, 100_0000000),
+            (100_0000000, 100_0000000, 0)
+        );
+        assert_eq!(
+            vault.preview_redeem(Some(0), 100_0000000),
+            (100_0000000, 100_0000000, 0)
+        );
+    }
+
+    #[test]
+    fn test_deposit_entry_fee() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.entry_fee = Some(100);
+            storage::set_fee(&e, fee);
+            storage::set_fee_recipients(
+                &e,
+                &vec![
+                    &e,
+                    FeeRecipient {
+                        address: frodo.clone(),
+                        weight: 5000,
+                    },
+                ],
+            );
+            storage::set_vault_data(
+                &e,
+                &VaultData {
+                    total_b_tokens: 1000_0000000,
+                    last_update_timestamp: e.ledger().timestamp(),
+                    total_shares: 1000_0000000,
+                    b_rate: init_b_rate,
+                    admin_balance: 0,
+                    fee_recipient_balance: 0,
+                    unsplit_fees: 0,
+                    high_water_mark: 0,
+                    deficit: 0,
+                },
             );
 
-            // no b_token fees applied when below target
-            assert_eq!(vault_data.admin_balance, init_admin_balance);
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(vault_data.total_b_tokens, init_b_supply);
+            let (b_tokens_minted, shares_minted, fee_b_tokens) =
+                deposit(&e, &pool, &asset, &samwise, 100_0000000);
+            assert_eq!(b_tokens_minted, 100_0000000);
+            assert_eq!(shares_minted, 99_0000000);
+            assert_eq!(fee_b_tokens, 1_0000000);
+
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_shares, 1099_0000000);
+            assert_eq!(vault_data.total_b_tokens, 1099_0000000);
+            assert_eq!(vault_data.admin_balance, 1_0000000);
+            assert_eq!(vault_data.unsplit_fees, 1_0000000);
+            assert_eq!(vault_data.recipient_fee_balance(&e, &frodo), 0_5000000);
+            assert_eq!(storage::get_vault_shares(&e, &samwise), 99_0000000);
         });
     }
 
     #[test]
-    fn test_update_rate_over_target_negative_admin_balance() {
+    fn test_mint_entry_fee() {
         let e = Env::default();
         e.mock_all_auths();
         e.set_default_info();
 
-        let init_admin_balance = -10_0000000;
         let init_b_rate = 1_000_000_000_000;
-        let init_b_supply = 1000_0000000;
         let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
         let (vault_address, pool, asset) =
-            create_test_fee_vault(&e, &bombadil, 2, 0_0500000, Some(init_b_rate));
-
-        let mock_client = MockPoolClient::new(&e, &pool);
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
 
         e.as_contract(&vault_address, || {
-            let mut vault_data = VaultData {
-                total_b_tokens: init_b_supply,
-                last_update_timestamp: e.ledger().timestamp(),
-                total_shares: 1200_0000000,
-                b_rate: init_b_rate,
-                admin_balance: init_admin_balance,
-            };
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.entry_fee = Some(100);
+            storage::set_fee(&e, fee);
+            storage::set_vault_data(
+                &e,
+                &VaultData {
+                    total_b_tokens: 1000_0000000,
+                    last_update_timestamp: e.ledger().timestamp(),
+                    total_shares: 1000_0000000,
+                    b_rate: init_b_rate,
+                    admin_balance: 0,
+                    fee_recipient_balance: 0,
+                    unsplit_fees: 0,
+                    high_water_mark: 0,
+                    deficit: 0,
+                },
+            );
 
-            let underlying_value_before =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
+            let (amount, b_tokens_minted, fee_b_tokens) =
+                mint(&e, &pool, &asset, &samwise, 99_0000000);
+            assert_eq!(amount, 100_0000000);
+            assert_eq!(b_tokens_minted, 100_0000000);
+            assert_eq!(fee_b_tokens, 1_0000000);
 
-            // approx 10.95% APR over 1 day
-            let new_b_rate = 1_000_300_000_000;
-            update_b_rate_and_time(&e, &mock_client, new_b_rate, 86400);
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_shares, 1099_0000000);
+            assert_eq!(vault_data.total_b_tokens, 1099_0000000);
+            assert_eq!(vault_data.admin_balance, 1_0000000);
+            assert_eq!(storage::get_vault_shares(&e, &samwise), 99_0000000);
+        });
+    }
 
-            vault_data.update_rate(&e, &pool, &asset);
+    #[test]
+    fn test_withdraw_exit_fee() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
 
-            let underlying_value_after =
-                vault_data.b_tokens_to_underlying_down(vault_data.total_b_tokens);
-            // 0.000_136_986 = 0.05 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_value_after,
-                underlying_value_before + underlying_value_before * 136_986 / 1_000_000_000,
-                10,
-            );
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
 
-            let admin_balance_delta = vault_data.admin_balance - init_admin_balance;
-            let underlying_admin_delta =
-                vault_data.b_tokens_to_underlying_down(admin_balance_delta);
-            // 0.000_163_013 = 0.0595 * 1 / 365
-            assert_approx_eq_abs(
-                underlying_admin_delta,
-                underlying_value_before * 163_013 / 1_000_000_000,
-                10,
+        e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.exit_fee = Some(50);
+            storage::set_fee(&e, fee);
+            storage::set_vault_data(
+                &e,
+                &VaultData {
+                    total_b_tokens: 1000_0000000,
+                    last_update_timestamp: e.ledger().timestamp(),
+                    total_shares: 1000_0000000,
+                    b_rate: init_b_rate,
+                    admin_balance: 0,
+                    fee_recipient_balance: 0,
+                    unsplit_fees: 0,
+                    high_water_mark: 0,
+                    deficit: 0,
+                },
             );
+            storage::set_vault_shares(&e, &samwise, 200_0000000);
 
-            assert_eq!(
-                vault_data.admin_balance,
-                init_admin_balance + admin_balance_delta
-            );
-            assert_eq!(vault_data.total_shares, 1200_000_0000);
-            assert_eq!(vault_data.b_rate, new_b_rate);
-            assert_eq!(vault_data.last_update_timestamp, e.ledger().timestamp());
-            assert_eq!(
-                vault_data.total_b_tokens,
-                init_b_supply - admin_balance_delta
+            let (underlying_withdrawn, b_tokens_burnt, shares_burnt, fee_b_tokens) =
+                withdraw(&e, &pool, &asset, &samwise, 99_5000000);
+            assert_eq!(underlying_withdrawn, 99_5000000);
+            assert_eq!(b_tokens_burnt, 99_5000000);
+            assert_eq!(shares_burnt, 100_0000000);
+            assert_eq!(fee_b_tokens, 0_5000000);
+
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_shares, 900_0000000);
+            assert_eq!(vault_data.total_b_tokens, 900_0000000);
+            assert_eq!(vault_data.admin_balance, 0_5000000);
+            assert_eq!(storage::get_vault_shares(&e, &samwise), 100_0000000);
+
+            // withdrawing more than the balance redeems all shares, net of the exit fee
+            let (underlying_withdrawn, b_tokens_burnt, shares_burnt, fee_b_tokens) =
+                withdraw(&e, &pool, &asset, &samwise, i64::MAX as i128);
+            assert_eq!(underlying_withdrawn, 99_5000000);
+            assert_eq!(b_tokens_burnt, 99_5000000);
+            assert_eq!(shares_burnt, 100_0000000);
+            assert_eq!(fee_b_tokens, 0_5000000);
+
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_shares, 800_0000000);
+            assert_eq!(vault_data.total_b_tokens, 800_0000000);
+            assert_eq!(vault_data.admin_balance, 1_0000000);
+            assert_eq!(storage::get_vault_shares(&e, &samwise), 0);
+        });
+    }
+
+    #[test]
+    fn test_redeem_exit_fee() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let (vault_address, pool, asset) =
+            create_test_fee_vault(&e, &bombadil, 0, 0_1000000, Some(init_b_rate));
+
+        e.as_contract(&vault_address, || {
+            let mut fee = Fee::from_rate_type(&e, 0, 0_1000000);
+            fee.exit_fee = Some(50);
+            storage::set_fee(&e, fee);
+            storage::set_vault_data(
+                &e,
+                &VaultData {
+                    total_b_tokens: 1000_0000000,
+                    last_update_timestamp: e.ledger().timestamp(),
+                    total_shares: 1000_0000000,
+                    b_rate: init_b_rate,
+                    admin_balance: 0,
+                    fee_recipient_balance: 0,
+                    unsplit_fees: 0,
+                    high_water_mark: 0,
+                    deficit: 0,
+                },
             );
+            storage::set_vault_shares(&e, &samwise, 100_0000000);
+
+            let (underlying_withdrawn, b_tokens_burnt, fee_b_tokens) =
+                redeem(&e, &pool, &asset, &samwise, 100_0000000);
+            assert_eq!(underlying_withdrawn, 99_5000000);
+            assert_eq!(b_tokens_burnt, 99_5000000);
+            assert_eq!(fee_b_tokens, 0_5000000);
+
+            let vault_data = storage::get_vault_data(&e);
+            assert_eq!(vault_data.total_shares, 900_0000000);
+            assert_eq!(vault_data.total_b_tokens, 900_0000000);
+            assert_eq!(vault_data.admin_balance, 0_5000000);
+            assert_eq!(storage::get_vault_shares(&e, &samwise), 0);
         });
     }
 }